@@ -0,0 +1,327 @@
+//! Sound effects and background music synthesized at startup into in-memory
+//! sample buffers, so the web build ships no audio files.
+//!
+//! Games request effects by writing [`PlaySfx`] messages and keep
+//! [`MusicTempo`] in step with their tick rate.
+
+use std::f32::consts::TAU;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::audio::{AddAudioSource, AudioPlugin, Decodable, Source, Volume};
+use bevy::prelude::*;
+
+use crate::settings::{Settings, SettingsPlugin};
+
+const SAMPLE_RATE: u32 = 22_050;
+const VOLUME_STEP: f32 = 0.1;
+const MUSIC_BPM: f32 = 132.0;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SettingsPlugin>() {
+            app.add_plugins(SettingsPlugin);
+        }
+
+        app.add_message::<PlaySfx>().init_resource::<MusicTempo>();
+
+        // Headless apps (tests, tools) have no audio output to play through.
+        if !app.is_plugin_added::<AudioPlugin>() {
+            return;
+        }
+
+        app.add_audio_source::<SynthSound>()
+            .add_systems(Startup, (synthesize_sounds, start_music).chain())
+            .add_systems(Update, (adjust_volume, play_sfx, update_music));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sfx {
+    Eat,
+    Turn,
+    Death,
+    PowerUp,
+    LevelUp,
+}
+
+#[derive(Message, Clone, Copy, Debug)]
+pub struct PlaySfx(pub Sfx);
+
+/// Playback speed of the background music; 1.0 is the base tempo.
+#[derive(Resource)]
+pub struct MusicTempo(pub f32);
+
+impl Default for MusicTempo {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// A mono sample buffer played through Bevy's audio output.
+#[derive(Asset, TypePath, Clone)]
+pub struct SynthSound {
+    samples: Arc<[f32]>,
+}
+
+pub struct SynthDecoder {
+    samples: Arc<[f32]>,
+    index: usize,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.index).copied();
+        self.index += 1;
+        sample
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.index))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.samples.len() as f32 / SAMPLE_RATE as f32))
+    }
+}
+
+impl Decodable for SynthSound {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> SynthDecoder {
+        SynthDecoder {
+            samples: self.samples.clone(),
+            index: 0,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct SoundBank {
+    eat: Handle<SynthSound>,
+    turn: Handle<SynthSound>,
+    death: Handle<SynthSound>,
+    power_up: Handle<SynthSound>,
+    level_up: Handle<SynthSound>,
+    music: Handle<SynthSound>,
+}
+
+impl SoundBank {
+    fn get(&self, sfx: Sfx) -> Handle<SynthSound> {
+        match sfx {
+            Sfx::Eat => self.eat.clone(),
+            Sfx::Turn => self.turn.clone(),
+            Sfx::Death => self.death.clone(),
+            Sfx::PowerUp => self.power_up.clone(),
+            Sfx::LevelUp => self.level_up.clone(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Music;
+
+#[derive(Clone, Copy)]
+enum Wave {
+    Sine,
+    Square,
+    Triangle,
+    Saw,
+    Noise,
+}
+
+// Single voice whose pitch glides exponentially from `from_hz` to `to_hz`, with
+// a short attack and a linear release to silence.
+fn voice(wave: Wave, from_hz: f32, to_hz: f32, seconds: f32, volume: f32) -> Vec<f32> {
+    let len = (seconds * SAMPLE_RATE as f32) as usize;
+    let attack = (0.005 * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0.0f32;
+    let mut noise = 0x1234_5678u32;
+
+    (0..len)
+        .map(|i| {
+            let progress = i as f32 / len as f32;
+            let freq = from_hz * (to_hz / from_hz).powf(progress);
+            phase = (phase + freq / SAMPLE_RATE as f32).fract();
+
+            let value = match wave {
+                Wave::Sine => (phase * TAU).sin(),
+                Wave::Square => {
+                    if phase < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Wave::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+                Wave::Saw => 2.0 * phase - 1.0,
+                Wave::Noise => {
+                    noise ^= noise << 13;
+                    noise ^= noise >> 17;
+                    noise ^= noise << 5;
+                    noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+                }
+            };
+            let envelope = if i < attack {
+                i as f32 / attack as f32
+            } else {
+                1.0 - progress
+            };
+            value * envelope * volume
+        })
+        .collect()
+}
+
+fn mix_into(buffer: &mut Vec<f32>, offset: usize, samples: &[f32]) {
+    if buffer.len() < offset + samples.len() {
+        buffer.resize(offset + samples.len(), 0.0);
+    }
+    for (out, sample) in buffer[offset..].iter_mut().zip(samples) {
+        *out += sample;
+    }
+}
+
+fn midi_to_hz(note: u8) -> f32 {
+    440.0 * 2f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+fn arpeggio(notes: &[u8], note_seconds: f32, wave: Wave, volume: f32) -> Vec<f32> {
+    let step = (note_seconds * SAMPLE_RATE as f32) as usize;
+    let mut buffer = Vec::new();
+    for (i, &note) in notes.iter().enumerate() {
+        let hz = midi_to_hz(note);
+        mix_into(&mut buffer, i * step, &voice(wave, hz, hz, note_seconds * 1.5, volume));
+    }
+    buffer
+}
+
+fn synthesize_music() -> Vec<f32> {
+    // Eighth-note lead over quarter-note bass; 0 is a rest.
+    const LEAD: [u8; 32] = [
+        72, 0, 76, 79, 76, 0, 72, 74, 76, 0, 74, 72, 69, 0, 67, 0, //
+        72, 0, 76, 79, 81, 0, 79, 76, 74, 0, 76, 74, 72, 0, 0, 0,
+    ];
+    const BASS: [u8; 16] = [48, 48, 45, 45, 41, 41, 43, 43, 48, 48, 45, 45, 41, 43, 48, 48];
+
+    let eighth = 60.0 / MUSIC_BPM / 2.0;
+    let step = (eighth * SAMPLE_RATE as f32) as usize;
+    let mut buffer = vec![0.0; step * LEAD.len()];
+
+    for (i, &note) in LEAD.iter().enumerate().filter(|(_, &note)| note != 0) {
+        let hz = midi_to_hz(note);
+        mix_into(&mut buffer, i * step, &voice(Wave::Square, hz, hz, eighth * 0.9, 0.08));
+    }
+    for (i, &note) in BASS.iter().enumerate() {
+        let hz = midi_to_hz(note);
+        mix_into(&mut buffer, i * 2 * step, &voice(Wave::Triangle, hz, hz, eighth * 1.8, 0.25));
+    }
+
+    // Keep the loop seamless: release tails past the end wrap to the start.
+    let loop_len = step * LEAD.len();
+    let tail: Vec<f32> = buffer.drain(loop_len..).collect();
+    for (out, sample) in buffer.iter_mut().zip(tail) {
+        *out += sample;
+    }
+    buffer
+}
+
+fn synthesize_sounds(mut commands: Commands, mut sounds: ResMut<Assets<SynthSound>>) {
+    let mut add = |samples: Vec<f32>| {
+        sounds.add(SynthSound {
+            samples: samples.into(),
+        })
+    };
+
+    let mut death = voice(Wave::Saw, 420.0, 60.0, 0.7, 0.35);
+    mix_into(&mut death, 0, &voice(Wave::Noise, 1.0, 1.0, 0.35, 0.15));
+
+    commands.insert_resource(SoundBank {
+        eat: add(voice(Wave::Square, 520.0, 1040.0, 0.09, 0.25)),
+        turn: add(voice(Wave::Sine, 300.0, 240.0, 0.03, 0.2)),
+        death: add(death),
+        power_up: add(arpeggio(&[67, 71, 74, 79], 0.06, Wave::Square, 0.2)),
+        level_up: add(arpeggio(&[72, 76, 79, 84], 0.09, Wave::Triangle, 0.35)),
+        music: add(synthesize_music()),
+    });
+}
+
+fn start_music(mut commands: Commands, bank: Res<SoundBank>, settings: Res<Settings>) {
+    commands.spawn((
+        AudioPlayer(bank.music.clone()),
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(settings.audio.music_volume())),
+        Music,
+    ));
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    mut requests: MessageReader<PlaySfx>,
+    bank: Res<SoundBank>,
+    settings: Res<Settings>,
+) {
+    let volume = settings.audio.sfx_volume();
+    for PlaySfx(sfx) in requests.read() {
+        if volume > 0.0 {
+            commands.spawn((
+                AudioPlayer(bank.get(*sfx)),
+                PlaybackSettings::DESPAWN.with_volume(Volume::Linear(volume)),
+            ));
+        }
+    }
+}
+
+fn update_music(
+    settings: Res<Settings>,
+    tempo: Res<MusicTempo>,
+    mut sinks: Query<&mut AudioSink, With<Music>>,
+) {
+    for mut sink in sinks.iter_mut() {
+        if settings.is_changed() {
+            sink.set_volume(Volume::Linear(settings.audio.music_volume()));
+        }
+        if (sink.speed() - tempo.0).abs() > f32::EPSILON {
+            sink.set_speed(tempo.0);
+        }
+    }
+}
+
+// Minus/Equal change the master volume and M toggles the music.
+fn adjust_volume(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    let audio = &settings.audio;
+    let master = if keyboard.just_pressed(KeyCode::Minus) {
+        (audio.master - VOLUME_STEP).max(0.0)
+    } else if keyboard.just_pressed(KeyCode::Equal) {
+        (audio.master + VOLUME_STEP).min(1.0)
+    } else {
+        audio.master
+    };
+    let music = if keyboard.just_pressed(KeyCode::KeyM) {
+        if audio.music > 0.0 {
+            0.0
+        } else {
+            crate::settings::AudioSettings::default().music
+        }
+    } else {
+        audio.music
+    };
+
+    if master != audio.master || music != audio.music {
+        settings.audio.master = master;
+        settings.audio.music = music;
+    }
+}
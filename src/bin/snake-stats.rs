@@ -0,0 +1,26 @@
+//! Adds up the telemetry logs the snake games write with the setting on: a
+//! histogram of final scores per game, and a heatmap of where the snake died
+//! per game and board size. Reads the data directory's logs, or those in the
+//! directory given.
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    use std::path::PathBuf;
+    use tiny_games::telemetry::{telemetry_dir, Report};
+
+    let dir = std::env::args().nth(1).map_or_else(telemetry_dir, PathBuf::from);
+    match Report::from_dir(&dir) {
+        Ok(report) if !report.games.is_empty() => print!("{report}"),
+        Ok(_) => {
+            eprintln!("no telemetry in {}; turn it on in a snake game's settings", dir.display());
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("failed to read {}: {err}", dir.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
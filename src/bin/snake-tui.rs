@@ -0,0 +1,192 @@
+//! snake-2d in a terminal, for playing over SSH without a graphics stack. The
+//! real game plugin runs headless, so the rules, tick timing, settings and
+//! high scores are the ones the windowed version uses.
+
+#[cfg(unix)]
+fn main() -> std::io::Result<()> {
+    terminal::run()
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("snake-tui needs a Unix terminal");
+    std::process::exit(1);
+}
+
+#[cfg(unix)]
+mod terminal {
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use std::io::{self, Read, Write};
+    use std::time::{Duration, Instant};
+    use tiny_games::games::snake_2d::{BoardSnapshot, Snake2dPlugin};
+    use tiny_games::high_scores::HighScores;
+    use tiny_games::launcher::{ActiveGame, AddMiniGame, MiniGame};
+    use tiny_games::menu::GameState;
+    use tiny_games::settings::{Settings, SettingsPlugin};
+    use tiny_games::theme::{Theme, ThemePlugin};
+    use tiny_games::tui;
+
+    const FRAME_TIME: Duration = Duration::from_millis(16);
+
+    /// Puts the terminal in raw, non-blocking mode on the alternate screen and
+    /// restores it when dropped.
+    struct RawMode(libc::termios);
+
+    impl RawMode {
+        fn enable() -> io::Result<Self> {
+            // SAFETY: tcgetattr/tcsetattr only read and write the termios struct we own.
+            unsafe {
+                let mut original: libc::termios = std::mem::zeroed();
+                if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let mut raw = original;
+                libc::cfmakeraw(&mut raw);
+                raw.c_cc[libc::VMIN] = 0;
+                raw.c_cc[libc::VTIME] = 0;
+                if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+                Ok(Self(original))
+            }
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+            let _ = io::stdout().flush();
+            // SAFETY: restores the settings read in `enable`.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+            }
+        }
+    }
+
+    fn terminal_size() -> (u16, u16) {
+        // SAFETY: TIOCGWINSZ fills in the winsize struct we pass.
+        unsafe {
+            let mut size: libc::winsize = std::mem::zeroed();
+            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0 {
+                (size.ws_col, size.ws_row)
+            } else {
+                (80, 24)
+            }
+        }
+    }
+
+    enum Input {
+        Key(KeyCode),
+        Quit,
+    }
+
+    fn parse_input(bytes: &[u8]) -> Vec<Input> {
+        let mut input = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                // Arrow keys arrive as ESC [ A-D, or ESC O A-D in application mode.
+                0x1b if i + 2 < bytes.len() && matches!(bytes[i + 1], b'[' | b'O') => {
+                    let key = match bytes[i + 2] {
+                        b'A' => Some(KeyCode::ArrowUp),
+                        b'B' => Some(KeyCode::ArrowDown),
+                        b'C' => Some(KeyCode::ArrowRight),
+                        b'D' => Some(KeyCode::ArrowLeft),
+                        _ => None,
+                    };
+                    input.extend(key.map(Input::Key));
+                    i += 2;
+                }
+                0x1b | 0x03 | b'q' | b'Q' => input.push(Input::Quit),
+                b'\r' | b'\n' => input.push(Input::Key(KeyCode::Enter)),
+                b' ' => input.push(Input::Key(KeyCode::Space)),
+                byte => {
+                    let key = match byte.to_ascii_lowercase() {
+                        b'w' => Some(KeyCode::KeyW),
+                        b'a' => Some(KeyCode::KeyA),
+                        b's' => Some(KeyCode::KeyS),
+                        b'd' => Some(KeyCode::KeyD),
+                        b't' => Some(KeyCode::KeyT),
+                        _ => None,
+                    };
+                    input.extend(key.map(Input::Key));
+                }
+            }
+            i += 1;
+        }
+        input
+    }
+
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_plugins((SettingsPlugin, ThemePlugin))
+            .add_mini_game(Snake2dPlugin);
+        app.finish();
+        app.cleanup();
+
+        // Pick the game, then start a round on the next update.
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<ActiveGame>>()
+            .set(Snake2dPlugin::active());
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app
+    }
+
+    pub fn run() -> io::Result<()> {
+        let mut app = headless_app();
+        let _raw_mode = RawMode::enable()?;
+        let mut stdin = io::stdin();
+        let mut stdout = io::stdout();
+        let mut last_size = None;
+        let mut buffer = [0; 64];
+
+        loop {
+            let started = Instant::now();
+            let read = stdin.read(&mut buffer).unwrap_or(0);
+            let mut keys = Vec::new();
+            for input in parse_input(&buffer[..read]) {
+                match input {
+                    Input::Quit => return Ok(()),
+                    Input::Key(key) => keys.push(key),
+                }
+            }
+
+            // Terminals only report presses, so each key is held for a single frame.
+            let world = app.world_mut();
+            let mut keyboard = world.resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.release_all();
+            keyboard.clear();
+            for &key in &keys {
+                keyboard.press(key);
+            }
+            if keys.contains(&KeyCode::Enter) && world.resource::<BoardSnapshot>().game_over {
+                world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+            }
+            app.update();
+
+            let size = terminal_size();
+            if last_size != Some(size) {
+                write!(stdout, "\x1b[2J")?;
+                last_size = Some(size);
+            }
+            let world = app.world();
+            let mode = world.resource::<Settings>().gameplay.mode;
+            let best = world.resource::<HighScores>().best(mode).unwrap_or(0);
+            let frame = tui::render(world.resource::<BoardSnapshot>(), world.resource::<Theme>(), best, size.0, size.1);
+            stdout.write_all(frame.as_bytes())?;
+            stdout.flush()?;
+
+            std::thread::sleep(FRAME_TIME.saturating_sub(started.elapsed()));
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Copying text for the player to paste elsewhere: the system clipboard on
+//! native builds, the browser's on the web.
+
+/// Puts `text` on the clipboard. The browser answers later, so on the web only
+/// a missing clipboard is reported.
+pub fn copy(text: &str) -> Result<(), String> {
+    backend::copy(text)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::cell::RefCell;
+
+    thread_local! {
+        // On X11 and Wayland the text is served from this process, so the
+        // clipboard is kept around rather than dropped after each copy.
+        static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = const { RefCell::new(None) };
+    }
+
+    pub fn copy(text: &str) -> Result<(), String> {
+        CLIPBOARD.with_borrow_mut(|clipboard| {
+            if clipboard.is_none() {
+                *clipboard = Some(arboard::Clipboard::new().map_err(|err| err.to_string())?);
+            }
+            clipboard.as_mut().unwrap().set_text(text).map_err(|err| err.to_string())
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    pub fn copy(text: &str) -> Result<(), String> {
+        let window = web_sys::window().ok_or("no browser window")?;
+        // Resolves once the browser has the text; there is nothing to do then.
+        let _ = window.navigator().clipboard().write_text(text);
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+//! Daily challenges: a round whose rules and food all follow from the date, so
+//! everyone playing on the same day plays the same game. Days are UTC. The first
+//! attempt on the day itself is scored and kept through [`crate::storage`];
+//! any recent day can be replayed for practice.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::random::GameRng;
+use crate::settings::{BoardSize, Speed};
+use crate::storage;
+
+/// Past days offered for practice besides today.
+pub const PRACTICE_DAYS: i64 = 6;
+// The share grid is at most this many emoji across and down.
+const SHARE_GRID_SIZE: i32 = 10;
+
+pub const HEAD_EMOJI: char = '🟨';
+pub const BODY_EMOJI: char = '🟩';
+pub const FOOD_EMOJI: char = '🟥';
+pub const EMPTY_EMOJI: char = '⬛';
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn today() -> Self {
+        Self::from_days(clock::unix_days())
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        // Howard Hinnant's civil_from_days, with years starting in March.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        Self {
+            year: (year_of_era + era * 400 + i64::from(month <= 2)) as i32,
+            month: month as u32,
+            day: (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32,
+        }
+    }
+
+    /// Days since 1970-01-01.
+    pub fn days(self) -> i64 {
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+        let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    pub fn plus_days(self, days: i64) -> Self {
+        Self::from_days(self.days() + days)
+    }
+
+    /// Seeds the day's food, e.g. 20261019.
+    pub fn seed(self) -> u64 {
+        (i64::from(self.year) * 10_000 + i64::from(self.month) * 100 + i64::from(self.day)) as u64
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The rules everyone plays under on a given day. The mode is always classic.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DailyRules {
+    pub board_size: BoardSize,
+    pub speed: Speed,
+}
+
+impl DailyRules {
+    pub fn for_date(date: Date) -> Self {
+        // A stream of their own, so the food still starts from the date's seed.
+        let mut rng = GameRng::seeded(!date.seed());
+        Self {
+            board_size: BoardSize::ALL[rng.random_range(0..BoardSize::ALL.len())],
+            speed: Speed::ALL[rng.random_range(0..Speed::ALL.len())],
+        }
+    }
+}
+
+/// A daily round: which day, and whether it is that day's scored attempt.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DailyRound {
+    pub date: Date,
+    pub scored: bool,
+}
+
+/// The daily round the menu asked for; the game takes it as the round starts.
+#[derive(Resource, Default, Debug)]
+pub struct DailyRequest(pub Option<DailyRound>);
+
+/// How a scored attempt ended.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct DailyResult {
+    pub score: u32,
+    pub length: u32,
+    pub seconds: u32,
+    /// The final board as rows of emoji, top row first.
+    pub grid: Vec<String>,
+}
+
+impl DailyResult {
+    pub fn summary(&self) -> String {
+        format!(
+            "Score {} · Length {} · {}:{:02}",
+            self.score,
+            self.length,
+            self.seconds / 60,
+            self.seconds % 60
+        )
+    }
+
+    pub fn share_text(&self, game_name: &str, date: Date) -> String {
+        format!("{game_name} daily {date}\n{}\n{}", self.summary(), self.grid.join("\n"))
+    }
+}
+
+/// A `width` by `height` board as emoji rows, top row first. Boards wider than
+/// the share grid are shrunk: each emoji covers a square block of cells and
+/// shows the head, body or food in it, in that order.
+pub fn emoji_grid(width: i32, height: i32, snake: &[IVec2], food: Option<IVec2>) -> Vec<String> {
+    let block = ((width.max(height) + SHARE_GRID_SIZE - 1) / SHARE_GRID_SIZE).max(1);
+    let columns = (width + block - 1) / block;
+    let rows = (height + block - 1) / block;
+    let in_block = |cell: IVec2, column: i32, row: i32| cell.x / block == column && cell.y / block == row;
+
+    (0..rows)
+        .rev()
+        .map(|row| {
+            (0..columns)
+                .map(|column| {
+                    if snake.first().is_some_and(|&head| in_block(head, column, row)) {
+                        HEAD_EMOJI
+                    } else if snake.iter().any(|&cell| in_block(cell, column, row)) {
+                        BODY_EMOJI
+                    } else if food.is_some_and(|food| in_block(food, column, row)) {
+                        FOOD_EMOJI
+                    } else {
+                        EMPTY_EMOJI
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct Attempt {
+    date: Date,
+    result: Option<DailyResult>,
+}
+
+/// The active game's scored daily attempts. The default log is not persisted,
+/// which keeps headless apps away from the player's results.
+#[derive(Resource, Default, Debug)]
+pub struct DailyLog {
+    key: Option<String>,
+    attempts: Vec<Attempt>,
+}
+
+impl DailyLog {
+    pub fn load(game_id: &str) -> Self {
+        let key = format!("{game_id}-daily");
+        Self {
+            attempts: storage::load(&key).unwrap_or_default(),
+            key: Some(key),
+        }
+    }
+
+    fn attempt(&self, date: Date) -> Option<&Attempt> {
+        self.attempts.iter().find(|attempt| attempt.date == date)
+    }
+
+    pub fn attempted(&self, date: Date) -> bool {
+        self.attempt(date).is_some()
+    }
+
+    /// How the day's scored attempt went, once it has ended.
+    pub fn result(&self, date: Date) -> Option<&DailyResult> {
+        self.attempt(date).and_then(|attempt| attempt.result.as_ref())
+    }
+
+    /// Uses up the day's scored attempt. Returns false if it was already used.
+    pub fn start(&mut self, date: Date) -> bool {
+        if self.attempted(date) {
+            return false;
+        }
+        self.attempts.push(Attempt { date, result: None });
+        self.save();
+        true
+    }
+
+    pub fn finish(&mut self, date: Date, result: DailyResult) {
+        if let Some(attempt) = self.attempts.iter_mut().find(|attempt| attempt.date == date) {
+            attempt.result = Some(result);
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        if let Some(key) = &self.key {
+            storage::save(key, &self.attempts);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod clock {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    pub fn unix_days() -> i64 {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        (seconds / 86_400) as i64
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod clock {
+    pub fn unix_days() -> i64 {
+        (js_sys::Date::now() / 86_400_000.0).floor() as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_round_trip_through_day_numbers() {
+        let date = Date { year: 2024, month: 2, day: 29 };
+        assert_eq!(Date::from_days(0), Date { year: 1970, month: 1, day: 1 });
+        assert_eq!(Date::from_days(date.days()), date);
+        assert_eq!(date.plus_days(1), Date { year: 2024, month: 3, day: 1 });
+        assert_eq!(Date { year: 2026, month: 1, day: 1 }.plus_days(-1).to_string(), "2025-12-31");
+        assert_eq!(date.seed(), 20240229);
+    }
+
+    #[test]
+    fn a_day_has_one_scored_attempt() {
+        let today = Date { year: 2026, month: 10, day: 19 };
+        let mut log = DailyLog::default();
+        assert!(log.start(today));
+        assert!(!log.start(today));
+        assert!(log.result(today).is_none());
+
+        let result = DailyResult {
+            score: 120,
+            length: 15,
+            seconds: 83,
+            grid: vec![],
+        };
+        log.finish(today, result.clone());
+        assert_eq!(log.result(today), Some(&result));
+        assert!(!log.attempted(today.plus_days(-1)));
+        assert_eq!(result.summary(), "Score 120 · Length 15 · 1:23");
+    }
+
+    #[test]
+    fn large_boards_share_a_shrunk_grid() {
+        let snake = [IVec2::new(29, 29), IVec2::new(28, 29), IVec2::new(27, 29), IVec2::new(26, 29)];
+        let grid = emoji_grid(30, 30, &snake, Some(IVec2::new(0, 0)));
+        assert_eq!(grid.len(), 10);
+        assert_eq!(grid[0], format!("{}{}{}", EMPTY_EMOJI.to_string().repeat(8), BODY_EMOJI, HEAD_EMOJI));
+        assert_eq!(grid[9], format!("{}{}", FOOD_EMOJI, EMPTY_EMOJI.to_string().repeat(9)));
+    }
+}
@@ -0,0 +1,455 @@
+//! Developer tools for the snake games. F3 toggles an overlay with the frame
+//! rate, tick count, entity and asset counts, followed by whatever the active
+//! game adds to [`DebugLines`]. During a round the backquote key opens a console;
+//! `godmode`, `help` and `clear` are handled as they are typed, and the other
+//! commands become [`ConsoleCommand`] messages that [`run_console_commands`]
+//! carries out on the game's snake through its [`ConsoleTarget`].
+
+use bevy::ecs::system::{StaticSystemParam, SystemParam};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystems};
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::menu::GameState;
+use crate::random::GameRng;
+use crate::snake_events::{GameStarted, SnakeMoved};
+use crate::theme::Theme;
+
+const CONSOLE_LINES: usize = 8;
+const FONT_SIZE: f32 = 16.0;
+const MAX_GROW: u32 = 1000;
+const MIN_SECONDS_PER_MOVE: f32 = 0.01;
+const MAX_SECONDS_PER_MOVE: f32 = 5.0;
+const HELP: &[&str] = &[
+    "grow N: add N segments at the tail, up to 1000",
+    "spawn_food [FACE] X Y: move the food",
+    "set_speed SECONDS: time per move, 0.01 to 5, until the next level",
+    "godmode: toggle dying",
+    "seed N: reseed the food",
+    "teleport [FACE] X Y: move the head, with the body trailing behind",
+    "clear: empty the console",
+];
+
+pub struct DevConsolePlugin;
+
+impl Plugin for DevConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<KeyboardInput>()
+            .add_message::<GameStarted>()
+            .add_message::<SnakeMoved>()
+            .add_message::<ConsoleCommand>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<DebugOverlay>()
+            .init_resource::<DebugLines>()
+            .init_resource::<DevConsole>()
+            .init_resource::<Cheats>()
+            .add_systems(PreUpdate, type_into_console.after(InputSystems))
+            .add_systems(OnEnter(GameState::Playing), start_round)
+            .add_systems(OnExit(GameState::Playing), close_console)
+            .add_systems(Update, count_ticks)
+            .add_systems(PostUpdate, (draw_overlay, draw_console));
+    }
+}
+
+/// What F3 shows.
+#[derive(Resource, Default, Debug)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    /// Moves made this round.
+    pub ticks: u32,
+    fps: f32,
+}
+
+/// Run condition for systems that only feed the overlay.
+pub fn overlay_visible(overlay: Res<DebugOverlay>) -> bool {
+    overlay.visible
+}
+
+/// Lines the active game adds to the overlay, refilled every frame it is shown.
+#[derive(Resource, Default, Debug)]
+pub struct DebugLines(pub Vec<String>);
+
+/// Developer switches that outlast a round.
+#[derive(Resource, Default, Debug)]
+pub struct Cheats {
+    /// Deadly moves are skipped instead of ending the round.
+    pub god_mode: bool,
+    /// Set once god mode or a console command has touched the current round,
+    /// which then stays off the record: no high score, daily result, stats,
+    /// telemetry or save to continue.
+    pub cheated: bool,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct DevConsole {
+    pub open: bool,
+    input: String,
+    log: Vec<String>,
+}
+
+impl DevConsole {
+    pub fn reply(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        let excess = self.log.len().saturating_sub(CONSOLE_LINES);
+        self.log.drain(..excess);
+    }
+
+    pub fn log(&self) -> &[String] {
+        &self.log
+    }
+
+    /// Runs one line as typed into the console.
+    pub fn submit(&mut self, line: &str, cheats: &mut Cheats, commands: &mut MessageWriter<ConsoleCommand>) {
+        self.reply(format!("> {line}"));
+        match line.trim() {
+            "" => {}
+            "help" => HELP.iter().for_each(|&help| self.reply(help)),
+            "clear" => self.log.clear(),
+            "godmode" => {
+                cheats.god_mode = !cheats.god_mode;
+                cheats.cheated = true;
+                self.reply(format!("god mode {}", if cheats.god_mode { "on" } else { "off" }));
+            }
+            line => match ConsoleCommand::parse(line) {
+                Ok(command) => {
+                    commands.write(command);
+                }
+                Err(err) => self.reply(err),
+            },
+        }
+    }
+}
+
+/// A console command for the active game. Cells are in the game's own grid; a
+/// game on a cube also takes the face, by name, and otherwise uses the head's.
+#[derive(Message, Clone, PartialEq, Debug)]
+pub enum ConsoleCommand {
+    Grow(u32),
+    SpawnFood { face: Option<String>, x: i32, y: i32 },
+    /// Seconds per move.
+    SetSpeed(f32),
+    Seed(u64),
+    Teleport { face: Option<String>, x: i32, y: i32 },
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let usage = |usage: &str| format!("usage: {name} {usage}");
+        let number = |arg: &str| arg.parse().map_err(|_| format!("{name}: {arg:?} is not a number"));
+        let cell = || match args[..] {
+            [x, y] => Ok((None, number(x)?, number(y)?)),
+            [face, x, y] => Ok((Some(face.to_lowercase()), number(x)?, number(y)?)),
+            _ => Err(usage("[FACE] X Y")),
+        };
+        match name {
+            "grow" => match args[..] {
+                [count] => count
+                    .parse()
+                    .ok()
+                    .filter(|count| *count <= MAX_GROW)
+                    .map(Self::Grow)
+                    .ok_or_else(|| usage("N, up to 1000")),
+                _ => Err(usage("N")),
+            },
+            "spawn_food" => cell().map(|(face, x, y)| Self::SpawnFood { face, x, y }),
+            "set_speed" => match args[..] {
+                [seconds] => seconds
+                    .parse()
+                    .ok()
+                    .filter(|seconds| (MIN_SECONDS_PER_MOVE..=MAX_SECONDS_PER_MOVE).contains(seconds))
+                    .map(Self::SetSpeed)
+                    .ok_or_else(|| usage("SECONDS, from 0.01 to 5")),
+                _ => Err(usage("SECONDS")),
+            },
+            "seed" => match args[..] {
+                [seed] => seed.parse().map(Self::Seed).map_err(|_| usage("N")),
+                _ => Err(usage("N")),
+            },
+            "teleport" => cell().map(|(face, x, y)| Self::Teleport { face, x, y }),
+            _ => Err(format!("unknown command {name:?}; try help")),
+        }
+    }
+}
+
+/// The snake the console acts on, as a game with cells `Cell` provides it.
+/// A game implements it on a [`SystemParam`] and registers
+/// [`run_console_commands`] with that param.
+pub trait ConsoleTarget {
+    type Cell: Copy;
+
+    /// The cell at `x`, `y` on `face`, or why there is none. A game on a cube
+    /// defaults to the head's face; a flat one takes no face.
+    fn cell(&self, face: Option<&str>, x: i32, y: i32) -> Result<Self::Cell, String>;
+
+    /// How replies name `cell`.
+    fn describe(&self, cell: Self::Cell) -> String;
+
+    /// Adds `count` segments waiting on the tail, to unfold as the snake moves,
+    /// and returns the new length; `None` without a snake.
+    fn grow(&mut self, count: u32) -> Option<usize>;
+
+    /// Replaces the food with one at `cell`.
+    fn spawn_food(&mut self, cell: Self::Cell);
+
+    /// Moves the head to `cell`, with the body trailing straight back from it.
+    fn teleport(&mut self, cell: Self::Cell);
+}
+
+/// Carries out the console's commands on the snake `T` gives access to, and
+/// marks the round as cheated once one of them changes it.
+pub fn run_console_commands<T: SystemParam + 'static>(
+    mut target: StaticSystemParam<T>,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<DevConsole>,
+    mut cheats: ResMut<Cheats>,
+    mut rng: ResMut<GameRng>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) where
+    for<'w, 's> T::Item<'w, 's>: ConsoleTarget,
+{
+    for command in console_commands.read() {
+        let changed_round = match command.clone() {
+            ConsoleCommand::Grow(count) => {
+                let Some(length) = target.grow(count) else {
+                    continue;
+                };
+                Ok(format!("length {length}"))
+            }
+            ConsoleCommand::SpawnFood { face, x, y } => target.cell(face.as_deref(), x, y).map(|cell| {
+                target.spawn_food(cell);
+                format!("food at {}", target.describe(cell))
+            }),
+            ConsoleCommand::Teleport { face, x, y } => target.cell(face.as_deref(), x, y).map(|cell| {
+                target.teleport(cell);
+                format!("head at {}", target.describe(cell))
+            }),
+            ConsoleCommand::Seed(seed) => {
+                *rng = GameRng::seeded(seed);
+                Ok(format!("food seeded with {seed}"))
+            }
+            ConsoleCommand::SetSpeed(seconds) => {
+                fixed_time.set_timestep(Duration::from_secs_f32(seconds));
+                Ok(format!("{seconds}s per move"))
+            }
+        };
+        match changed_round {
+            Ok(reply) => {
+                cheats.cheated = true;
+                console.reply(reply);
+            }
+            Err(err) => console.reply(err),
+        }
+    }
+}
+
+#[derive(Component)]
+struct OverlayText;
+
+#[derive(Component)]
+struct ConsoleText;
+
+// Runs before the games look at the keyboard. While the console is open it
+// takes every key, so typing doesn't steer the snake or leave the round.
+fn type_into_console(
+    mut keys: MessageReader<KeyboardInput>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut console: ResMut<DevConsole>,
+    mut cheats: ResMut<Cheats>,
+    game_state: Option<Res<State<GameState>>>,
+    mut commands: MessageWriter<ConsoleCommand>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+    }
+    let playing = game_state.is_some_and(|state| *state.get() == GameState::Playing);
+    if !console.open {
+        keys.clear();
+        if playing && keyboard.just_pressed(KeyCode::Backquote) {
+            console.open = true;
+            keyboard.reset_all();
+        }
+        return;
+    }
+
+    for key in keys.read().filter(|key| key.state == ButtonState::Pressed) {
+        match &key.logical_key {
+            _ if key.key_code == KeyCode::Backquote => console.open = false,
+            Key::Escape => console.open = false,
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                console.submit(&line, &mut cheats, &mut commands);
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            _ => {
+                if let Some(text) = &key.text {
+                    console.input.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+    }
+    keyboard.reset_all();
+}
+
+// God mode carries over into the next round, and so does the cheating.
+fn start_round(mut cheats: ResMut<Cheats>) {
+    cheats.cheated = cheats.god_mode;
+}
+
+fn close_console(mut console: ResMut<DevConsole>) {
+    console.open = false;
+}
+
+fn count_ticks(
+    mut overlay: ResMut<DebugOverlay>,
+    mut game_started: MessageReader<GameStarted>,
+    mut snake_moved: MessageReader<SnakeMoved>,
+) {
+    if game_started.read().count() > 0 {
+        overlay.ticks = 0;
+    }
+    overlay.ticks += snake_moved.read().count() as u32;
+}
+
+fn text_panel(theme: &Theme, node: Node, marker: impl Component) -> impl Bundle {
+    (
+        Text::default(),
+        TextFont {
+            font_size: FONT_SIZE,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(6.0)),
+            ..node
+        },
+        BackgroundColor(theme.background.with_alpha(0.8)),
+        GlobalZIndex(30),
+        marker,
+    )
+}
+
+fn draw_overlay(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut lines: ResMut<DebugLines>,
+    theme: Res<Theme>,
+    entities: Query<()>,
+    meshes: Option<Res<Assets<Mesh>>>,
+    materials: Option<Res<Assets<StandardMaterial>>>,
+    color_materials: Option<Res<Assets<ColorMaterial>>>,
+    images: Option<Res<Assets<Image>>>,
+    mut text: Query<(Entity, &mut Text), With<OverlayText>>,
+) {
+    let lines = std::mem::take(&mut lines.0);
+    if !overlay.visible {
+        for (entity, _) in text.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let delta = time.delta_secs();
+    if delta > 0.0 {
+        overlay.fps = if overlay.fps > 0.0 { overlay.fps * 0.95 + 0.05 / delta } else { 1.0 / delta };
+    }
+    let material_count = materials.map_or(0, |assets| assets.len()) + color_materials.map_or(0, |assets| assets.len());
+    let mut shown = vec![
+        format!("FPS: {:.0}", overlay.fps),
+        format!("Tick: {}", overlay.ticks),
+        format!("Entities: {}", entities.iter().count()),
+        format!(
+            "Assets: {} meshes, {material_count} materials, {} images",
+            meshes.map_or(0, |assets| assets.len()),
+            images.map_or(0, |assets| assets.len()),
+        ),
+    ];
+    shown.extend(lines);
+
+    match text.single_mut() {
+        Ok((_, mut text)) => **text = shown.join("\n"),
+        Err(_) => {
+            let node = Node {
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..default()
+            };
+            commands.spawn(text_panel(&theme, node, OverlayText)).insert(Text::new(shown.join("\n")));
+        }
+    }
+}
+
+fn draw_console(
+    mut commands: Commands,
+    console: Res<DevConsole>,
+    theme: Res<Theme>,
+    mut text: Query<(Entity, &mut Text), With<ConsoleText>>,
+) {
+    if !console.open {
+        for (entity, _) in text.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let shown = console.log.iter().map(String::as_str).chain([format!("> {}_", console.input).as_str()]).collect::<Vec<_>>().join("\n");
+    match text.single_mut() {
+        Ok((_, mut text)) => **text = shown,
+        Err(_) => {
+            let node = Node {
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                width: Val::Percent(60.0),
+                ..default()
+            };
+            commands.spawn(text_panel(&theme, node, ConsoleText)).insert(Text::new(shown));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_parse_with_an_optional_face() {
+        assert_eq!(ConsoleCommand::parse("grow 10"), Ok(ConsoleCommand::Grow(10)));
+        assert_eq!(ConsoleCommand::parse("set_speed 0.05"), Ok(ConsoleCommand::SetSpeed(0.05)));
+        assert_eq!(ConsoleCommand::parse("seed 1234"), Ok(ConsoleCommand::Seed(1234)));
+        assert_eq!(
+            ConsoleCommand::parse("spawn_food 3 4"),
+            Ok(ConsoleCommand::SpawnFood { face: None, x: 3, y: 4 })
+        );
+        assert_eq!(
+            ConsoleCommand::parse("teleport Front 9 0"),
+            Ok(ConsoleCommand::Teleport { face: Some("front".to_string()), x: 9, y: 0 })
+        );
+
+        assert_eq!(ConsoleCommand::parse("grow 1000"), Ok(ConsoleCommand::Grow(1000)));
+        assert_eq!(ConsoleCommand::parse("set_speed 5"), Ok(ConsoleCommand::SetSpeed(5.0)));
+
+        for bad in [
+            "grow",
+            "grow -1",
+            "grow 4000000000",
+            "set_speed 0",
+            "set_speed nan",
+            "set_speed inf",
+            "set_speed 1e30",
+            "set_speed 1e-12",
+            "teleport 1",
+            "spawn_food a b",
+            "fly",
+        ] {
+            assert!(ConsoleCommand::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
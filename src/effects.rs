@@ -0,0 +1,383 @@
+//! Lightweight CPU particles, screen shake, screen flash and the death
+//! dissolve sequence. Every effect scales with [`EffectsSettings::intensity`],
+//! and an intensity of zero turns them off entirely.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+const INTENSITY_STEPS: [f32; 3] = [1.0, 0.5, 0.0];
+const SHAKE_DECAY: f32 = 1.5;
+const DISSOLVE_INTERVAL: f32 = 0.05;
+const DISSOLVE_DURATION: f32 = 0.25;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectsSettings>()
+            .init_resource::<ScreenShake>()
+            .init_resource::<DeathSequence>()
+            .add_systems(PreUpdate, remove_camera_shake)
+            .add_systems(
+                Update,
+                (
+                    cycle_effects_intensity,
+                    update_particles,
+                    update_dissolve,
+                    update_screen_flash,
+                    advance_death_sequence,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_camera_shake.before(TransformSystems::Propagate),
+            );
+    }
+}
+
+/// Global effects strength: 1.0 is full, 0.0 disables every effect.
+#[derive(Resource, Clone, Copy)]
+pub struct EffectsSettings {
+    pub intensity: f32,
+}
+
+impl Default for EffectsSettings {
+    fn default() -> Self {
+        Self { intensity: 1.0 }
+    }
+}
+
+impl EffectsSettings {
+    pub fn enabled(&self) -> bool {
+        self.intensity > 0.0
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec3,
+    gravity: Vec3,
+    lifetime: Timer,
+}
+
+/// Parameters for [`spawn_burst`]. `count` is scaled by the effects intensity.
+pub struct Burst {
+    pub origin: Vec3,
+    pub count: usize,
+    pub speed: f32,
+    pub lifetime: f32,
+    pub gravity: Vec3,
+}
+
+/// Spawns a burst of particles at `burst.origin`. `direction` supplies a unit
+/// vector per particle and `visual` the sprite or mesh each particle renders as.
+pub fn spawn_burst<B: Bundle>(
+    commands: &mut Commands,
+    settings: &EffectsSettings,
+    burst: Burst,
+    mut direction: impl FnMut() -> Vec3,
+    visual: impl Fn() -> B,
+) {
+    let count = (burst.count as f32 * settings.intensity).round() as usize;
+    for _ in 0..count {
+        commands.spawn((
+            visual(),
+            Transform::from_translation(burst.origin),
+            Particle {
+                velocity: direction() * burst.speed,
+                gravity: burst.gravity,
+                lifetime: Timer::from_seconds(burst.lifetime, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn update_particles(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Particle, &mut Transform)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut particle, mut transform) in query.iter_mut() {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let gravity = particle.gravity;
+        particle.velocity += gravity * dt;
+        transform.translation += particle.velocity * dt;
+        transform.scale = Vec3::splat(particle.lifetime.fraction_remaining());
+    }
+}
+
+/// Camera trauma; the offset grows with the square of trauma and decays over time.
+#[derive(Resource, Default)]
+pub struct ScreenShake {
+    trauma: f32,
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, amount: f32, settings: &EffectsSettings) {
+        self.trauma = (self.trauma + amount * settings.intensity).min(1.0);
+    }
+}
+
+/// Marks a camera to be shaken by [`ScreenShake`], up to `max_offset` world units.
+#[derive(Component)]
+pub struct CameraShake {
+    max_offset: f32,
+    applied: Vec3,
+}
+
+impl CameraShake {
+    pub fn new(max_offset: f32) -> Self {
+        Self {
+            max_offset,
+            applied: Vec3::ZERO,
+        }
+    }
+}
+
+// The offset is removed before gameplay systems run so cameras that follow the
+// player never see (or accumulate) the shake.
+fn remove_camera_shake(mut cameras: Query<(&mut CameraShake, &mut Transform)>) {
+    for (mut shake, mut transform) in cameras.iter_mut() {
+        transform.translation -= shake.applied;
+        shake.applied = Vec3::ZERO;
+    }
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut cameras: Query<(&mut CameraShake, &mut Transform)>,
+) {
+    if screen_shake.trauma <= 0.0 {
+        return;
+    }
+
+    let amount = screen_shake.trauma * screen_shake.trauma;
+    let t = time.elapsed_secs();
+    for (mut shake, mut transform) in cameras.iter_mut() {
+        let offset = (transform.right() * (t * 47.0).sin() + transform.up() * (t * 61.0).cos())
+            * shake.max_offset
+            * amount;
+        transform.translation += offset;
+        shake.applied = offset;
+    }
+
+    screen_shake.trauma = (screen_shake.trauma - SHAKE_DECAY * time.delta_secs()).max(0.0);
+}
+
+#[derive(Component)]
+pub struct ScreenFlash {
+    color: Color,
+    timer: Timer,
+}
+
+/// Covers the screen with `color` and fades it out over `duration` seconds.
+pub fn spawn_flash(commands: &mut Commands, settings: &EffectsSettings, color: Color, duration: f32) {
+    if !settings.enabled() {
+        return;
+    }
+
+    let color = color.with_alpha(color.alpha() * settings.intensity);
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(color),
+        GlobalZIndex(i32::MAX),
+        ScreenFlash {
+            color,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        },
+    ));
+}
+
+fn update_screen_flash(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ScreenFlash, &mut BackgroundColor)>,
+) {
+    for (entity, mut flash, mut background) in query.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let alpha = flash.color.alpha() * flash.timer.fraction_remaining();
+        background.0 = flash.color.with_alpha(alpha);
+    }
+}
+
+/// Shrinks an entity to nothing and then despawns it.
+#[derive(Component)]
+pub struct Dissolve(Timer);
+
+impl Default for Dissolve {
+    fn default() -> Self {
+        Self(Timer::from_seconds(DISSOLVE_DURATION, TimerMode::Once))
+    }
+}
+
+fn update_dissolve(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Dissolve, &mut Transform)>,
+) {
+    for (entity, mut dissolve, mut transform) in query.iter_mut() {
+        dissolve.0.tick(time.delta());
+        if dissolve.0.is_finished() {
+            commands.entity(entity).despawn();
+        } else {
+            transform.scale = Vec3::splat(dissolve.0.fraction_remaining());
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum DeathState {
+    #[default]
+    Alive,
+    Dissolving,
+    Finished,
+}
+
+/// Dissolves the snake one segment at a time after death. Games show their
+/// game-over screen once [`DeathSequence::is_finished`] returns true.
+#[derive(Resource, Default)]
+pub struct DeathSequence {
+    state: DeathState,
+    queue: VecDeque<Entity>,
+    timer: Timer,
+}
+
+impl DeathSequence {
+    pub fn start(&mut self, segments: impl IntoIterator<Item = Entity>, settings: &EffectsSettings) {
+        if self.state != DeathState::Alive {
+            return;
+        }
+
+        if settings.enabled() {
+            self.queue = segments.into_iter().collect();
+            self.timer = Timer::from_seconds(DISSOLVE_INTERVAL, TimerMode::Repeating);
+            self.state = DeathState::Dissolving;
+        } else {
+            self.state = DeathState::Finished;
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.state != DeathState::Alive
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state == DeathState::Finished
+    }
+}
+
+fn advance_death_sequence(time: Res<Time>, mut commands: Commands, mut death: ResMut<DeathSequence>) {
+    if death.state != DeathState::Dissolving {
+        return;
+    }
+
+    death.timer.tick(time.delta());
+    for _ in 0..death.timer.times_finished_this_tick() {
+        match death.queue.pop_front() {
+            Some(entity) => {
+                if let Ok(mut entity) = commands.get_entity(entity) {
+                    entity.insert(Dissolve::default());
+                }
+            }
+            None => {
+                death.state = DeathState::Finished;
+                break;
+            }
+        }
+    }
+}
+
+fn cycle_effects_intensity(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<EffectsSettings>) {
+    if keyboard.just_pressed(KeyCode::KeyE) {
+        let current = INTENSITY_STEPS
+            .iter()
+            .position(|&step| step == settings.intensity)
+            .unwrap_or(0);
+        settings.intensity = INTENSITY_STEPS[(current + 1) % INTENSITY_STEPS.len()];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    fn app_with_step(step: f32) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(step)))
+            .add_plugins(EffectsPlugin);
+        app
+    }
+
+    fn burst(app: &mut App) {
+        let settings = *app.world().resource::<EffectsSettings>();
+        let mut commands = app.world_mut().commands();
+        spawn_burst(
+            &mut commands,
+            &settings,
+            Burst {
+                origin: Vec3::ZERO,
+                count: 10,
+                speed: 1.0,
+                lifetime: 0.5,
+                gravity: Vec3::ZERO,
+            },
+            || Vec3::X,
+            || (),
+        );
+        app.world_mut().flush();
+    }
+
+    fn particle_count(app: &mut App) -> usize {
+        let world = app.world_mut();
+        world.query::<&Particle>().iter(world).count()
+    }
+
+    #[test]
+    fn particles_expire() {
+        let mut app = app_with_step(0.1);
+        burst(&mut app);
+        assert_eq!(particle_count(&mut app), 10);
+
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(particle_count(&mut app), 0);
+    }
+
+    #[test]
+    fn zero_intensity_disables_effects() {
+        let mut app = app_with_step(0.1);
+        app.insert_resource(EffectsSettings { intensity: 0.0 });
+        burst(&mut app);
+        assert_eq!(particle_count(&mut app), 0);
+
+        let segment = app.world_mut().spawn(Transform::default()).id();
+        let settings = *app.world().resource::<EffectsSettings>();
+        app.world_mut().resource_mut::<DeathSequence>().start([segment], &settings);
+        assert!(app.world().resource::<DeathSequence>().is_finished());
+        assert!(app.world().get_entity(segment).is_ok());
+    }
+}
@@ -0,0 +1,937 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::VecDeque;
+
+use crate::audio::{MusicTempo, PlaySfx, Sfx};
+use crate::effects::{
+    spawn_burst, spawn_flash, Burst, CameraShake, DeathSequence, EffectsPlugin, EffectsSettings,
+    ScreenShake,
+};
+use crate::high_scores::HighScores;
+use crate::launcher::{add_game_states, MiniGame};
+use crate::menu::GameState;
+use crate::settings::Settings;
+use crate::theme::Theme;
+
+const WELL_WIDTH: i32 = 10;
+const WELL_HEIGHT: i32 = 20;
+// Pieces spawn in two hidden rows above the visible well.
+const WELL_ROWS: i32 = WELL_HEIGHT + 2;
+const HUD_HEIGHT: f32 = 40.0;
+const CELL_SIZE: f32 = 24.0;
+const PREVIEW_CELL_SIZE: f32 = 18.0;
+const NEXT_PREVIEWS: usize = 5;
+const LINES_PER_LEVEL: u32 = 10;
+const LINE_SCORES: [u32; 5] = [0, 100, 300, 500, 800];
+const SOFT_DROP_FACTOR: f32 = 20.0;
+const LOCK_DELAY: f32 = 0.5;
+const MAX_LOCK_RESETS: u32 = 15;
+const AUTO_SHIFT_DELAY: f32 = 0.17;
+const AUTO_SHIFT_INTERVAL: f32 = 0.05;
+const GHOST_ALPHA: f32 = 0.25;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+impl Kind {
+    const ALL: [Kind; 7] = [Kind::I, Kind::O, Kind::T, Kind::S, Kind::Z, Kind::J, Kind::L];
+
+    /// Index into [`Theme::pieces`].
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Cells of the spawn orientation inside the piece's bounding box, y up.
+    fn cells(self) -> [IVec2; 4] {
+        let cells = match self {
+            Kind::I => [(0, 2), (1, 2), (2, 2), (3, 2)],
+            Kind::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            Kind::T => [(1, 2), (0, 1), (1, 1), (2, 1)],
+            Kind::S => [(1, 2), (2, 2), (0, 1), (1, 1)],
+            Kind::Z => [(0, 2), (1, 2), (1, 1), (2, 1)],
+            Kind::J => [(0, 2), (0, 1), (1, 1), (2, 1)],
+            Kind::L => [(2, 2), (0, 1), (1, 1), (2, 1)],
+        };
+        cells.map(|(x, y)| IVec2::new(x, y))
+    }
+
+    fn box_size(self) -> i32 {
+        match self {
+            Kind::I => 4,
+            Kind::O => 2,
+            _ => 3,
+        }
+    }
+
+    // Bounding box positions that put every piece's top row in the first hidden row.
+    fn spawn_position(self) -> IVec2 {
+        match self {
+            Kind::I => IVec2::new(3, WELL_HEIGHT - 2),
+            Kind::O => IVec2::new(4, WELL_HEIGHT),
+            _ => IVec2::new(3, WELL_HEIGHT - 1),
+        }
+    }
+}
+
+// SRS wall kicks, y up, indexed by [rotation before][0 clockwise, 1 counter-clockwise].
+const JLSTZ_KICKS: [[[(i32, i32); 5]; 2]; 4] = [
+    [[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]],
+    [[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)], [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]],
+    [[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)], [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]],
+    [[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)], [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]],
+];
+const I_KICKS: [[[(i32, i32); 5]; 2]; 4] = [
+    [[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]],
+    [[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]],
+    [[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]],
+    [[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]],
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Piece {
+    kind: Kind,
+    rotation: u8,
+    pos: IVec2,
+}
+
+impl Piece {
+    fn spawn(kind: Kind) -> Self {
+        Self {
+            kind,
+            rotation: 0,
+            pos: kind.spawn_position(),
+        }
+    }
+
+    fn cells(&self) -> [IVec2; 4] {
+        let size = self.kind.box_size();
+        self.kind.cells().map(|mut cell| {
+            for _ in 0..self.rotation {
+                cell = IVec2::new(cell.y, size - 1 - cell.x);
+            }
+            cell + self.pos
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum BlocksEvent {
+    Rotated,
+    HardDropped,
+    Cleared(Vec<i32>),
+    LevelUp,
+    ToppedOut,
+}
+
+/// The well, the falling piece and everything that decides what comes next.
+#[derive(Resource)]
+struct Blocks {
+    well: Vec<Option<Kind>>,
+    piece: Piece,
+    hold: Option<Kind>,
+    hold_used: bool,
+    queue: VecDeque<Kind>,
+    bag: Vec<Kind>,
+    score: u32,
+    lines: u32,
+    level: u32,
+    gravity_timer: f32,
+    lock_timer: f32,
+    lock_resets: u32,
+    lowest_y: i32,
+    paused: bool,
+    game_over: bool,
+    events: Vec<BlocksEvent>,
+}
+
+impl Blocks {
+    fn new(rng: &mut impl Rng) -> Self {
+        let mut blocks = Self {
+            well: vec![None; (WELL_WIDTH * WELL_ROWS) as usize],
+            piece: Piece::spawn(Kind::O),
+            hold: None,
+            hold_used: false,
+            queue: VecDeque::new(),
+            bag: Vec::new(),
+            score: 0,
+            lines: 0,
+            level: 1,
+            gravity_timer: 0.0,
+            lock_timer: 0.0,
+            lock_resets: 0,
+            lowest_y: 0,
+            paused: false,
+            game_over: false,
+            events: Vec::new(),
+        };
+        blocks.spawn_next(rng);
+        blocks
+    }
+
+    // 7-bag randomizer: every run of seven pieces contains each kind once.
+    fn draw(&mut self, rng: &mut impl Rng) -> Kind {
+        if self.bag.is_empty() {
+            self.bag = Kind::ALL.to_vec();
+            self.bag.shuffle(rng);
+        }
+        self.bag.pop().expect("bag was just refilled")
+    }
+
+    fn cell(&self, pos: IVec2) -> Option<Kind> {
+        self.well[(pos.y * WELL_WIDTH + pos.x) as usize]
+    }
+
+    fn fits(&self, piece: &Piece) -> bool {
+        piece.cells().iter().all(|&cell| {
+            cell.x >= 0 && cell.x < WELL_WIDTH && cell.y >= 0 && cell.y < WELL_ROWS && self.cell(cell).is_none()
+        })
+    }
+
+    fn spawn(&mut self, kind: Kind) {
+        self.piece = Piece::spawn(kind);
+        self.gravity_timer = 0.0;
+        self.lock_timer = 0.0;
+        self.lock_resets = 0;
+        self.lowest_y = self.piece.pos.y;
+        if !self.fits(&self.piece) {
+            self.top_out();
+        }
+    }
+
+    fn spawn_next(&mut self, rng: &mut impl Rng) {
+        while self.queue.len() <= NEXT_PREVIEWS {
+            let kind = self.draw(rng);
+            self.queue.push_back(kind);
+        }
+        let kind = self.queue.pop_front().expect("queue was just filled");
+        self.hold_used = false;
+        self.spawn(kind);
+    }
+
+    fn top_out(&mut self) {
+        self.game_over = true;
+        self.events.push(BlocksEvent::ToppedOut);
+    }
+
+    /// Moves the falling piece to `piece` if it fits. Reaching a new lowest row
+    /// restarts lock delay; other moves restart it a limited number of times.
+    fn try_place(&mut self, piece: Piece) -> bool {
+        if !self.fits(&piece) {
+            return false;
+        }
+        self.piece = piece;
+        if piece.pos.y < self.lowest_y {
+            self.lowest_y = piece.pos.y;
+            self.lock_resets = 0;
+            self.lock_timer = 0.0;
+        } else if self.lock_resets < MAX_LOCK_RESETS {
+            self.lock_resets += 1;
+            self.lock_timer = 0.0;
+        }
+        true
+    }
+
+    fn shift(&mut self, offset: IVec2) -> bool {
+        let piece = Piece {
+            pos: self.piece.pos + offset,
+            ..self.piece
+        };
+        self.try_place(piece)
+    }
+
+    fn rotate(&mut self, clockwise: bool) -> bool {
+        let from = self.piece.rotation as usize;
+        let to = if clockwise { (from + 1) % 4 } else { (from + 3) % 4 };
+        let kicks = match self.piece.kind {
+            Kind::I => &I_KICKS,
+            _ => &JLSTZ_KICKS,
+        };
+        for &(x, y) in &kicks[from][usize::from(!clockwise)] {
+            let piece = Piece {
+                rotation: to as u8,
+                pos: self.piece.pos + IVec2::new(x, y),
+                ..self.piece
+            };
+            if self.try_place(piece) {
+                self.events.push(BlocksEvent::Rotated);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn hold(&mut self, rng: &mut impl Rng) -> bool {
+        if self.hold_used {
+            return false;
+        }
+        match self.hold.replace(self.piece.kind) {
+            Some(kind) => self.spawn(kind),
+            None => self.spawn_next(rng),
+        }
+        self.hold_used = true;
+        true
+    }
+
+    fn drop_distance(&self) -> i32 {
+        let mut distance = 0;
+        loop {
+            let piece = Piece {
+                pos: self.piece.pos - IVec2::Y * (distance + 1),
+                ..self.piece
+            };
+            if !self.fits(&piece) {
+                return distance;
+            }
+            distance += 1;
+        }
+    }
+
+    fn ghost(&self) -> Piece {
+        Piece {
+            pos: self.piece.pos - IVec2::Y * self.drop_distance(),
+            ..self.piece
+        }
+    }
+
+    fn hard_drop(&mut self, rng: &mut impl Rng) {
+        let distance = self.drop_distance();
+        self.piece = self.ghost();
+        self.score += 2 * distance as u32;
+        self.events.push(BlocksEvent::HardDropped);
+        self.lock(rng);
+    }
+
+    /// Advances gravity and lock delay by `delta` seconds. `gravity` is the
+    /// time per row before soft drop speeds it up.
+    fn tick(&mut self, delta: f32, gravity: f32, soft_drop: bool, rng: &mut impl Rng) {
+        if self.paused || self.game_over {
+            return;
+        }
+
+        let interval = if soft_drop { gravity / SOFT_DROP_FACTOR } else { gravity };
+        self.gravity_timer += delta;
+        while self.gravity_timer >= interval {
+            self.gravity_timer -= interval;
+            if !self.shift(IVec2::NEG_Y) {
+                self.gravity_timer = 0.0;
+                break;
+            }
+            if soft_drop {
+                self.score += 1;
+            }
+        }
+
+        if self.drop_distance() == 0 {
+            self.lock_timer += delta;
+            if self.lock_timer >= LOCK_DELAY {
+                self.lock(rng);
+            }
+        }
+    }
+
+    fn lock(&mut self, rng: &mut impl Rng) {
+        let cells = self.piece.cells();
+        for cell in cells {
+            self.well[(cell.y * WELL_WIDTH + cell.x) as usize] = Some(self.piece.kind);
+        }
+        // Locking entirely above the visible well ends the game.
+        if cells.iter().all(|cell| cell.y >= WELL_HEIGHT) {
+            self.top_out();
+            return;
+        }
+
+        let full: Vec<i32> = (0..WELL_ROWS)
+            .filter(|&y| (0..WELL_WIDTH).all(|x| self.cell(IVec2::new(x, y)).is_some()))
+            .collect();
+        if !full.is_empty() {
+            let mut well: Vec<Option<Kind>> = self
+                .well
+                .chunks(WELL_WIDTH as usize)
+                .enumerate()
+                .filter(|(y, _)| !full.contains(&(*y as i32)))
+                .flat_map(|(_, row)| row.iter().copied())
+                .collect();
+            well.resize(self.well.len(), None);
+            self.well = well;
+
+            self.score += LINE_SCORES[full.len()] * self.level;
+            self.lines += full.len() as u32;
+            let level = 1 + self.lines / LINES_PER_LEVEL;
+            self.events.push(BlocksEvent::Cleared(full));
+            if level > self.level {
+                self.level = level;
+                self.events.push(BlocksEvent::LevelUp);
+            }
+        }
+
+        self.spawn_next(rng);
+    }
+}
+
+/// Seconds per row at `level`, following the usual guideline curve.
+fn gravity_interval(level: u32) -> f32 {
+    let level = level.saturating_sub(1) as f32;
+    (0.8 - level * 0.007).powf(level)
+}
+
+// Held left/right repeats after a short delay.
+#[derive(Resource, Default)]
+struct AutoShift {
+    direction: i32,
+    timer: f32,
+}
+
+#[derive(Component)]
+struct WellCell(IVec2);
+
+#[derive(Clone, Copy, PartialEq)]
+enum PreviewSlot {
+    Hold,
+    Next(usize),
+}
+
+#[derive(Component)]
+struct PreviewBlock {
+    slot: PreviewSlot,
+    index: usize,
+}
+
+#[derive(Component)]
+struct GridLine;
+
+#[derive(Component)]
+struct BoardText;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct GameCamera;
+
+pub struct BlocksPlugin;
+
+impl MiniGame for BlocksPlugin {
+    const ID: &'static str = "blocks";
+    const NAME: &'static str = "Blocks";
+    const THUMBNAIL: &'static [&'static str] = &[
+        "+      +",
+        "+  ##  +",
+        "+   #  +",
+        "+   #  +",
+        "+      +",
+        "+o   oo+",
+        "+ooo oo+",
+        "++++++++",
+    ];
+}
+
+impl Plugin for BlocksPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_states(app);
+        app.add_plugins(EffectsPlugin)
+            .add_message::<PlaySfx>()
+            .init_resource::<MusicTempo>()
+            .init_resource::<Theme>()
+            .init_resource::<Settings>()
+            .init_resource::<HighScores>()
+            .init_resource::<ClearColor>()
+            .add_systems(Self::on_enter(), (setup, apply_theme).chain())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_round, spawn_board).chain().run_if(Self::is_active()),
+            )
+            .add_systems(
+                Update,
+                apply_theme.run_if(Self::is_active().and(resource_changed::<Theme>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_input,
+                    apply_gravity,
+                    play_feedback,
+                    draw_well,
+                    draw_previews,
+                    update_score_text,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        CameraShake::new(6.0),
+        GameCamera,
+        Transform::from_xyz(
+            WELL_WIDTH as f32 * CELL_SIZE / 2.0,
+            WELL_HEIGHT as f32 * CELL_SIZE / 2.0 + HUD_HEIGHT / 2.0,
+            0.0,
+        ),
+        DespawnOnExit(BlocksPlugin::active()),
+    ));
+}
+
+fn reset_round(mut commands: Commands, mut death: ResMut<DeathSequence>, mut tempo: ResMut<MusicTempo>) {
+    commands.insert_resource(Blocks::new(&mut rand::rng()));
+    commands.insert_resource(AutoShift::default());
+    *death = DeathSequence::default();
+    tempo.0 = 1.0;
+}
+
+// The well's bottom-left corner sits at the world origin, like the snake board.
+fn grid_to_world(pos: IVec2) -> Vec3 {
+    Vec3::new(
+        pos.x as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+        pos.y as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+        1.0,
+    )
+}
+
+// Bottom-left corner of a preview box, which is four cells wide and two tall.
+fn preview_origin(slot: PreviewSlot) -> Vec2 {
+    let top = WELL_HEIGHT as f32 * CELL_SIZE - 2.0 * PREVIEW_CELL_SIZE;
+    match slot {
+        PreviewSlot::Hold => Vec2::new(-5.0 * PREVIEW_CELL_SIZE, top),
+        PreviewSlot::Next(i) => Vec2::new(
+            WELL_WIDTH as f32 * CELL_SIZE + PREVIEW_CELL_SIZE,
+            top - i as f32 * 3.0 * PREVIEW_CELL_SIZE,
+        ),
+    }
+}
+
+fn spawn_board(mut commands: Commands, theme: Res<Theme>) {
+    let width = WELL_WIDTH as f32 * CELL_SIZE;
+    let height = WELL_HEIGHT as f32 * CELL_SIZE;
+
+    for x in 0..=WELL_WIDTH {
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(Vec2::new(2.0, height)),
+                ..default()
+            },
+            Transform::from_xyz(x as f32 * CELL_SIZE, height / 2.0, 0.0),
+            GridLine,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+    for y in 0..=WELL_HEIGHT {
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(Vec2::new(width, 2.0)),
+                ..default()
+            },
+            Transform::from_xyz(width / 2.0, y as f32 * CELL_SIZE, 0.0),
+            GridLine,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+
+    for y in 0..WELL_HEIGHT {
+        for x in 0..WELL_WIDTH {
+            let pos = IVec2::new(x, y);
+            commands.spawn((
+                Sprite {
+                    color: Color::NONE,
+                    custom_size: Some(Vec2::splat(CELL_SIZE - 2.0)),
+                    ..default()
+                },
+                Transform::from_translation(grid_to_world(pos)),
+                WellCell(pos),
+                DespawnOnExit(GameState::Playing),
+            ));
+        }
+    }
+
+    let slots = std::iter::once(PreviewSlot::Hold).chain((0..NEXT_PREVIEWS).map(PreviewSlot::Next));
+    for slot in slots {
+        for index in 0..4 {
+            commands.spawn((
+                Sprite {
+                    color: Color::NONE,
+                    custom_size: Some(Vec2::splat(PREVIEW_CELL_SIZE - 2.0)),
+                    ..default()
+                },
+                Transform::default(),
+                Visibility::Hidden,
+                PreviewBlock { slot, index },
+                DespawnOnExit(GameState::Playing),
+            ));
+        }
+    }
+
+    for (label, slot) in [("HOLD", PreviewSlot::Hold), ("NEXT", PreviewSlot::Next(0))] {
+        let origin = preview_origin(slot);
+        commands.spawn((
+            Text2d::new(label),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(theme.text),
+            Transform::from_xyz(origin.x + 2.0 * PREVIEW_CELL_SIZE, origin.y + 3.0 * PREVIEW_CELL_SIZE, 1.0),
+            BoardText,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+
+    commands.spawn((
+        Text::new("Score: 0"),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        ScoreText,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+fn handle_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut blocks: ResMut<Blocks>,
+    mut auto_shift: ResMut<AutoShift>,
+    mut death: ResMut<DeathSequence>,
+    mut tempo: ResMut<MusicTempo>,
+) {
+    let mut rng = rand::rng();
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        *blocks = Blocks::new(&mut rng);
+        *auto_shift = AutoShift::default();
+        *death = DeathSequence::default();
+        tempo.0 = 1.0;
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::KeyP) && !blocks.game_over {
+        blocks.paused = !blocks.paused;
+    }
+    if blocks.paused || blocks.game_over {
+        return;
+    }
+
+    let [up, _, left, right] = settings.gameplay.controls.bindings();
+    if keyboard.any_just_pressed(up.iter().copied()) || keyboard.just_pressed(KeyCode::KeyX) {
+        blocks.rotate(true);
+    }
+    if keyboard.any_just_pressed([KeyCode::KeyZ, KeyCode::KeyQ]) {
+        blocks.rotate(false);
+    }
+    if keyboard.any_just_pressed([KeyCode::KeyC, KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        blocks.hold(&mut rng);
+    }
+
+    let direction = match (
+        keyboard.any_pressed(left.iter().copied()),
+        keyboard.any_pressed(right.iter().copied()),
+    ) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => 0,
+    };
+    if direction != auto_shift.direction {
+        auto_shift.direction = direction;
+        auto_shift.timer = -AUTO_SHIFT_DELAY;
+        if direction != 0 {
+            blocks.shift(IVec2::X * direction);
+        }
+    } else if direction != 0 {
+        auto_shift.timer += time.delta_secs();
+        while auto_shift.timer >= AUTO_SHIFT_INTERVAL {
+            auto_shift.timer -= AUTO_SHIFT_INTERVAL;
+            blocks.shift(IVec2::X * direction);
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Space) {
+        blocks.hard_drop(&mut rng);
+    }
+}
+
+fn apply_gravity(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut blocks: ResMut<Blocks>,
+) {
+    let [_, down, _, _] = settings.gameplay.controls.bindings();
+    let gravity = gravity_interval(blocks.level) * settings.gameplay.speed.interval_scale();
+    let soft_drop = keyboard.any_pressed(down.iter().copied());
+    blocks.tick(time.delta_secs(), gravity, soft_drop, &mut rand::rng());
+}
+
+fn play_feedback(
+    mut commands: Commands,
+    mut blocks: ResMut<Blocks>,
+    settings: Res<Settings>,
+    mut high_scores: ResMut<HighScores>,
+    effects: Res<EffectsSettings>,
+    mut death: ResMut<DeathSequence>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut tempo: ResMut<MusicTempo>,
+    mut sfx: MessageWriter<PlaySfx>,
+    theme: Res<Theme>,
+) {
+    let mut rng = rand::rng();
+    for event in std::mem::take(&mut blocks.events) {
+        match event {
+            BlocksEvent::Rotated => {
+                sfx.write(PlaySfx(Sfx::Turn));
+            }
+            BlocksEvent::HardDropped => {
+                screen_shake.add_trauma(0.15, &effects);
+            }
+            BlocksEvent::Cleared(rows) => {
+                sfx.write(PlaySfx(if rows.len() == 4 { Sfx::PowerUp } else { Sfx::Eat }));
+                for y in rows {
+                    spawn_burst(
+                        &mut commands,
+                        &effects,
+                        Burst {
+                            origin: grid_to_world(IVec2::new(WELL_WIDTH / 2, y)).with_z(2.0),
+                            count: 24,
+                            speed: 160.0,
+                            lifetime: 0.4,
+                            gravity: Vec3::new(0.0, -400.0, 0.0),
+                        },
+                        || Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU)).extend(0.0),
+                        || Sprite {
+                            color: theme.text,
+                            custom_size: Some(Vec2::splat(4.0)),
+                            ..default()
+                        },
+                    );
+                }
+            }
+            BlocksEvent::LevelUp => {
+                sfx.write(PlaySfx(Sfx::LevelUp));
+                tempo.0 = (1.0 + (blocks.level - 1) as f32 * 0.05).min(1.6);
+            }
+            BlocksEvent::ToppedOut => {
+                high_scores.record(blocks.score, settings.gameplay.mode);
+                sfx.write(PlaySfx(Sfx::Death));
+                screen_shake.add_trauma(0.8, &effects);
+                spawn_flash(&mut commands, &effects, theme.flash.with_alpha(0.6), 0.4);
+                // Nothing to dissolve; this just moves the sequence on to the game-over screen.
+                death.start(std::iter::empty(), &effects);
+            }
+        }
+    }
+}
+
+fn draw_well(blocks: Res<Blocks>, theme: Res<Theme>, mut cells: Query<(&WellCell, &mut Sprite)>) {
+    let piece = blocks.piece.cells();
+    let ghost = blocks.ghost().cells();
+    let falling = !blocks.game_over;
+    for (cell, mut sprite) in cells.iter_mut() {
+        let color = theme.piece(blocks.piece.kind.index());
+        sprite.color = if let Some(kind) = blocks.cell(cell.0) {
+            theme.piece(kind.index())
+        } else if falling && piece.contains(&cell.0) {
+            color
+        } else if falling && ghost.contains(&cell.0) {
+            color.with_alpha(GHOST_ALPHA)
+        } else {
+            Color::NONE
+        };
+    }
+}
+
+fn draw_previews(
+    blocks: Res<Blocks>,
+    theme: Res<Theme>,
+    mut previews: Query<(&PreviewBlock, &mut Sprite, &mut Transform, &mut Visibility)>,
+) {
+    for (block, mut sprite, mut transform, mut visibility) in previews.iter_mut() {
+        let kind = match block.slot {
+            PreviewSlot::Hold => blocks.hold,
+            PreviewSlot::Next(i) => blocks.queue.get(i).copied(),
+        };
+        let Some(kind) = kind else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // Centre the spawn orientation in its four-by-two box.
+        let cells = kind.cells();
+        let min = cells.iter().copied().reduce(IVec2::min).unwrap_or_default();
+        let max = cells.iter().copied().reduce(IVec2::max).unwrap_or_default();
+        let size = (max - min + IVec2::ONE).as_vec2();
+        let offset = (Vec2::new(4.0, 2.0) - size) / 2.0;
+        let cell = (cells[block.index] - min).as_vec2() + offset + Vec2::splat(0.5);
+
+        *visibility = Visibility::Inherited;
+        sprite.color = if block.slot == PreviewSlot::Hold && blocks.hold_used {
+            theme.piece(kind.index()).with_alpha(0.4)
+        } else {
+            theme.piece(kind.index())
+        };
+        transform.translation = (preview_origin(block.slot) + cell * PREVIEW_CELL_SIZE).extend(1.0);
+    }
+}
+
+fn update_score_text(
+    blocks: Res<Blocks>,
+    death: Res<DeathSequence>,
+    high_scores: Res<HighScores>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    let best = high_scores.best(settings.gameplay.mode).unwrap_or(0).max(blocks.score);
+    for mut text in query.iter_mut() {
+        **text = if death.is_finished() {
+            format!(
+                "Game Over! Final Score: {}  Best: {}\nPress Enter for menu or R to restart",
+                blocks.score, best
+            )
+        } else if blocks.paused {
+            "Paused - P to resume, R to restart".to_string()
+        } else {
+            format!(
+                "Score: {}  Lines: {}  Level: {}  Best: {}",
+                blocks.score, blocks.lines, blocks.level, best
+            )
+        };
+    }
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut grid: Query<&mut Sprite, With<GridLine>>,
+    mut texts: Query<&mut TextColor, Or<(With<ScoreText>, With<BoardText>)>>,
+) {
+    clear_color.0 = theme.background;
+    for mut sprite in grid.iter_mut() {
+        sprite.color = theme.grid;
+    }
+    for mut color in texts.iter_mut() {
+        color.0 = theme.text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Long enough that gravity never moves the piece during a test.
+    const NO_GRAVITY: f32 = 1000.0;
+
+    fn filled_cells(blocks: &Blocks) -> usize {
+        blocks.well.iter().filter(|cell| cell.is_some()).count()
+    }
+
+    #[test]
+    fn each_bag_deals_every_piece_once() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut blocks = Blocks::new(&mut rng);
+        // The falling piece and the next queue are the first bag, less one.
+        let mut dealt: Vec<Kind> = std::iter::once(blocks.piece.kind).chain(blocks.queue.iter().copied()).collect();
+        dealt.push(blocks.draw(&mut rng));
+        for _ in 0..2 {
+            dealt.extend((0..7).map(|_| blocks.draw(&mut rng)));
+        }
+
+        for bag in dealt.chunks(7) {
+            let mut indices: Vec<usize> = bag.iter().map(|kind| kind.index()).collect();
+            indices.sort();
+            assert_eq!(indices, vec![0, 1, 2, 3, 4, 5, 6]);
+        }
+    }
+
+    #[test]
+    fn rotation_kicks_off_the_wall() {
+        let mut blocks = Blocks::new(&mut StdRng::seed_from_u64(1));
+        // Vertical I in the rightmost column; a flat I there would poke through the wall.
+        blocks.piece = Piece {
+            kind: Kind::I,
+            rotation: 1,
+            pos: IVec2::new(7, 5),
+        };
+        assert!(blocks.piece.cells().iter().all(|cell| cell.x == WELL_WIDTH - 1));
+
+        assert!(blocks.rotate(true));
+        assert_eq!(blocks.piece.rotation, 2);
+        let mut xs: Vec<i32> = blocks.piece.cells().iter().map(|cell| cell.x).collect();
+        xs.sort();
+        assert_eq!(xs, vec![6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn clearing_four_lines_scores_by_level() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut blocks = Blocks::new(&mut rng);
+        for y in 0..4 {
+            for x in 1..WELL_WIDTH {
+                blocks.well[(y * WELL_WIDTH + x) as usize] = Some(Kind::O);
+            }
+        }
+        blocks.level = 2;
+        blocks.piece = Piece {
+            kind: Kind::I,
+            rotation: 1,
+            pos: IVec2::new(-2, 0),
+        };
+
+        blocks.hard_drop(&mut rng);
+        assert_eq!(blocks.lines, 4);
+        assert_eq!(blocks.score, LINE_SCORES[4] * 2);
+        assert_eq!(filled_cells(&blocks), 0);
+        assert!(blocks.events.contains(&BlocksEvent::Cleared(vec![0, 1, 2, 3])));
+    }
+
+    #[test]
+    fn lock_delay_restarts_when_the_piece_moves() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut blocks = Blocks::new(&mut rng);
+        let distance = blocks.drop_distance();
+        blocks.shift(IVec2::NEG_Y * distance);
+
+        blocks.tick(LOCK_DELAY * 0.6, NO_GRAVITY, false, &mut rng);
+        assert!(blocks.shift(IVec2::NEG_X) || blocks.shift(IVec2::X));
+        blocks.tick(LOCK_DELAY * 0.6, NO_GRAVITY, false, &mut rng);
+        assert_eq!(filled_cells(&blocks), 0);
+
+        blocks.tick(LOCK_DELAY * 0.6, NO_GRAVITY, false, &mut rng);
+        assert_eq!(filled_cells(&blocks), 4);
+    }
+
+    #[test]
+    fn hold_swaps_once_per_piece() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut blocks = Blocks::new(&mut rng);
+        let first = blocks.piece.kind;
+        let next = blocks.queue[0];
+
+        assert!(blocks.hold(&mut rng));
+        assert_eq!(blocks.hold, Some(first));
+        assert_eq!(blocks.piece.kind, next);
+        assert!(!blocks.hold(&mut rng));
+
+        blocks.hard_drop(&mut rng);
+        assert!(blocks.hold(&mut rng));
+        assert_eq!(blocks.piece.kind, first);
+    }
+}
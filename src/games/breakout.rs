@@ -0,0 +1,599 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::audio::{MusicTempo, PlaySfx, Sfx};
+use crate::effects::{
+    spawn_burst, spawn_flash, Burst, CameraShake, DeathSequence, EffectsPlugin, EffectsSettings,
+    ScreenShake,
+};
+use crate::high_scores::HighScores;
+use crate::launcher::{add_game_states, MiniGame};
+use crate::menu::GameState;
+use crate::physics::advance;
+use crate::settings::Settings;
+use crate::theme::Theme;
+
+// The field is centred on the world origin; the camera leaves room for the HUD above it.
+const FIELD_WIDTH: f32 = 560.0;
+const FIELD_HEIGHT: f32 = 500.0;
+const HUD_HEIGHT: f32 = 40.0;
+const WALL_THICKNESS: f32 = 8.0;
+const PADDLE_SIZE: Vec2 = Vec2::new(90.0, 14.0);
+const PADDLE_Y: f32 = -FIELD_HEIGHT / 2.0 + 30.0;
+const PADDLE_SPEED: f32 = 520.0;
+const BALL_SIZE: f32 = 10.0;
+const BALL_SPEED: f32 = 300.0;
+// Each brick hit speeds the ball up a little; each cleared layout raises the starting speed.
+const SPEED_RAMP: f32 = 1.015;
+const LAYOUT_SPEEDUP: f32 = 1.1;
+const MAX_BALL_SPEED: f32 = 900.0;
+const MAX_BOUNCE_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+const BRICK_SIZE: Vec2 = Vec2::new(52.0, 18.0);
+const BRICK_GAP: f32 = 4.0;
+const BRICK_TOP: f32 = FIELD_HEIGHT / 2.0 - 50.0;
+const BRICK_POINTS: u32 = 10;
+const LIVES: u32 = 3;
+const POWER_UP_SIZE: Vec2 = Vec2::new(24.0, 12.0);
+const POWER_UP_SPEED: f32 = 150.0;
+const MULTI_BALL_SPREAD: f32 = 0.35;
+const MAX_BALLS: usize = 12;
+
+// Brick layouts, top row first: `#` brick, `M` brick that drops a multi-ball power-up.
+const LAYOUTS: &[&[&str]] = &[
+    &[
+        "##########",
+        "####MM####",
+        "##########",
+        "##########",
+        "###M##M###",
+    ],
+    &[
+        "#.#.##.#.#",
+        "##M####M##",
+        ".########.",
+        "..######..",
+        "...#MM#...",
+    ],
+    &[
+        "M########M",
+        "#........#",
+        "#.######.#",
+        "#.#MMMM#.#",
+        "#.######.#",
+        "#........#",
+        "##########",
+    ],
+];
+
+#[derive(Resource)]
+struct Breakout {
+    score: u32,
+    lives: u32,
+    layout: usize,
+    // Speed new balls launch at, raised with each cleared layout.
+    launch_speed: f32,
+    launched: bool,
+    paused: bool,
+    game_over: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Collider {
+    Wall,
+    Paddle,
+    Brick(Entity),
+}
+
+#[derive(Component)]
+struct Paddle;
+
+#[derive(Component)]
+struct Ball {
+    velocity: Vec2,
+}
+
+#[derive(Component)]
+struct Brick {
+    row: usize,
+    multi_ball: bool,
+}
+
+#[derive(Component)]
+struct PowerUp;
+
+#[derive(Component)]
+struct Wall;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct GameCamera;
+
+pub struct BreakoutPlugin;
+
+impl MiniGame for BreakoutPlugin {
+    const ID: &'static str = "breakout";
+    const NAME: &'static str = "Breakout";
+    const THUMBNAIL: &'static [&'static str] = &[
+        "++++++++",
+        "+oooooo+",
+        "+oo oo +",
+        "+      +",
+        "+  *   +",
+        "+      +",
+        "+  ### +",
+        "+      +",
+    ];
+}
+
+impl Plugin for BreakoutPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_states(app);
+        app.add_plugins(EffectsPlugin)
+            .add_message::<PlaySfx>()
+            .init_resource::<MusicTempo>()
+            .init_resource::<Theme>()
+            .init_resource::<Settings>()
+            .init_resource::<HighScores>()
+            .init_resource::<ClearColor>()
+            .add_systems(Self::on_enter(), (setup, apply_theme).chain())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_round, spawn_field).chain().run_if(Self::is_active()),
+            )
+            .add_systems(
+                Update,
+                apply_theme.run_if(Self::is_active().and(resource_changed::<Theme>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_pause,
+                    move_paddle,
+                    launch_ball,
+                    move_balls,
+                    move_power_ups,
+                    lose_balls,
+                    next_layout,
+                    update_score_text,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        CameraShake::new(6.0),
+        GameCamera,
+        Transform::from_xyz(0.0, HUD_HEIGHT / 2.0, 0.0),
+        DespawnOnExit(BreakoutPlugin::active()),
+    ));
+}
+
+fn reset_round(mut commands: Commands, mut death: ResMut<DeathSequence>, mut tempo: ResMut<MusicTempo>) {
+    commands.insert_resource(Breakout {
+        score: 0,
+        lives: LIVES,
+        layout: 0,
+        launch_speed: BALL_SPEED,
+        launched: false,
+        paused: false,
+        game_over: false,
+    });
+    *death = DeathSequence::default();
+    tempo.0 = 1.0;
+}
+
+fn wall_rects() -> [Rect; 3] {
+    let half = WALL_THICKNESS / 2.0;
+    [
+        Rect::new(-FIELD_WIDTH / 2.0 - WALL_THICKNESS, -FIELD_HEIGHT / 2.0, -FIELD_WIDTH / 2.0, FIELD_HEIGHT / 2.0 + half),
+        Rect::new(FIELD_WIDTH / 2.0, -FIELD_HEIGHT / 2.0, FIELD_WIDTH / 2.0 + WALL_THICKNESS, FIELD_HEIGHT / 2.0 + half),
+        Rect::new(-FIELD_WIDTH / 2.0, FIELD_HEIGHT / 2.0 - half, FIELD_WIDTH / 2.0, FIELD_HEIGHT / 2.0 + half),
+    ]
+}
+
+fn ball_bundle(position: Vec2, velocity: Vec2, theme: &Theme) -> impl Bundle {
+    (
+        Sprite {
+            color: theme.food,
+            custom_size: Some(Vec2::splat(BALL_SIZE)),
+            ..default()
+        },
+        Transform::from_translation(position.extend(2.0)),
+        Ball { velocity },
+        DespawnOnExit(GameState::Playing),
+    )
+}
+
+// A ball waiting to launch rides on top of the paddle.
+fn resting_ball_position(paddle_x: f32) -> Vec2 {
+    Vec2::new(paddle_x, PADDLE_Y + (PADDLE_SIZE.y + BALL_SIZE) / 2.0)
+}
+
+fn spawn_bricks(commands: &mut Commands, layout: &[&str], theme: &Theme) {
+    let step = BRICK_SIZE + Vec2::splat(BRICK_GAP);
+    for (row, line) in layout.iter().enumerate() {
+        let left = -(line.len() as f32) * step.x / 2.0 + step.x / 2.0;
+        for (column, cell) in line.chars().enumerate() {
+            if cell == '.' {
+                continue;
+            }
+            commands.spawn((
+                Sprite {
+                    color: theme.piece(row),
+                    custom_size: Some(BRICK_SIZE),
+                    ..default()
+                },
+                Transform::from_xyz(left + column as f32 * step.x, BRICK_TOP - row as f32 * step.y, 1.0),
+                Brick {
+                    row,
+                    multi_ball: cell == 'M',
+                },
+                DespawnOnExit(GameState::Playing),
+            ));
+        }
+    }
+}
+
+fn spawn_field(mut commands: Commands, theme: Res<Theme>) {
+    for rect in wall_rects() {
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(rect.size()),
+                ..default()
+            },
+            Transform::from_translation(rect.center().extend(0.0)),
+            Wall,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+
+    commands.spawn((
+        Sprite {
+            color: theme.head,
+            custom_size: Some(PADDLE_SIZE),
+            ..default()
+        },
+        Transform::from_xyz(0.0, PADDLE_Y, 1.0),
+        Paddle,
+        DespawnOnExit(GameState::Playing),
+    ));
+    commands.spawn(ball_bundle(resting_ball_position(0.0), Vec2::ZERO, &theme));
+    spawn_bricks(&mut commands, LAYOUTS[0], &theme);
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        ScoreText,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+fn toggle_pause(keyboard: Res<ButtonInput<KeyCode>>, mut game: ResMut<Breakout>) {
+    if keyboard.just_pressed(KeyCode::KeyP) && !game.game_over {
+        game.paused = !game.paused;
+    }
+}
+
+fn is_running(game: &Breakout) -> bool {
+    !game.paused && !game.game_over
+}
+
+fn move_paddle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    game: Res<Breakout>,
+    mut paddle: Query<&mut Transform, With<Paddle>>,
+    mut balls: Query<&mut Transform, (With<Ball>, Without<Paddle>)>,
+) {
+    if !is_running(&game) {
+        return;
+    }
+    let Ok(mut paddle) = paddle.single_mut() else {
+        return;
+    };
+
+    let [_, _, left, right] = settings.gameplay.controls.bindings();
+    let direction =
+        keyboard.any_pressed(right.iter().copied()) as i32 as f32 - keyboard.any_pressed(left.iter().copied()) as i32 as f32;
+    let limit = (FIELD_WIDTH - PADDLE_SIZE.x) / 2.0;
+    paddle.translation.x = (paddle.translation.x + direction * PADDLE_SPEED * time.delta_secs()).clamp(-limit, limit);
+
+    if !game.launched {
+        for mut ball in balls.iter_mut() {
+            ball.translation = resting_ball_position(paddle.translation.x).extend(ball.translation.z);
+        }
+    }
+}
+
+fn launch_ball(keyboard: Res<ButtonInput<KeyCode>>, mut game: ResMut<Breakout>, mut balls: Query<&mut Ball>) {
+    if !is_running(&game) || game.launched || !keyboard.just_pressed(KeyCode::Space) {
+        return;
+    }
+    game.launched = true;
+    let angle = rand::rng().random_range(-0.4..0.4);
+    for mut ball in balls.iter_mut() {
+        ball.velocity = Vec2::from_angle(std::f32::consts::FRAC_PI_2 + angle) * game.launch_speed;
+    }
+}
+
+fn move_balls(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut game: ResMut<Breakout>,
+    mut balls: Query<(&mut Transform, &mut Ball), (Without<Paddle>, Without<Brick>)>,
+    paddle: Query<&Transform, With<Paddle>>,
+    bricks: Query<(Entity, &Transform, &Brick)>,
+    effects: Res<EffectsSettings>,
+    mut tempo: ResMut<MusicTempo>,
+    mut sfx: MessageWriter<PlaySfx>,
+    theme: Res<Theme>,
+) {
+    if !is_running(&game) || !game.launched {
+        return;
+    }
+    let Ok(paddle) = paddle.single() else {
+        return;
+    };
+    let paddle_x = paddle.translation.x;
+
+    let mut colliders: Vec<(Collider, Rect)> = wall_rects().into_iter().map(|rect| (Collider::Wall, rect)).collect();
+    colliders.push((Collider::Paddle, Rect::from_center_size(paddle.translation.truncate(), PADDLE_SIZE)));
+    for (entity, transform, _) in bricks.iter() {
+        colliders.push((Collider::Brick(entity), Rect::from_center_size(transform.translation.truncate(), BRICK_SIZE)));
+    }
+
+    // Bricks are shared between balls, so one broken by an earlier ball is gone for the rest.
+    let mut rng = rand::rng();
+    let mut fastest: f32 = 0.0;
+    for (mut transform, mut ball) in balls.iter_mut() {
+        let mut position = transform.translation.truncate();
+        let mut velocity = ball.velocity;
+        advance(
+            &mut position,
+            &mut velocity,
+            Vec2::splat(BALL_SIZE / 2.0),
+            time.delta_secs(),
+            &mut colliders,
+            |collider, hit, contact, velocity| match collider {
+                Collider::Wall => false,
+                Collider::Paddle => {
+                    // Where the ball lands on the paddle sets the angle it leaves at.
+                    if hit.normal == Vec2::Y {
+                        let reach = (PADDLE_SIZE.x + BALL_SIZE) / 2.0;
+                        let offset = ((contact.x - paddle_x) / reach).clamp(-1.0, 1.0);
+                        *velocity = Vec2::from_angle(std::f32::consts::FRAC_PI_2 - offset * MAX_BOUNCE_ANGLE)
+                            * velocity.length();
+                    }
+                    sfx.write(PlaySfx(Sfx::Turn));
+                    false
+                }
+                Collider::Brick(entity) => {
+                    let Ok((_, brick_transform, brick)) = bricks.get(entity) else {
+                        return true;
+                    };
+                    game.score += BRICK_POINTS;
+                    *velocity = (*velocity * SPEED_RAMP).clamp_length_max(MAX_BALL_SPEED);
+                    sfx.write(PlaySfx(Sfx::Eat));
+
+                    let color = theme.piece(brick.row);
+                    spawn_burst(
+                        &mut commands,
+                        &effects,
+                        Burst {
+                            origin: brick_transform.translation.with_z(3.0),
+                            count: 12,
+                            speed: 120.0,
+                            lifetime: 0.35,
+                            gravity: Vec3::new(0.0, -300.0, 0.0),
+                        },
+                        || Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU)).extend(0.0),
+                        || Sprite {
+                            color,
+                            custom_size: Some(Vec2::splat(4.0)),
+                            ..default()
+                        },
+                    );
+                    if brick.multi_ball {
+                        commands.spawn((
+                            Sprite {
+                                color: theme.food,
+                                custom_size: Some(POWER_UP_SIZE),
+                                ..default()
+                            },
+                            Transform::from_translation(brick_transform.translation.with_z(2.0)),
+                            PowerUp,
+                            DespawnOnExit(GameState::Playing),
+                        ));
+                    }
+                    commands.entity(entity).despawn();
+                    true
+                }
+            },
+        );
+        ball.velocity = velocity;
+        transform.translation = position.extend(transform.translation.z);
+        fastest = fastest.max(velocity.length());
+    }
+    tempo.0 = (fastest / BALL_SPEED).clamp(1.0, 1.6);
+}
+
+// Catching a power-up splits every ball in play into three.
+fn move_power_ups(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Breakout>,
+    mut power_ups: Query<(Entity, &mut Transform), (With<PowerUp>, Without<Paddle>)>,
+    paddle: Query<&Transform, With<Paddle>>,
+    balls: Query<(&Transform, &Ball), (Without<PowerUp>, Without<Paddle>)>,
+    mut sfx: MessageWriter<PlaySfx>,
+    theme: Res<Theme>,
+) {
+    if !is_running(&game) {
+        return;
+    }
+    let Ok(paddle) = paddle.single() else {
+        return;
+    };
+    let paddle_rect = Rect::from_center_size(paddle.translation.truncate(), PADDLE_SIZE + POWER_UP_SIZE);
+
+    for (entity, mut transform) in power_ups.iter_mut() {
+        transform.translation.y -= POWER_UP_SPEED * time.delta_secs();
+        if paddle_rect.contains(transform.translation.truncate()) {
+            commands.entity(entity).despawn();
+            sfx.write(PlaySfx(Sfx::PowerUp));
+            let mut count = balls.iter().count();
+            for (ball_transform, ball) in balls.iter() {
+                for spread in [-MULTI_BALL_SPREAD, MULTI_BALL_SPREAD] {
+                    if count >= MAX_BALLS {
+                        break;
+                    }
+                    let velocity = Vec2::from_angle(spread).rotate(ball.velocity);
+                    commands.spawn(ball_bundle(ball_transform.translation.truncate(), velocity, &theme));
+                    count += 1;
+                }
+            }
+        } else if transform.translation.y < -FIELD_HEIGHT / 2.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn lose_balls(
+    mut commands: Commands,
+    mut game: ResMut<Breakout>,
+    settings: Res<Settings>,
+    mut high_scores: ResMut<HighScores>,
+    balls: Query<(Entity, &Transform), With<Ball>>,
+    paddle: Query<&Transform, With<Paddle>>,
+    power_ups: Query<Entity, With<PowerUp>>,
+    effects: Res<EffectsSettings>,
+    mut death: ResMut<DeathSequence>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut sfx: MessageWriter<PlaySfx>,
+    theme: Res<Theme>,
+) {
+    if !is_running(&game) || !game.launched {
+        return;
+    }
+
+    let mut remaining = 0;
+    for (entity, transform) in balls.iter() {
+        if transform.translation.y < -(FIELD_HEIGHT + BALL_SIZE) / 2.0 {
+            commands.entity(entity).despawn();
+        } else {
+            remaining += 1;
+        }
+    }
+    if remaining > 0 {
+        return;
+    }
+
+    game.lives -= 1;
+    game.launched = false;
+    for entity in power_ups.iter() {
+        commands.entity(entity).despawn();
+    }
+    screen_shake.add_trauma(0.5, &effects);
+    sfx.write(PlaySfx(Sfx::Death));
+
+    if game.lives > 0 {
+        let paddle_x = paddle.single().map_or(0.0, |paddle| paddle.translation.x);
+        commands.spawn(ball_bundle(resting_ball_position(paddle_x), Vec2::ZERO, &theme));
+        return;
+    }
+    game.game_over = true;
+    high_scores.record(game.score, settings.gameplay.mode);
+    spawn_flash(&mut commands, &effects, theme.flash.with_alpha(0.6), 0.4);
+    // Nothing to dissolve; this just moves the sequence on to the game-over screen.
+    death.start(std::iter::empty(), &effects);
+}
+
+// Clearing every brick moves on to the next layout, wrapping around with a faster ball.
+fn next_layout(
+    mut commands: Commands,
+    mut game: ResMut<Breakout>,
+    bricks: Query<(), With<Brick>>,
+    balls: Query<Entity, With<Ball>>,
+    power_ups: Query<Entity, With<PowerUp>>,
+    paddle: Query<&Transform, With<Paddle>>,
+    mut sfx: MessageWriter<PlaySfx>,
+    theme: Res<Theme>,
+) {
+    if !is_running(&game) || !bricks.is_empty() {
+        return;
+    }
+
+    for entity in balls.iter().chain(power_ups.iter()) {
+        commands.entity(entity).despawn();
+    }
+    game.layout += 1;
+    game.launch_speed = (game.launch_speed * LAYOUT_SPEEDUP).min(MAX_BALL_SPEED);
+    game.launched = false;
+    sfx.write(PlaySfx(Sfx::LevelUp));
+
+    let paddle_x = paddle.single().map_or(0.0, |paddle| paddle.translation.x);
+    commands.spawn(ball_bundle(resting_ball_position(paddle_x), Vec2::ZERO, &theme));
+    spawn_bricks(&mut commands, LAYOUTS[game.layout % LAYOUTS.len()], &theme);
+}
+
+fn update_score_text(
+    game: Res<Breakout>,
+    death: Res<DeathSequence>,
+    high_scores: Res<HighScores>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    let best = high_scores.best(settings.gameplay.mode).unwrap_or(0).max(game.score);
+    for mut text in query.iter_mut() {
+        **text = if death.is_finished() {
+            format!("Game Over! Final Score: {}  Best: {}\nPress Enter for menu", game.score, best)
+        } else if game.paused {
+            "Paused - P to resume".to_string()
+        } else if !game.launched {
+            format!("Score: {}  Lives: {}  Best: {}  Space to launch", game.score, game.lives, best)
+        } else {
+            format!("Score: {}  Lives: {}  Best: {}", game.score, game.lives, best)
+        };
+    }
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sprites: Query<(&mut Sprite, Option<&Brick>, Has<Wall>, Has<Paddle>, Has<Ball>, Has<PowerUp>)>,
+    mut texts: Query<&mut TextColor, With<ScoreText>>,
+) {
+    clear_color.0 = theme.background;
+    for (mut sprite, brick, is_wall, is_paddle, is_ball, is_power_up) in sprites.iter_mut() {
+        if let Some(brick) = brick {
+            sprite.color = theme.piece(brick.row);
+        } else if is_wall {
+            sprite.color = theme.grid;
+        } else if is_paddle {
+            sprite.color = theme.head;
+        } else if is_ball || is_power_up {
+            sprite.color = theme.food;
+        }
+    }
+    for mut color in texts.iter_mut() {
+        color.0 = theme.text;
+    }
+}
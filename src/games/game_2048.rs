@@ -0,0 +1,615 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+use crate::audio::{PlaySfx, Sfx};
+use crate::effects::{CameraShake, DeathSequence, EffectsPlugin, EffectsSettings, ScreenShake};
+use crate::grid::{cell_to_world, frame_board};
+use crate::high_scores::HighScores;
+use crate::launcher::{add_game_states, MiniGame};
+use crate::menu::GameState;
+use crate::settings::Settings;
+use crate::theme::Theme;
+
+const SIZE: i32 = 4;
+const CELL_SIZE: f32 = 110.0;
+const TILE_SIZE: f32 = CELL_SIZE - 10.0;
+const HUD_HEIGHT: f32 = 40.0;
+const VIEWPORT: Vec2 = Vec2::new(780.0, 580.0);
+const WINNING_TILE: u32 = 2048;
+const UNDO_LIMIT: usize = 32;
+const SLIDE_TIME: f32 = 0.1;
+const POP_TIME: f32 = 0.15;
+const POP_SCALE: f32 = 0.2;
+// Shortest mouse drag or touch swipe, in logical pixels, that counts as a move.
+const SWIPE_DISTANCE: f32 = 30.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Slide {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Slide {
+    /// Cells of line `i`, starting from the edge the tiles slide towards.
+    fn line(self, i: i32) -> [IVec2; SIZE as usize] {
+        std::array::from_fn(|j| {
+            let j = j as i32;
+            match self {
+                Slide::Left => IVec2::new(j, i),
+                Slide::Right => IVec2::new(SIZE - 1 - j, i),
+                Slide::Down => IVec2::new(i, j),
+                Slide::Up => IVec2::new(i, SIZE - 1 - j),
+            }
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct TileMove {
+    from: IVec2,
+    to: IVec2,
+    value: u32,
+}
+
+/// What a slide did, for scoring and animation.
+#[derive(Default, PartialEq, Debug)]
+struct SlideResult {
+    moves: Vec<TileMove>,
+    merged: Vec<IVec2>,
+    points: u32,
+}
+
+// Tile values by row, bottom row first; zero is empty.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+struct Tiles([[u32; SIZE as usize]; SIZE as usize]);
+
+impl Tiles {
+    fn get(&self, cell: IVec2) -> u32 {
+        self.0[cell.y as usize][cell.x as usize]
+    }
+
+    fn set(&mut self, cell: IVec2, value: u32) {
+        self.0[cell.y as usize][cell.x as usize] = value;
+    }
+
+    fn cells() -> impl Iterator<Item = IVec2> {
+        (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| IVec2::new(x, y)))
+    }
+
+    fn empty_cells(&self) -> Vec<IVec2> {
+        Self::cells().filter(|&cell| self.get(cell) == 0).collect()
+    }
+
+    /// Slides every tile towards one edge, merging equal neighbours once per
+    /// move. Returns `None` when nothing would move.
+    fn slide(&self, direction: Slide) -> Option<(Tiles, SlideResult)> {
+        let mut tiles = Tiles::default();
+        let mut result = SlideResult::default();
+        for i in 0..SIZE {
+            let line = direction.line(i);
+            let mut placed = 0;
+            let mut can_merge = false;
+            for &from in &line {
+                let value = self.get(from);
+                if value == 0 {
+                    continue;
+                }
+                if can_merge && tiles.get(line[placed - 1]) == value {
+                    let to = line[placed - 1];
+                    tiles.set(to, value * 2);
+                    result.moves.push(TileMove { from, to, value });
+                    result.merged.push(to);
+                    result.points += value * 2;
+                    can_merge = false;
+                } else {
+                    let to = line[placed];
+                    tiles.set(to, value);
+                    result.moves.push(TileMove { from, to, value });
+                    placed += 1;
+                    can_merge = true;
+                }
+            }
+        }
+        (tiles != *self).then_some((tiles, result))
+    }
+
+    fn can_move(&self) -> bool {
+        [Slide::Up, Slide::Down, Slide::Left, Slide::Right]
+            .into_iter()
+            .any(|direction| self.slide(direction).is_some())
+    }
+
+    fn highest(&self) -> u32 {
+        Self::cells().map(|cell| self.get(cell)).max().unwrap_or(0)
+    }
+
+    // New tiles are a 2, or a 4 one time in ten.
+    fn spawn(&mut self, rng: &mut impl Rng) -> Option<IVec2> {
+        let cell = *self.empty_cells().choose(rng)?;
+        self.set(cell, if rng.random_bool(0.1) { 4 } else { 2 });
+        Some(cell)
+    }
+}
+
+#[derive(Resource)]
+struct Game {
+    tiles: Tiles,
+    score: u32,
+    history: Vec<(Tiles, u32)>,
+    over: bool,
+}
+
+impl Game {
+    fn new(rng: &mut impl Rng) -> Self {
+        let mut tiles = Tiles::default();
+        tiles.spawn(rng);
+        tiles.spawn(rng);
+        Self {
+            tiles,
+            score: 0,
+            history: Vec::new(),
+            over: false,
+        }
+    }
+
+    /// Applies a move and adds a tile. Returns what happened and where the
+    /// new tile went, or `None` if the move changed nothing.
+    fn play(&mut self, direction: Slide, rng: &mut impl Rng) -> Option<(SlideResult, Option<IVec2>)> {
+        let (tiles, result) = self.tiles.slide(direction)?;
+        if self.history.len() == UNDO_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push((self.tiles, self.score));
+        self.tiles = tiles;
+        self.score += result.points;
+        let spawned = self.tiles.spawn(rng);
+        self.over = !self.tiles.can_move();
+        Some((result, spawned))
+    }
+
+    fn undo(&mut self) -> bool {
+        let Some((tiles, score)) = self.history.pop() else {
+            return false;
+        };
+        self.tiles = tiles;
+        self.score = score;
+        self.over = false;
+        true
+    }
+}
+
+/// Tile sprites are rebuilt after every move: tiles first slide from their old
+/// cells, then the settled board appears with merged and new tiles popping in.
+#[derive(Resource)]
+struct TileView {
+    rebuild: bool,
+    moves: Vec<TileMove>,
+    pops: Vec<IVec2>,
+    slide: Timer,
+}
+
+impl TileView {
+    fn animate(&mut self, moves: Vec<TileMove>, pops: Vec<IVec2>) {
+        self.rebuild = true;
+        self.moves = moves;
+        self.pops = pops;
+        // With nothing to slide, the board settles on the next frame.
+        let duration = if self.moves.is_empty() { 0.0 } else { SLIDE_TIME };
+        self.slide = Timer::from_seconds(duration, TimerMode::Once);
+    }
+
+    fn settle(&mut self) {
+        self.animate(Vec::new(), Vec::new());
+    }
+}
+
+#[derive(Component)]
+struct Tile;
+
+#[derive(Component)]
+struct Sliding {
+    from: Vec2,
+    to: Vec2,
+}
+
+#[derive(Component)]
+struct Pop(Timer);
+
+#[derive(Component)]
+struct TileLabel;
+
+#[derive(Component)]
+struct EmptyCell;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct GameCamera;
+
+pub struct Game2048Plugin;
+
+impl MiniGame for Game2048Plugin {
+    const ID: &'static str = "2048";
+    const NAME: &'static str = "2048";
+    const THUMBNAIL: &'static [&'static str] = &[
+        "        ",
+        " ++ ++  ",
+        " ++ ++  ",
+        "        ",
+        " oo ##  ",
+        " oo ##  ",
+        "        ",
+        "        ",
+    ];
+}
+
+impl Plugin for Game2048Plugin {
+    fn build(&self, app: &mut App) {
+        add_game_states(app);
+        app.add_plugins(EffectsPlugin)
+            .add_message::<PlaySfx>()
+            .init_resource::<Theme>()
+            .init_resource::<Settings>()
+            .init_resource::<HighScores>()
+            .init_resource::<ClearColor>()
+            .add_systems(Self::on_enter(), (setup, apply_theme).chain())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_round, spawn_board).chain().run_if(Self::is_active()),
+            )
+            .add_systems(
+                Update,
+                apply_theme.run_if(Self::is_active().and(resource_changed::<Theme>)),
+            )
+            // Moves happen only on input; the remaining systems just animate the result.
+            .add_systems(
+                Update,
+                (handle_input, animate_tiles, update_score_text)
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        CameraShake::new(6.0),
+        GameCamera,
+        frame_board(IVec2::splat(SIZE), CELL_SIZE, HUD_HEIGHT, VIEWPORT),
+        DespawnOnExit(Game2048Plugin::active()),
+    ));
+}
+
+fn reset_round(mut commands: Commands, mut death: ResMut<DeathSequence>) {
+    commands.insert_resource(Game::new(&mut rand::rng()));
+    let mut view = TileView {
+        rebuild: false,
+        moves: Vec::new(),
+        pops: Vec::new(),
+        slide: Timer::default(),
+    };
+    view.settle();
+    commands.insert_resource(view);
+    *death = DeathSequence::default();
+}
+
+fn spawn_board(mut commands: Commands, theme: Res<Theme>) {
+    for cell in Tiles::cells() {
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(Vec2::splat(TILE_SIZE)),
+                ..default()
+            },
+            Transform::from_translation(cell_to_world(cell, CELL_SIZE).extend(0.0)),
+            EmptyCell,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        ScoreText,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+fn tile_color(value: u32, theme: &Theme) -> Color {
+    theme.piece((value.max(2).ilog2() as usize - 1) % 7)
+}
+
+fn spawn_tile(commands: &mut Commands, value: u32, position: Vec2, z: f32, theme: &Theme) -> Entity {
+    commands
+        .spawn((
+            Sprite {
+                color: tile_color(value, theme),
+                custom_size: Some(Vec2::splat(TILE_SIZE)),
+                ..default()
+            },
+            Transform::from_translation(position.extend(z)),
+            Tile,
+            DespawnOnExit(GameState::Playing),
+        ))
+        .with_child((
+            Text2d::new(value.to_string()),
+            TextFont {
+                font_size: if value < 1000 { 44.0 } else { 34.0 },
+                ..default()
+            },
+            TextColor(theme.background),
+            Transform::from_xyz(0.0, 0.0, 0.1),
+            TileLabel,
+        ))
+        .id()
+}
+
+// Arrow keys/WASD, or a mouse drag or touch swipe, slide the tiles; U or Z undoes.
+fn handle_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Option<Res<Touches>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    mut drag_start: Local<Option<Vec2>>,
+    mut game: ResMut<Game>,
+    mut view: ResMut<TileView>,
+    mut high_scores: ResMut<HighScores>,
+    effects: Res<EffectsSettings>,
+    mut death: ResMut<DeathSequence>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        next_state.set(GameState::Playing);
+        return;
+    }
+    if keyboard.any_just_pressed([KeyCode::KeyU, KeyCode::KeyZ]) && !death.is_started() && game.undo() {
+        view.settle();
+        sfx.write(PlaySfx(Sfx::Turn));
+        return;
+    }
+
+    let cursor = windows.single().ok().and_then(Window::cursor_position);
+    let mut swipe = None;
+    if mouse.just_pressed(MouseButton::Left) {
+        *drag_start = cursor;
+    }
+    if mouse.just_released(MouseButton::Left) {
+        if let (Some(start), Some(end)) = (drag_start.take(), cursor) {
+            swipe = Some(end - start);
+        }
+    }
+    if let Some(touch) = touches.as_ref().and_then(|touches| touches.iter_just_released().next()) {
+        swipe = Some(touch.position() - touch.start_position());
+    }
+
+    let [up, down, left, right] = settings.gameplay.controls.bindings();
+    let direction = if keyboard.any_just_pressed(up.iter().copied()) {
+        Some(Slide::Up)
+    } else if keyboard.any_just_pressed(down.iter().copied()) {
+        Some(Slide::Down)
+    } else if keyboard.any_just_pressed(left.iter().copied()) {
+        Some(Slide::Left)
+    } else if keyboard.any_just_pressed(right.iter().copied()) {
+        Some(Slide::Right)
+    } else {
+        // Window coordinates grow downwards.
+        swipe.filter(|delta| delta.length() >= SWIPE_DISTANCE).map(|delta| {
+            if delta.x.abs() > delta.y.abs() {
+                if delta.x > 0.0 { Slide::Right } else { Slide::Left }
+            } else if delta.y > 0.0 {
+                Slide::Down
+            } else {
+                Slide::Up
+            }
+        })
+    };
+    let Some(direction) = direction else {
+        return;
+    };
+    if game.over {
+        return;
+    }
+
+    let reached_goal = game.tiles.highest() >= WINNING_TILE;
+    let Some((result, spawned)) = game.play(direction, &mut rand::rng()) else {
+        return;
+    };
+    let mut pops = result.merged.clone();
+    pops.extend(spawned);
+    view.animate(result.moves, pops);
+
+    if !reached_goal && game.tiles.highest() >= WINNING_TILE {
+        sfx.write(PlaySfx(Sfx::LevelUp));
+    } else if result.points > 0 {
+        sfx.write(PlaySfx(Sfx::Eat));
+    } else {
+        sfx.write(PlaySfx(Sfx::Turn));
+    }
+
+    if game.over {
+        high_scores.record(game.score, settings.gameplay.mode);
+        sfx.write(PlaySfx(Sfx::Death));
+        screen_shake.add_trauma(0.4, &effects);
+        // Nothing to dissolve; this just moves the sequence on to the game-over screen.
+        death.start(std::iter::empty(), &effects);
+    }
+}
+
+fn animate_tiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Game>,
+    mut view: ResMut<TileView>,
+    theme: Res<Theme>,
+    tiles: Query<Entity, With<Tile>>,
+    mut sliding: Query<(&Sliding, &mut Transform), Without<Pop>>,
+    mut pops: Query<(&mut Pop, &mut Transform), Without<Sliding>>,
+) {
+    let despawned = view.rebuild;
+    if view.rebuild {
+        view.rebuild = false;
+        for entity in tiles.iter() {
+            commands.entity(entity).despawn();
+        }
+        for tile_move in &view.moves {
+            let from = cell_to_world(tile_move.from, CELL_SIZE);
+            let to = cell_to_world(tile_move.to, CELL_SIZE);
+            let entity = spawn_tile(&mut commands, tile_move.value, from, 1.0, &theme);
+            commands.entity(entity).insert(Sliding { from, to });
+        }
+    }
+
+    if !view.slide.is_finished() {
+        view.slide.tick(time.delta());
+        let t = view.slide.fraction();
+        for (slide, mut transform) in sliding.iter_mut() {
+            transform.translation = slide.from.lerp(slide.to, t).extend(transform.translation.z);
+        }
+        if !view.slide.is_finished() {
+            return;
+        }
+
+        if !despawned {
+            for entity in tiles.iter() {
+                commands.entity(entity).despawn();
+            }
+        }
+        for cell in Tiles::cells() {
+            let value = game.tiles.get(cell);
+            if value == 0 {
+                continue;
+            }
+            let entity = spawn_tile(&mut commands, value, cell_to_world(cell, CELL_SIZE), 1.0, &theme);
+            if view.pops.contains(&cell) {
+                commands.entity(entity).insert(Pop(Timer::from_seconds(POP_TIME, TimerMode::Once)));
+            }
+        }
+    }
+
+    for (mut pop, mut transform) in pops.iter_mut() {
+        pop.0.tick(time.delta());
+        let t = pop.0.fraction();
+        transform.scale = Vec3::splat(1.0 + POP_SCALE * (t * std::f32::consts::PI).sin());
+    }
+}
+
+fn update_score_text(
+    game: Res<Game>,
+    death: Res<DeathSequence>,
+    high_scores: Res<HighScores>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    let best = high_scores.best(settings.gameplay.mode).unwrap_or(0).max(game.score);
+    for mut text in query.iter_mut() {
+        **text = if death.is_finished() {
+            format!(
+                "No moves left! Final Score: {}  Best: {}\nPress Enter for menu, U to undo or R to restart",
+                game.score, best
+            )
+        } else if game.tiles.highest() >= WINNING_TILE {
+            format!("Score: {}  Best: {}  You made {WINNING_TILE}! Keep going", game.score, best)
+        } else {
+            format!("Score: {}  Best: {}", game.score, best)
+        };
+    }
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    game: Option<Res<Game>>,
+    mut view: Option<ResMut<TileView>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut cells: Query<&mut Sprite, With<EmptyCell>>,
+    mut texts: Query<&mut TextColor, With<ScoreText>>,
+) {
+    clear_color.0 = theme.background;
+    for mut sprite in cells.iter_mut() {
+        sprite.color = theme.grid;
+    }
+    for mut color in texts.iter_mut() {
+        color.0 = theme.text;
+    }
+    // Tiles pick up the new colours when they are rebuilt.
+    if let (Some(_), Some(view)) = (game, view.as_mut()) {
+        view.settle();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn row(values: [u32; 4]) -> Tiles {
+        let mut tiles = Tiles::default();
+        tiles.0[0] = values;
+        tiles
+    }
+
+    #[test]
+    fn slides_merge_each_tile_once() {
+        let (tiles, result) = row([2, 2, 2, 2]).slide(Slide::Left).unwrap();
+        assert_eq!(tiles, row([4, 4, 0, 0]));
+        assert_eq!(result.points, 8);
+        assert_eq!(result.merged, vec![IVec2::new(0, 0), IVec2::new(1, 0)]);
+
+        let (tiles, _) = row([4, 4, 8, 0]).slide(Slide::Right).unwrap();
+        assert_eq!(tiles, row([0, 0, 8, 8]));
+
+        let (tiles, result) = row([2, 0, 0, 2]).slide(Slide::Up).unwrap();
+        assert_eq!(tiles.get(IVec2::new(0, 3)), 2);
+        assert_eq!(tiles.get(IVec2::new(3, 3)), 2);
+        assert_eq!(result.points, 0);
+
+        assert_eq!(row([2, 4, 8, 16]).slide(Slide::Left), None);
+    }
+
+    #[test]
+    fn undo_restores_tiles_and_score() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut game = Game::new(&mut rng);
+        game.tiles = row([2, 2, 0, 0]);
+        let before = game.tiles;
+
+        assert!(game.play(Slide::Left, &mut rng).is_some());
+        assert_eq!(game.score, 4);
+        assert_eq!(game.tiles.empty_cells().len(), 14);
+
+        assert!(game.undo());
+        assert_eq!(game.tiles, before);
+        assert_eq!(game.score, 0);
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn full_board_without_merges_is_over() {
+        let mut tiles = Tiles::default();
+        for cell in Tiles::cells() {
+            tiles.set(cell, if (cell.x + cell.y) % 2 == 0 { 2 } else { 4 });
+        }
+        assert!(!tiles.can_move());
+        tiles.set(IVec2::ZERO, 4);
+        assert!(tiles.can_move());
+    }
+}
@@ -0,0 +1,588 @@
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+use bevy::window::PrimaryWindow;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::audio::{PlaySfx, Sfx};
+use crate::effects::{spawn_flash, CameraShake, DeathSequence, EffectsPlugin, EffectsSettings, ScreenShake};
+use crate::grid::{cell_to_world, cursor_position, frame_board, world_to_cell};
+use crate::launcher::{add_game_states, MiniGame};
+use crate::menu::GameState;
+use crate::settings::{BoardSize, Settings};
+use crate::theme::Theme;
+
+const CELL_SIZE: f32 = 32.0;
+const HUD_HEIGHT: f32 = 40.0;
+const VIEWPORT: Vec2 = Vec2::new(780.0, 580.0);
+const REVEALED_ALPHA: f32 = 0.3;
+
+/// The classic difficulties, picked with the board size setting.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Preset {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Preset {
+    fn from_board_size(size: BoardSize) -> Self {
+        match size {
+            BoardSize::Small => Preset::Beginner,
+            BoardSize::Medium => Preset::Intermediate,
+            BoardSize::Large => Preset::Expert,
+        }
+    }
+
+    fn board_size(self) -> BoardSize {
+        match self {
+            Preset::Beginner => BoardSize::Small,
+            Preset::Intermediate => BoardSize::Medium,
+            Preset::Expert => BoardSize::Large,
+        }
+    }
+
+    fn size(self) -> IVec2 {
+        match self {
+            Preset::Beginner => IVec2::new(9, 9),
+            Preset::Intermediate => IVec2::new(16, 16),
+            Preset::Expert => IVec2::new(30, 16),
+        }
+    }
+
+    fn mines(self) -> usize {
+        match self {
+            Preset::Beginner => 10,
+            Preset::Intermediate => 40,
+            Preset::Expert => 99,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Preset::Beginner => "Beginner",
+            Preset::Intermediate => "Intermediate",
+            Preset::Expert => "Expert",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Cover {
+    Hidden,
+    Flagged,
+    Revealed,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Outcome {
+    Playing,
+    Won,
+    Lost,
+}
+
+/// Mines are placed on the first reveal, so the first click is always safe.
+#[derive(Resource)]
+struct Minefield {
+    size: IVec2,
+    mine_count: usize,
+    mines: Vec<bool>,
+    cover: Vec<Cover>,
+    placed: bool,
+    outcome: Outcome,
+    exploded: Option<IVec2>,
+}
+
+impl Minefield {
+    fn new(size: IVec2, mine_count: usize) -> Self {
+        let cells = (size.x * size.y) as usize;
+        Self {
+            size,
+            mine_count,
+            mines: vec![false; cells],
+            cover: vec![Cover::Hidden; cells],
+            placed: false,
+            outcome: Outcome::Playing,
+            exploded: None,
+        }
+    }
+
+    fn index(&self, cell: IVec2) -> usize {
+        (cell.y * self.size.x + cell.x) as usize
+    }
+
+    fn contains(&self, cell: IVec2) -> bool {
+        cell.cmpge(IVec2::ZERO).all() && cell.cmplt(self.size).all()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = IVec2> + use<> {
+        let size = self.size;
+        (0..size.y).flat_map(move |y| (0..size.x).map(move |x| IVec2::new(x, y)))
+    }
+
+    fn neighbours(&self, cell: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y)))
+            .filter(|offset| *offset != IVec2::ZERO)
+            .map(move |offset| cell + offset)
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    fn is_mine(&self, cell: IVec2) -> bool {
+        self.mines[self.index(cell)]
+    }
+
+    fn cover(&self, cell: IVec2) -> Cover {
+        self.cover[self.index(cell)]
+    }
+
+    fn adjacent_mines(&self, cell: IVec2) -> usize {
+        self.neighbours(cell).filter(|&neighbour| self.is_mine(neighbour)).count()
+    }
+
+    fn flags(&self) -> usize {
+        self.cover.iter().filter(|&&cover| cover == Cover::Flagged).count()
+    }
+
+    // Keeps the clicked cell and, when there is room, its neighbours clear so
+    // the first click always opens an area.
+    fn place_mines(&mut self, safe: IVec2, rng: &mut impl Rng) {
+        let around: Vec<IVec2> = self.neighbours(safe).chain([safe]).collect();
+        let total = (self.size.x * self.size.y) as usize;
+        let keep_clear = |cell: &IVec2| {
+            if total - around.len() >= self.mine_count {
+                around.contains(cell)
+            } else {
+                *cell == safe
+            }
+        };
+        let mut candidates: Vec<IVec2> = self.cells().filter(|cell| !keep_clear(cell)).collect();
+        candidates.shuffle(rng);
+        for cell in candidates.into_iter().take(self.mine_count) {
+            let index = self.index(cell);
+            self.mines[index] = true;
+        }
+        self.placed = true;
+    }
+
+    fn reveal(&mut self, cell: IVec2, rng: &mut impl Rng) -> bool {
+        if self.outcome != Outcome::Playing || !self.contains(cell) || self.cover(cell) != Cover::Hidden {
+            return false;
+        }
+        if !self.placed {
+            self.place_mines(cell, rng);
+        }
+        if self.is_mine(cell) {
+            let index = self.index(cell);
+            self.cover[index] = Cover::Revealed;
+            self.exploded = Some(cell);
+            self.outcome = Outcome::Lost;
+            return true;
+        }
+
+        // Flood-fill outwards from cells with no adjacent mines.
+        let mut pending = vec![cell];
+        while let Some(cell) = pending.pop() {
+            let index = self.index(cell);
+            if self.cover[index] != Cover::Hidden {
+                continue;
+            }
+            self.cover[index] = Cover::Revealed;
+            if self.adjacent_mines(cell) == 0 {
+                pending.extend(self.neighbours(cell));
+            }
+        }
+
+        let cleared = self.cells().all(|cell| self.is_mine(cell) || self.cover(cell) == Cover::Revealed);
+        if cleared {
+            self.outcome = Outcome::Won;
+            for index in 0..self.cover.len() {
+                if self.mines[index] {
+                    self.cover[index] = Cover::Flagged;
+                }
+            }
+        }
+        true
+    }
+
+    fn toggle_flag(&mut self, cell: IVec2) -> bool {
+        if self.outcome != Outcome::Playing || !self.contains(cell) {
+            return false;
+        }
+        let index = self.index(cell);
+        self.cover[index] = match self.cover[index] {
+            Cover::Hidden => Cover::Flagged,
+            Cover::Flagged => Cover::Hidden,
+            Cover::Revealed => return false,
+        };
+        true
+    }
+
+    /// Reveals the hidden neighbours of a revealed number once the right number
+    /// of flags surround it.
+    fn chord(&mut self, cell: IVec2, rng: &mut impl Rng) -> bool {
+        if !self.contains(cell) || self.cover(cell) != Cover::Revealed {
+            return false;
+        }
+        let mines = self.adjacent_mines(cell);
+        let flags = self.neighbours(cell).filter(|&n| self.cover(n) == Cover::Flagged).count();
+        if mines == 0 || flags != mines {
+            return false;
+        }
+        let hidden: Vec<IVec2> = self.neighbours(cell).filter(|&n| self.cover(n) == Cover::Hidden).collect();
+        let mut changed = false;
+        for neighbour in hidden {
+            changed |= self.reveal(neighbour, rng);
+        }
+        changed
+    }
+}
+
+#[derive(Resource, Default)]
+struct Clock(Stopwatch);
+
+#[derive(Component)]
+struct FieldCell(IVec2);
+
+#[derive(Component)]
+struct CellLabel;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct GameCamera;
+
+pub struct MinesweeperPlugin;
+
+impl MiniGame for MinesweeperPlugin {
+    const ID: &'static str = "minesweeper";
+    const NAME: &'static str = "Minesweeper";
+    const THUMBNAIL: &'static [&'static str] = &[
+        "++++++++",
+        "++  ++++",
+        "+    *++",
+        "+     ++",
+        "++ #   +",
+        "+++    +",
+        "++++  ++",
+        "++++++++",
+    ];
+    const BOARD_SIZES: bool = true;
+}
+
+impl Plugin for MinesweeperPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_states(app);
+        app.add_plugins(EffectsPlugin)
+            .add_message::<PlaySfx>()
+            .init_resource::<Theme>()
+            .init_resource::<Settings>()
+            .init_resource::<ClearColor>()
+            .init_resource::<Clock>()
+            .add_systems(Self::on_enter(), (setup, apply_theme).chain())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_round, spawn_field).chain().run_if(Self::is_active()),
+            )
+            .add_systems(
+                Update,
+                apply_theme.run_if(Self::is_active().and(resource_changed::<Theme>)),
+            )
+            // Nothing moves on its own: the field only changes in response to clicks.
+            .add_systems(
+                Update,
+                (
+                    handle_keys,
+                    handle_clicks,
+                    finish_round,
+                    draw_field.run_if(resource_changed::<Minefield>.or(resource_changed::<Theme>)),
+                    update_score_text,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2d, CameraShake::new(6.0), GameCamera, DespawnOnExit(MinesweeperPlugin::active())));
+}
+
+fn reset_round(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut clock: ResMut<Clock>,
+    mut death: ResMut<DeathSequence>,
+    mut camera: Query<&mut Transform, With<GameCamera>>,
+) {
+    let preset = Preset::from_board_size(settings.gameplay.board_size);
+    commands.insert_resource(Minefield::new(preset.size(), preset.mines()));
+    clock.0.reset();
+    *death = DeathSequence::default();
+    for mut transform in camera.iter_mut() {
+        *transform = frame_board(preset.size(), CELL_SIZE, HUD_HEIGHT, VIEWPORT);
+    }
+}
+
+fn spawn_field(mut commands: Commands, settings: Res<Settings>, theme: Res<Theme>) {
+    let size = Preset::from_board_size(settings.gameplay.board_size).size();
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let cell = IVec2::new(x, y);
+            commands
+                .spawn((
+                    Sprite {
+                        color: theme.grid,
+                        custom_size: Some(Vec2::splat(CELL_SIZE - 2.0)),
+                        ..default()
+                    },
+                    Transform::from_translation(cell_to_world(cell, CELL_SIZE).extend(0.0)),
+                    FieldCell(cell),
+                    DespawnOnExit(GameState::Playing),
+                ))
+                .with_child((
+                    Text2d::default(),
+                    TextFont {
+                        font_size: 22.0,
+                        ..default()
+                    },
+                    TextColor(theme.text),
+                    Transform::from_xyz(0.0, 0.0, 1.0),
+                    CellLabel,
+                ));
+        }
+    }
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        ScoreText,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+// R restarts; 1-3 switch difficulty, which is saved as the board size setting.
+fn handle_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let preset = if keyboard.just_pressed(KeyCode::Digit1) {
+        Some(Preset::Beginner)
+    } else if keyboard.just_pressed(KeyCode::Digit2) {
+        Some(Preset::Intermediate)
+    } else if keyboard.just_pressed(KeyCode::Digit3) {
+        Some(Preset::Expert)
+    } else {
+        None
+    };
+    if let Some(preset) = preset {
+        settings.gameplay.board_size = preset.board_size();
+        next_state.set(GameState::Playing);
+    } else if keyboard.just_pressed(KeyCode::KeyR) {
+        next_state.set(GameState::Playing);
+    }
+}
+
+// Left click reveals, or chords on a revealed number; right click flags; middle click chords.
+fn handle_clicks(
+    mouse: Res<ButtonInput<MouseButton>>,
+    time: Res<Time>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut field: ResMut<Minefield>,
+    mut clock: ResMut<Clock>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    if field.placed && field.outcome == Outcome::Playing {
+        clock.0.tick(time.delta());
+    }
+    if !mouse.any_just_pressed([MouseButton::Left, MouseButton::Right, MouseButton::Middle]) {
+        return;
+    }
+    let (Ok(window), Ok((camera, transform))) = (windows.single(), camera.single()) else {
+        return;
+    };
+    let Some(position) = cursor_position(window, camera, transform) else {
+        return;
+    };
+    let cell = world_to_cell(position, CELL_SIZE);
+    if !field.contains(cell) {
+        return;
+    }
+
+    // Clicks that change nothing should not trigger a redraw.
+    let mut rng = rand::rng();
+    let minefield = field.bypass_change_detection();
+    let changed = if mouse.just_pressed(MouseButton::Right) {
+        minefield.toggle_flag(cell)
+    } else if mouse.just_pressed(MouseButton::Middle) || minefield.cover(cell) == Cover::Revealed {
+        minefield.chord(cell, &mut rng)
+    } else {
+        minefield.reveal(cell, &mut rng)
+    };
+    if changed {
+        field.set_changed();
+        sfx.write(PlaySfx(Sfx::Turn));
+    }
+}
+
+fn finish_round(
+    mut commands: Commands,
+    field: Res<Minefield>,
+    effects: Res<EffectsSettings>,
+    mut death: ResMut<DeathSequence>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut sfx: MessageWriter<PlaySfx>,
+    theme: Res<Theme>,
+) {
+    if field.outcome == Outcome::Playing || death.is_started() {
+        return;
+    }
+    if field.outcome == Outcome::Lost {
+        sfx.write(PlaySfx(Sfx::Death));
+        screen_shake.add_trauma(0.6, &effects);
+        spawn_flash(&mut commands, &effects, theme.flash.with_alpha(0.6), 0.4);
+    } else {
+        sfx.write(PlaySfx(Sfx::LevelUp));
+    }
+    // Nothing to dissolve; this just moves the sequence on to the game-over screen.
+    death.start(std::iter::empty(), &effects);
+}
+
+fn draw_field(
+    field: Res<Minefield>,
+    theme: Res<Theme>,
+    mut cells: Query<(&FieldCell, &mut Sprite, &Children)>,
+    mut labels: Query<(&mut Text2d, &mut TextColor), With<CellLabel>>,
+) {
+    let lost = field.outcome == Outcome::Lost;
+    for (cell, mut sprite, children) in cells.iter_mut() {
+        let cell = cell.0;
+        let (color, label, label_color) = match field.cover(cell) {
+            _ if field.exploded == Some(cell) => (theme.flash, "*".to_string(), theme.background),
+            Cover::Flagged if lost && !field.is_mine(cell) => (theme.grid, "X".to_string(), theme.food),
+            Cover::Flagged => (theme.grid, "F".to_string(), theme.food),
+            Cover::Hidden if lost && field.is_mine(cell) => (theme.grid, "*".to_string(), theme.food),
+            Cover::Hidden => (theme.grid, String::new(), theme.text),
+            Cover::Revealed => {
+                let mines = field.adjacent_mines(cell);
+                let label = if mines == 0 { String::new() } else { mines.to_string() };
+                (theme.grid.with_alpha(REVEALED_ALPHA), label, theme.text)
+            }
+        };
+        sprite.color = color;
+        for child in children.iter() {
+            if let Ok((mut text, mut text_color)) = labels.get_mut(child) {
+                text.0 = label.clone();
+                text_color.0 = label_color;
+            }
+        }
+    }
+}
+
+fn update_score_text(
+    field: Res<Minefield>,
+    settings: Res<Settings>,
+    clock: Res<Clock>,
+    death: Res<DeathSequence>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    let preset = Preset::from_board_size(settings.gameplay.board_size);
+    let seconds = clock.0.elapsed_secs() as u32;
+    for mut text in query.iter_mut() {
+        **text = match field.outcome {
+            Outcome::Won if death.is_finished() => {
+                format!("Cleared in {seconds}s!\nPress Enter for menu or R to restart")
+            }
+            Outcome::Lost if death.is_finished() => "Boom! Press Enter for menu or R to restart".to_string(),
+            _ => format!(
+                "{}  Mines: {}  Time: {}s  (1-3 difficulty)",
+                preset.label(),
+                field.mine_count as i64 - field.flags() as i64,
+                seconds
+            ),
+        };
+    }
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut texts: Query<&mut TextColor, With<ScoreText>>,
+) {
+    clear_color.0 = theme.background;
+    for mut color in texts.iter_mut() {
+        color.0 = theme.text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn field_with_mines(size: IVec2, mines: &[IVec2]) -> Minefield {
+        let mut field = Minefield::new(size, mines.len());
+        for &mine in mines {
+            let index = field.index(mine);
+            field.mines[index] = true;
+        }
+        field.placed = true;
+        field
+    }
+
+    #[test]
+    fn first_click_opens_a_safe_area() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for preset in [Preset::Beginner, Preset::Intermediate, Preset::Expert] {
+            let mut field = Minefield::new(preset.size(), preset.mines());
+            let first = IVec2::new(4, 4);
+            assert!(field.reveal(first, &mut rng));
+
+            assert_eq!(field.mines.iter().filter(|&&mine| mine).count(), preset.mines());
+            assert_eq!(field.adjacent_mines(first), 0);
+            assert!(!field.is_mine(first));
+            assert!(field.neighbours(first).all(|cell| field.cover(cell) == Cover::Revealed));
+            assert_ne!(field.outcome, Outcome::Lost);
+        }
+    }
+
+    #[test]
+    fn chording_reveals_unflagged_neighbours() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut field = field_with_mines(IVec2::new(3, 3), &[IVec2::new(0, 0)]);
+        assert!(field.reveal(IVec2::new(1, 1), &mut rng));
+        assert_eq!(field.cover(IVec2::new(2, 2)), Cover::Hidden);
+
+        // Without the flag the number is not satisfied.
+        assert!(!field.chord(IVec2::new(1, 1), &mut rng));
+        assert!(field.toggle_flag(IVec2::new(0, 0)));
+        assert!(field.chord(IVec2::new(1, 1), &mut rng));
+        assert_eq!(field.outcome, Outcome::Won);
+    }
+
+    #[test]
+    fn revealing_a_mine_loses() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut field = field_with_mines(IVec2::new(3, 3), &[IVec2::new(2, 2)]);
+        assert!(field.toggle_flag(IVec2::new(2, 2)));
+        assert!(!field.reveal(IVec2::new(2, 2), &mut rng));
+        assert!(field.toggle_flag(IVec2::new(2, 2)));
+        assert!(field.reveal(IVec2::new(2, 2), &mut rng));
+        assert_eq!(field.outcome, Outcome::Lost);
+        assert_eq!(field.exploded, Some(IVec2::new(2, 2)));
+    }
+}
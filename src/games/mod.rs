@@ -0,0 +1,9 @@
+//! The games in the collection, each a [`crate::launcher::MiniGame`] plugin.
+
+pub mod blocks;
+pub mod breakout;
+pub mod game_2048;
+pub mod minesweeper;
+pub mod pong;
+pub mod snake_2d;
+pub mod snake_3d;
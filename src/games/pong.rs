@@ -0,0 +1,446 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::audio::{MusicTempo, PlaySfx, Sfx};
+use crate::effects::{spawn_flash, CameraShake, DeathSequence, EffectsPlugin, EffectsSettings, ScreenShake};
+use crate::launcher::{add_game_states, MiniGame};
+use crate::menu::GameState;
+use crate::physics::advance;
+use crate::settings::Settings;
+use crate::theme::Theme;
+
+// The court is centred on the world origin; the camera leaves room for the HUD above it.
+const COURT_WIDTH: f32 = 760.0;
+const COURT_HEIGHT: f32 = 480.0;
+const HUD_HEIGHT: f32 = 40.0;
+const WALL_THICKNESS: f32 = 8.0;
+const PADDLE_SIZE: Vec2 = Vec2::new(12.0, 80.0);
+const PADDLE_INSET: f32 = 30.0;
+const PADDLE_SPEED: f32 = 420.0;
+const CPU_SPEED: f32 = 300.0;
+// The CPU stops short of the ball's height by this much, which leaves it beatable.
+const CPU_DEADZONE: f32 = 12.0;
+const BALL_SIZE: f32 = 12.0;
+const SERVE_SPEED: f32 = 320.0;
+const SERVE_DELAY: f32 = 1.0;
+const SPEEDUP: f32 = 1.06;
+const MAX_BALL_SPEED: f32 = 1100.0;
+const MAX_BOUNCE_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+const WINNING_SCORE: u32 = 7;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    fn index(self) -> usize {
+        match self {
+            Side::Left => 0,
+            Side::Right => 1,
+        }
+    }
+
+    /// Direction the paddle on this side hits the ball towards.
+    fn facing(self) -> f32 {
+        match self {
+            Side::Left => 1.0,
+            Side::Right => -1.0,
+        }
+    }
+
+    fn paddle_x(self) -> f32 {
+        -self.facing() * (COURT_WIDTH / 2.0 - PADDLE_INSET)
+    }
+
+    fn other(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Opponent {
+    Cpu,
+    Player,
+}
+
+#[derive(Resource)]
+struct Match {
+    // Chosen at the start of each round.
+    opponent: Option<Opponent>,
+    scores: [u32; 2],
+    serve: Timer,
+    serve_to: Side,
+    winner: Option<Side>,
+    paused: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Collider {
+    Wall,
+    Paddle(Side),
+}
+
+#[derive(Component)]
+struct Paddle(Side);
+
+#[derive(Component)]
+struct Ball {
+    velocity: Vec2,
+}
+
+#[derive(Component)]
+struct CourtLine;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct GameCamera;
+
+pub struct PongPlugin;
+
+impl MiniGame for PongPlugin {
+    const ID: &'static str = "pong";
+    const NAME: &'static str = "Pong";
+    const THUMBNAIL: &'static [&'static str] = &[
+        "++++++++",
+        "        ",
+        "#   +   ",
+        "#   + * ",
+        "#   +  #",
+        "    +  #",
+        "       #",
+        "++++++++",
+    ];
+}
+
+impl Plugin for PongPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_states(app);
+        app.add_plugins(EffectsPlugin)
+            .add_message::<PlaySfx>()
+            .init_resource::<MusicTempo>()
+            .init_resource::<Theme>()
+            .init_resource::<Settings>()
+            .init_resource::<ClearColor>()
+            .add_systems(Self::on_enter(), (setup, apply_theme).chain())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_round, spawn_court).chain().run_if(Self::is_active()),
+            )
+            .add_systems(
+                Update,
+                apply_theme.run_if(Self::is_active().and(resource_changed::<Theme>)),
+            )
+            .add_systems(
+                Update,
+                (choose_opponent, move_paddles, move_ball, update_score_text)
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        CameraShake::new(6.0),
+        GameCamera,
+        Transform::from_xyz(0.0, HUD_HEIGHT / 2.0, 0.0),
+        DespawnOnExit(PongPlugin::active()),
+    ));
+}
+
+fn reset_round(mut commands: Commands, mut death: ResMut<DeathSequence>, mut tempo: ResMut<MusicTempo>) {
+    commands.insert_resource(Match {
+        opponent: None,
+        scores: [0, 0],
+        serve: Timer::from_seconds(SERVE_DELAY, TimerMode::Once),
+        serve_to: if rand::rng().random() { Side::Left } else { Side::Right },
+        winner: None,
+        paused: false,
+    });
+    *death = DeathSequence::default();
+    tempo.0 = 1.0;
+}
+
+fn wall_rect(top: bool) -> Rect {
+    let y = (COURT_HEIGHT + WALL_THICKNESS) / 2.0;
+    Rect::from_center_half_size(
+        Vec2::new(0.0, if top { y } else { -y }),
+        Vec2::new(COURT_WIDTH, WALL_THICKNESS) / 2.0,
+    )
+}
+
+fn spawn_court(mut commands: Commands, theme: Res<Theme>) {
+    for top in [true, false] {
+        let rect = wall_rect(top);
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(rect.size()),
+                ..default()
+            },
+            Transform::from_translation(rect.center().extend(0.0)),
+            CourtLine,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+    let dashes = 15;
+    let spacing = COURT_HEIGHT / dashes as f32;
+    for i in 0..dashes {
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(Vec2::new(4.0, spacing / 2.0)),
+                ..default()
+            },
+            Transform::from_xyz(0.0, -COURT_HEIGHT / 2.0 + spacing * (i as f32 + 0.5), 0.0),
+            CourtLine,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+
+    for side in [Side::Left, Side::Right] {
+        commands.spawn((
+            Sprite {
+                color: theme.head,
+                custom_size: Some(PADDLE_SIZE),
+                ..default()
+            },
+            Transform::from_xyz(side.paddle_x(), 0.0, 1.0),
+            Paddle(side),
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+    commands.spawn((
+        Sprite {
+            color: theme.food,
+            custom_size: Some(Vec2::splat(BALL_SIZE)),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 1.0),
+        Ball { velocity: Vec2::ZERO },
+        DespawnOnExit(GameState::Playing),
+    ));
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        ScoreText,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+fn choose_opponent(keyboard: Res<ButtonInput<KeyCode>>, mut state: ResMut<Match>) {
+    if state.opponent.is_none() {
+        if keyboard.any_just_pressed([KeyCode::Digit1, KeyCode::Numpad1]) {
+            state.opponent = Some(Opponent::Cpu);
+        } else if keyboard.any_just_pressed([KeyCode::Digit2, KeyCode::Numpad2]) {
+            state.opponent = Some(Opponent::Player);
+        }
+    } else if keyboard.just_pressed(KeyCode::KeyP) && state.winner.is_none() {
+        state.paused = !state.paused;
+    }
+}
+
+fn is_running(state: &Match) -> bool {
+    state.opponent.is_some() && state.winner.is_none() && !state.paused
+}
+
+fn move_paddles(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    state: Res<Match>,
+    ball: Query<(&Transform, &Ball), Without<Paddle>>,
+    mut paddles: Query<(&Paddle, &mut Transform)>,
+) {
+    if !is_running(&state) {
+        return;
+    }
+
+    let axis = |up: &[KeyCode], down: &[KeyCode]| {
+        keyboard.any_pressed(up.iter().copied()) as i32 as f32 - keyboard.any_pressed(down.iter().copied()) as i32 as f32
+    };
+    let [up, down, _, _] = settings.gameplay.controls.bindings();
+    let (left_axis, right_axis) = match state.opponent {
+        Some(Opponent::Player) => (
+            axis(&[KeyCode::KeyW], &[KeyCode::KeyS]),
+            axis(&[KeyCode::ArrowUp], &[KeyCode::ArrowDown]),
+        ),
+        _ => (axis(up, down), 0.0),
+    };
+
+    let limit = (COURT_HEIGHT - PADDLE_SIZE.y) / 2.0;
+    for (paddle, mut transform) in paddles.iter_mut() {
+        let step = match (paddle.0, state.opponent) {
+            (Side::Left, _) => left_axis * PADDLE_SPEED,
+            (Side::Right, Some(Opponent::Player)) => right_axis * PADDLE_SPEED,
+            (Side::Right, _) => {
+                // Track the ball while it approaches, otherwise drift back to the middle.
+                let target = match ball.single() {
+                    Ok((ball_transform, ball)) if ball.velocity.x > 0.0 => ball_transform.translation.y,
+                    _ => 0.0,
+                };
+                let gap = target - transform.translation.y;
+                if gap.abs() > CPU_DEADZONE {
+                    gap.signum() * CPU_SPEED
+                } else {
+                    0.0
+                }
+            }
+        } * time.delta_secs();
+        transform.translation.y = (transform.translation.y + step).clamp(-limit, limit);
+    }
+}
+
+fn move_ball(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut state: ResMut<Match>,
+    mut ball: Query<(&mut Transform, &mut Ball), Without<Paddle>>,
+    paddles: Query<(&Paddle, &Transform)>,
+    effects: Res<EffectsSettings>,
+    mut death: ResMut<DeathSequence>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut tempo: ResMut<MusicTempo>,
+    mut sfx: MessageWriter<PlaySfx>,
+    theme: Res<Theme>,
+) {
+    if !is_running(&state) {
+        return;
+    }
+    let Ok((mut transform, mut ball)) = ball.single_mut() else {
+        return;
+    };
+
+    if ball.velocity == Vec2::ZERO {
+        state.serve.tick(time.delta());
+        if state.serve.is_finished() {
+            let angle = rand::rng().random_range(-0.5..0.5);
+            ball.velocity = Vec2::from_angle(angle) * SERVE_SPEED * Vec2::new(-state.serve_to.facing(), 1.0);
+        }
+        return;
+    }
+
+    let mut colliders = vec![(Collider::Wall, wall_rect(true)), (Collider::Wall, wall_rect(false))];
+    for (paddle, paddle_transform) in paddles.iter() {
+        colliders.push((
+            Collider::Paddle(paddle.0),
+            Rect::from_center_size(paddle_transform.translation.truncate(), PADDLE_SIZE),
+        ));
+    }
+
+    let mut position = transform.translation.truncate();
+    let mut velocity = ball.velocity;
+    advance(
+        &mut position,
+        &mut velocity,
+        Vec2::splat(BALL_SIZE / 2.0),
+        time.delta_secs(),
+        &mut colliders,
+        |collider, hit, contact, velocity| {
+            let Collider::Paddle(side) = collider else {
+                return false;
+            };
+            // Only the paddle's face steers the ball; its ends just bounce it.
+            if hit.normal.x == side.facing() {
+                let paddle_y = paddles
+                    .iter()
+                    .find(|(paddle, _)| paddle.0 == side)
+                    .map_or(0.0, |(_, transform)| transform.translation.y);
+                let reach = (PADDLE_SIZE.y + BALL_SIZE) / 2.0;
+                let offset = ((contact.y - paddle_y) / reach).clamp(-1.0, 1.0);
+                let speed = (velocity.length() * SPEEDUP).min(MAX_BALL_SPEED);
+                *velocity = Vec2::from_angle(offset * MAX_BOUNCE_ANGLE) * speed * Vec2::new(side.facing(), 1.0);
+            }
+            sfx.write(PlaySfx(Sfx::Turn));
+            false
+        },
+    );
+    ball.velocity = velocity;
+    transform.translation = position.extend(transform.translation.z);
+    tempo.0 = (velocity.length() / SERVE_SPEED).clamp(1.0, 1.5);
+
+    if position.x.abs() < (COURT_WIDTH + BALL_SIZE) / 2.0 {
+        return;
+    }
+
+    let conceding = if position.x < 0.0 { Side::Left } else { Side::Right };
+    let scorer = conceding.other();
+    state.scores[scorer.index()] += 1;
+    state.serve_to = conceding;
+    state.serve.reset();
+    ball.velocity = Vec2::ZERO;
+    transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+    tempo.0 = 1.0;
+    screen_shake.add_trauma(0.3, &effects);
+
+    if state.scores[scorer.index()] < WINNING_SCORE {
+        sfx.write(PlaySfx(Sfx::Eat));
+        return;
+    }
+    state.winner = Some(scorer);
+    let lost_to_cpu = state.opponent == Some(Opponent::Cpu) && scorer == Side::Right;
+    sfx.write(PlaySfx(if lost_to_cpu { Sfx::Death } else { Sfx::LevelUp }));
+    spawn_flash(&mut commands, &effects, theme.flash.with_alpha(0.4), 0.4);
+    // Nothing to dissolve; this just moves the sequence on to the game-over screen.
+    death.start(std::iter::empty(), &effects);
+}
+
+fn update_score_text(state: Res<Match>, death: Res<DeathSequence>, mut query: Query<&mut Text, With<ScoreText>>) {
+    let [left, right] = state.scores;
+    for mut text in query.iter_mut() {
+        **text = match (state.opponent, state.winner) {
+            (None, _) => "Press 1 to play the CPU or 2 for two players".to_string(),
+            (Some(opponent), Some(winner)) if death.is_finished() => {
+                let result = match (opponent, winner) {
+                    (Opponent::Cpu, Side::Left) => "You win!",
+                    (Opponent::Cpu, Side::Right) => "The CPU wins!",
+                    (Opponent::Player, Side::Left) => "Left player wins!",
+                    (Opponent::Player, Side::Right) => "Right player wins!",
+                };
+                format!("{result} {left} - {right}\nPress Enter for menu")
+            }
+            _ if state.paused => "Paused - P to resume".to_string(),
+            _ => format!("{left} - {right}"),
+        };
+    }
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sprites: Query<(&mut Sprite, Has<CourtLine>, Has<Paddle>, Has<Ball>)>,
+    mut texts: Query<&mut TextColor, With<ScoreText>>,
+) {
+    clear_color.0 = theme.background;
+    for (mut sprite, is_line, is_paddle, is_ball) in sprites.iter_mut() {
+        if is_line {
+            sprite.color = theme.grid;
+        } else if is_paddle {
+            sprite.color = theme.head;
+        } else if is_ball {
+            sprite.color = theme.food;
+        }
+    }
+    for mut color in texts.iter_mut() {
+        color.0 = theme.text;
+    }
+}
@@ -0,0 +1,1970 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::SystemParam;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use bevy::window::{WindowCloseRequested, WindowFocused};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::audio::{MusicTempo, PlaySfx, Sfx};
+use crate::daily::{emoji_grid, DailyLog, DailyRequest, DailyResult, DailyRound, DailyRules};
+use crate::dev_console::{overlay_visible, run_console_commands, Cheats, ConsoleTarget, DebugLines, DevConsolePlugin};
+use crate::effects::{
+    spawn_burst, spawn_flash, Burst, CameraShake, DeathSequence, EffectsPlugin, EffectsSettings,
+    ScreenShake,
+};
+use crate::high_scores::HighScores;
+use crate::launcher::{add_game_states, MiniGame};
+use crate::menu::GameState;
+use crate::random::{GameRng, RngState};
+use crate::replay::{BestReplays, Ghost, Replay};
+use crate::save_state::RoundSave;
+use crate::settings::{BoardSize, GameMode, Settings, Speed};
+use crate::snake_events::{
+    add_snake_messages, FoodEaten, FoodKind, GameEnded, GameStarted, SnakeDied, SnakeMoved,
+};
+use crate::snake_rules::{play_tick, press_turn, take_queued_turn, Arena, Heading, TickOutcome};
+use crate::stats::{count_snake, track_snake, StatsPlugin};
+use crate::telemetry::{record_telemetry, TelemetryLog};
+use crate::theme::Theme;
+use crate::time_travel::{
+    record_history, save_round, travel_through_history, History, Rewind, RoundSnapshot, TimeTravel, TimeTravelPlugin,
+    TimeTravelSystems,
+};
+
+// Boards of this size are drawn at 1:1; other sizes zoom the camera to cover the same area.
+const GRID_WIDTH: i32 = 20;
+const GRID_HEIGHT: i32 = 20;
+const HUD_HEIGHT: f32 = 40.0;
+const CELL_SIZE: f32 = 20.0;
+const MOVE_INTERVAL: f32 = 0.15;
+const MIN_MOVE_INTERVAL: f32 = 0.06;
+const POINTS_PER_LEVEL: u32 = 50;
+const LEVEL_SPEEDUP: f32 = 0.9;
+const BODY_SAMPLES_PER_CELL: usize = 8;
+const BODY_HALF_WIDTH: f32 = (CELL_SIZE - 4.0) / 2.0;
+const HEAD_RADIUS: f32 = (CELL_SIZE - 2.0) / 2.0;
+const TAIL_TAPER_CELLS: f32 = 3.0;
+const GHOST_ALPHA: f32 = 0.3;
+
+#[derive(Component)]
+struct SnakeHead;
+
+#[derive(Component)]
+struct SnakeSegment;
+
+#[derive(Component)]
+struct Food;
+
+#[derive(Component)]
+struct GridLine;
+
+// One of the ghost's sprites, by index from its head.
+#[derive(Component)]
+struct GhostSegment(usize);
+
+#[derive(Resource, Default)]
+struct SnakeBody(Vec<Entity>);
+
+#[derive(Resource, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Heading for Direction {
+    fn reversed(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct CurrentDirection(Direction);
+
+#[derive(Resource)]
+struct NextDirection(Direction);
+
+// A turn pressed while another was still waiting for its tick.
+#[derive(Resource, Default)]
+struct QueuedTurn(Option<Direction>);
+
+#[derive(Component, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+// Grid cell a segment occupied before the last tick, used to interpolate rendering.
+#[derive(Component, Clone, Copy)]
+struct PreviousPosition(Position);
+
+#[derive(Resource)]
+struct Score(u32);
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Resource)]
+struct Level(u32);
+
+#[derive(Resource, Clone, Copy)]
+struct Board {
+    width: i32,
+    height: i32,
+}
+
+impl Board {
+    fn new(size: BoardSize) -> Self {
+        let cells = match size {
+            BoardSize::Small => 15,
+            BoardSize::Medium => 20,
+            BoardSize::Large => 30,
+        };
+        Self {
+            width: cells,
+            height: cells,
+        }
+    }
+
+    fn contains(&self, pos: &Position) -> bool {
+        pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height
+    }
+}
+
+impl Arena for Board {
+    type Cell = Position;
+    type Direction = Direction;
+
+    fn start(&self) -> (Vec<Position>, Direction) {
+        let start = Position {
+            x: self.width / 2,
+            y: self.height / 2,
+        };
+        ((0..3).map(|i| Position { x: start.x - i, ..start }).collect(), Direction::Right)
+    }
+
+    fn step(&self, head: Position, direction: Direction) -> Option<(Position, Direction)> {
+        let step = direction_vector(direction).as_ivec2();
+        let next = Position {
+            x: head.x + step.x,
+            y: head.y + step.y,
+        };
+        self.contains(&next).then_some((next, direction))
+    }
+
+    fn food_cell(&self, rng: &mut GameRng, body: &[Position]) -> Position {
+        loop {
+            let pos = Position {
+                x: rng.random_range(0..self.width),
+                y: rng.random_range(0..self.height),
+            };
+            if !body.contains(&pos) {
+                return pos;
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GameOver(bool);
+
+// What the current round is played under: the player's settings, the saved
+// round's when continuing, or the day's for a daily challenge.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+struct RoundRules {
+    mode: GameMode,
+    board_size: BoardSize,
+    speed: Speed,
+    daily: Option<DailyRound>,
+}
+
+// Time spent moving this round, for the daily challenge's result.
+#[derive(Resource, Default)]
+struct PlayTime(Duration);
+
+// The round so far, offered to `BestReplays` when it ends. `None` for a continued
+// round saved without one.
+#[derive(Resource, Default)]
+struct Recording(Option<Replay<Direction>>);
+
+#[derive(Resource, Default)]
+struct GhostRun(Option<Ghost<Board>>);
+
+#[derive(Resource, Clone, Copy, PartialEq, Default)]
+enum BodyStyle {
+    #[default]
+    Segments,
+    Continuous,
+}
+
+#[derive(Component)]
+struct BodyMesh;
+
+#[derive(Component)]
+struct GameCamera;
+
+/// A round in progress, as kept by [`RoundSave`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct SavedRound {
+    mode: GameMode,
+    board_size: BoardSize,
+    speed: Speed,
+    /// Head first.
+    snake: Vec<Position>,
+    food: Option<Position>,
+    direction: Direction,
+    next_direction: Direction,
+    score: u32,
+    level: u32,
+    /// Seconds already spent towards the next move.
+    step_progress: f32,
+    rng: RngState,
+    #[serde(default)]
+    recording: Option<Replay<Direction>>,
+    #[serde(default)]
+    daily: Option<DailyRound>,
+    /// Seconds spent moving before the round was saved.
+    #[serde(default)]
+    play_time: f32,
+}
+
+// The saved round being continued, if any, for the systems that set up a round.
+#[derive(Resource, Default)]
+struct ResumedRound(Option<SavedRound>);
+
+// What a `SavedRound` is taken from.
+#[derive(SystemParam)]
+struct RoundState<'w, 's> {
+    rules: Res<'w, RoundRules>,
+    snake_body: Res<'w, SnakeBody>,
+    positions: Query<'w, 's, &'static Position>,
+    food: Query<'w, 's, &'static Position, With<Food>>,
+    current_dir: Res<'w, CurrentDirection>,
+    next_dir: Res<'w, NextDirection>,
+    score: Res<'w, Score>,
+    level: Res<'w, Level>,
+    fixed_time: Res<'w, Time<Fixed>>,
+    rng: Res<'w, GameRng>,
+    recording: Res<'w, Recording>,
+    game_over: Res<'w, GameOver>,
+    play_time: Res<'w, PlayTime>,
+}
+
+impl RoundSnapshot<SavedRound> for RoundState<'_, '_> {
+    fn saved(&self) -> SavedRound {
+        SavedRound {
+            mode: self.rules.mode,
+            board_size: self.rules.board_size,
+            speed: self.rules.speed,
+            snake: self.snake_body.0.iter().filter_map(|&entity| self.positions.get(entity).ok()).copied().collect(),
+            food: self.food.single().ok().copied(),
+            direction: self.current_dir.0,
+            next_direction: self.next_dir.0,
+            score: self.score.0,
+            level: self.level.0,
+            step_progress: self.fixed_time.overstep().as_secs_f32(),
+            rng: self.rng.state(),
+            recording: self.recording.0.clone(),
+            daily: self.rules.daily,
+            play_time: self.play_time.0.as_secs_f32(),
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.game_over.0
+    }
+}
+
+/// A round in grid cells, for frontends that do not draw with Bevy such as the
+/// terminal one. Refreshed every frame while playing.
+#[derive(Resource, Clone, Default, PartialEq, Debug)]
+pub struct BoardSnapshot {
+    pub width: i32,
+    pub height: i32,
+    /// Head first.
+    pub snake: Vec<IVec2>,
+    pub food: Option<IVec2>,
+    pub score: u32,
+    pub level: u32,
+    /// Set once the death sequence has finished and a new round can start.
+    pub game_over: bool,
+}
+
+pub struct Snake2dPlugin;
+
+impl MiniGame for Snake2dPlugin {
+    const ID: &'static str = "snake-2d";
+    const NAME: &'static str = "Snake";
+    const THUMBNAIL: &'static [&'static str] = &[
+        "++++++++",
+        "+      +",
+        "+ oo#  +",
+        "+ o    +",
+        "+ o  * +",
+        "+ o    +",
+        "+      +",
+        "++++++++",
+    ];
+    const BOARD_SIZES: bool = true;
+    const GHOSTS: bool = true;
+    const DAILY: bool = true;
+    const TELEMETRY: bool = true;
+}
+
+impl Plugin for Snake2dPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_states(app);
+        add_snake_messages::<Position>(app);
+        if !app.is_plugin_added::<StatsPlugin>() {
+            app.add_plugins(StatsPlugin);
+        }
+        track_snake::<Position>(app);
+        if !app.is_plugin_added::<DevConsolePlugin>() {
+            app.add_plugins(DevConsolePlugin);
+        }
+        if !app.is_plugin_added::<TimeTravelPlugin>() {
+            app.add_plugins(TimeTravelPlugin);
+        }
+        app.add_plugins(EffectsPlugin)
+            .add_message::<PlaySfx>()
+            .add_message::<WindowCloseRequested>()
+            .add_message::<WindowFocused>()
+            .init_resource::<MusicTempo>()
+            .init_resource::<Theme>()
+            .init_resource::<Settings>()
+            .init_resource::<HighScores>()
+            .init_resource::<RoundSave>()
+            .init_resource::<BestReplays>()
+            .init_resource::<DailyLog>()
+            .init_resource::<DailyRequest>()
+            .init_resource::<GameRng>()
+            .init_resource::<ClearColor>()
+            .init_resource::<BodyStyle>()
+            .init_resource::<BoardSnapshot>()
+            .init_resource::<Recording>()
+            .init_resource::<GhostRun>()
+            .init_resource::<TelemetryLog>()
+            .init_resource::<History<SavedRound>>()
+            .add_systems(Self::on_enter(), (setup, apply_theme).chain())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    load_saved_round,
+                    reset_round,
+                    spawn_board,
+                    spawn_snake,
+                    spawn_initial_food,
+                    record_history::<RoundState, SavedRound>,
+                )
+                    .chain()
+                    .run_if(Self::is_active()),
+            )
+            .add_systems(OnExit(GameState::Playing), save_round::<RoundState, SavedRound>.run_if(Self::is_active()))
+            .add_systems(
+                Update,
+                apply_theme.run_if(Self::is_active().and(resource_changed::<Theme>)),
+            )
+            .add_systems(
+                RunFixedMainLoop,
+                input_handler
+                    .in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop)
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    advance_snake,
+                    grow_snake,
+                    score_food,
+                    end_round,
+                    respawn_food,
+                    step_ghost,
+                    count_play_time,
+                    update_level,
+                    record_history::<RoundState, SavedRound>,
+                    record_telemetry::<Position>,
+                    count_snake::<Position>,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_body_style,
+                    finish_daily,
+                    record_result,
+                    play_sounds,
+                    burst_food,
+                    show_death,
+                    interpolate_segments,
+                    draw_ghost,
+                    apply_body_style,
+                    update_body_mesh,
+                    update_score_text,
+                    take_snapshot,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                Update,
+                (
+                    run_console_commands::<ConsoleSnake>,
+                    travel_through_history::<SavedRound>.after(TimeTravelSystems),
+                    describe_round.run_if(overlay_visible),
+                )
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                Update,
+                save_round::<RoundState, SavedRound>.run_if(
+                    in_state(GameState::Playing)
+                        .and(Self::is_active())
+                        .and(on_message::<WindowCloseRequested>.or(on_message::<WindowFocused>)),
+                ),
+            );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2d, CameraShake::new(8.0), GameCamera, DespawnOnExit(Snake2dPlugin::active())));
+}
+
+fn tick_interval(speed: Speed, level: u32) -> f32 {
+    (MOVE_INTERVAL * speed.interval_scale() * LEVEL_SPEEDUP.powi(level as i32)).max(MIN_MOVE_INTERVAL)
+}
+
+// Takes the saved round if the player chose to continue it.
+fn load_saved_round(mut commands: Commands, mut round_save: ResMut<RoundSave>) {
+    let round = round_save.take::<SavedRound>().filter(|round| !round.snake.is_empty());
+    commands.insert_resource(ResumedRound(round));
+}
+
+// A continued round carries on under the rules it was started with. A daily
+// round only counts as the day's scored attempt if that is still unused.
+fn round_rules(
+    settings: &Settings,
+    resumed: Option<&SavedRound>,
+    daily: Option<DailyRound>,
+    daily_log: &mut DailyLog,
+) -> RoundRules {
+    if let Some(round) = resumed {
+        return RoundRules {
+            mode: round.mode,
+            board_size: round.board_size,
+            speed: round.speed,
+            daily: round.daily,
+        };
+    }
+    match daily {
+        Some(daily) => {
+            let rules = DailyRules::for_date(daily.date);
+            RoundRules {
+                mode: GameMode::Classic,
+                board_size: rules.board_size,
+                speed: rules.speed,
+                daily: Some(DailyRound {
+                    scored: daily.scored && daily_log.start(daily.date),
+                    ..daily
+                }),
+            }
+        }
+        None => RoundRules {
+            mode: settings.gameplay.mode,
+            board_size: settings.gameplay.board_size,
+            speed: settings.gameplay.speed,
+            daily: None,
+        },
+    }
+}
+
+fn reset_round(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    resumed: Res<ResumedRound>,
+    mut daily_request: ResMut<DailyRequest>,
+    mut daily_log: ResMut<DailyLog>,
+    best_replays: Res<BestReplays>,
+    mut rng: ResMut<GameRng>,
+    mut death: ResMut<DeathSequence>,
+    mut tempo: ResMut<MusicTempo>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut camera: Query<&mut Transform, With<GameCamera>>,
+    mut game_started: MessageWriter<GameStarted>,
+) {
+    let resumed = resumed.0.as_ref();
+    let rules = round_rules(&settings, resumed, daily_request.0.take(), &mut daily_log);
+    let board = Board::new(rules.board_size);
+    let level = resumed.map_or(0, |round| round.level);
+    let interval = tick_interval(rules.speed, level);
+
+    commands.insert_resource(rules);
+    commands.insert_resource(board);
+    commands.insert_resource(CurrentDirection(resumed.map_or(Direction::Right, |round| round.direction)));
+    commands.insert_resource(NextDirection(resumed.map_or(Direction::Right, |round| round.next_direction)));
+    commands.insert_resource(QueuedTurn::default());
+    commands.insert_resource(SnakeBody::default());
+    commands.insert_resource(Score(resumed.map_or(0, |round| round.score)));
+    commands.insert_resource(Level(level));
+    commands.insert_resource(GameOver(false));
+    commands.insert_resource(PlayTime(Duration::from_secs_f32(resumed.map_or(0.0, |round| round.play_time))));
+    *death = DeathSequence::default();
+    tempo.0 = MOVE_INTERVAL / interval;
+
+    // One fixed step is one move. Time owed from before the round is dropped so
+    // the first move comes a full interval after the start, or as long after
+    // as was left when a continued round was saved.
+    fixed_time.set_timestep(Duration::from_secs_f32(interval));
+    let overstep = fixed_time.overstep();
+    fixed_time.discard_overstep(overstep);
+    if let Some(round) = resumed {
+        fixed_time.accumulate_overstep(Duration::from_secs_f32(round.step_progress));
+    }
+
+    // A fresh round racing the ghost starts from the best run's RNG, so food
+    // turns up in the same places for both. A continued round keeps its ghost
+    // if it was racing the same run, caught up to where the round was left.
+    // Daily rounds draw their food from the date instead and have no ghost.
+    let best = (settings.gameplay.ghost && rules.daily.is_none())
+        .then(|| best_replays.best::<Direction>(rules.mode, Some(rules.board_size)))
+        .flatten();
+    let recording = match (resumed, rules.daily) {
+        (Some(round), _) => {
+            *rng = GameRng::from_state(round.rng);
+            round.recording.clone()
+        }
+        (None, Some(daily)) => {
+            *rng = GameRng::seeded(daily.date.seed());
+            Some(Replay::new(rng.state(), rules.mode, Some(rules.board_size)))
+        }
+        (None, None) => {
+            if let Some(best) = &best {
+                *rng = GameRng::from_state(best.rng);
+            }
+            Some(Replay::new(rng.state(), rules.mode, Some(rules.board_size)))
+        }
+    };
+    let ghost = best.zip(recording.as_ref()).filter(|(best, recording)| best.rng == recording.rng).map(
+        |(best, recording)| {
+            let mut ghost = Ghost::new(&board, best);
+            ghost.catch_up(&board, recording.ticks);
+            ghost
+        },
+    );
+    commands.insert_resource(Recording(recording));
+    commands.insert_resource(GhostRun(ghost));
+    commands.insert_resource(History::<SavedRound>::default());
+    game_started.write(GameStarted {
+        resumed: resumed.is_some(),
+        rng: rng.state(),
+        mode: rules.mode,
+        speed: rules.speed,
+        board_size: Some(rules.board_size),
+    });
+
+    // Centre the board below the HUD, zooming so it fills the same screen area.
+    let zoom = board.width.max(board.height) as f32 / GRID_WIDTH.max(GRID_HEIGHT) as f32;
+    for mut transform in camera.iter_mut() {
+        *transform = Transform::from_xyz(
+            board.width as f32 * CELL_SIZE / 2.0,
+            board.height as f32 * CELL_SIZE / 2.0 + HUD_HEIGHT / 2.0 * zoom,
+            0.0,
+        )
+        .with_scale(Vec3::splat(zoom));
+    }
+}
+
+fn spawn_board(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    board: Res<Board>,
+) {
+    commands.spawn((
+        Mesh2d(meshes.add(Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        ))),
+        MeshMaterial2d(materials.add(ColorMaterial::default())),
+        Transform::default(),
+        Visibility::Hidden,
+        BodyMesh,
+        DespawnOnExit(GameState::Playing),
+    ));
+
+    let width = board.width as f32 * CELL_SIZE;
+    let height = board.height as f32 * CELL_SIZE;
+
+    for x in 0..=board.width {
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(Vec2::new(2.0, height)),
+                ..default()
+            },
+            Transform::from_xyz(x as f32 * CELL_SIZE, height / 2.0, 0.0),
+            GridLine,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+    for y in 0..=board.height {
+        commands.spawn((
+            Sprite {
+                color: theme.grid,
+                custom_size: Some(Vec2::new(width, 2.0)),
+                ..default()
+            },
+            Transform::from_xyz(width / 2.0, y as f32 * CELL_SIZE, 0.0),
+            GridLine,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+
+    commands.spawn((
+        Text::new("Score: 0"),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        ScoreText,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+// The board's bottom-left corner sits at the world origin; the camera is moved to frame it.
+fn grid_to_world(pos: &Position) -> Vec3 {
+    Vec3::new(
+        pos.x as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+        pos.y as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+        1.0,
+    )
+}
+
+fn food_bundle(pos: Position, theme: &Theme) -> impl Bundle {
+    let shape = theme.food_shape;
+    (
+        Sprite {
+            color: theme.food,
+            custom_size: Some(Vec2::splat((CELL_SIZE - 4.0) * shape.scale())),
+            ..default()
+        },
+        Transform::from_translation(grid_to_world(&pos)).with_rotation(Quat::from_rotation_z(shape.angle())),
+        Food,
+        pos,
+        DespawnOnExit(GameState::Playing),
+    )
+}
+
+fn head_bundle(pos: Position, theme: &Theme) -> impl Bundle {
+    (
+        Sprite {
+            color: theme.head,
+            custom_size: Some(Vec2::splat(CELL_SIZE - 2.0)),
+            ..default()
+        },
+        Transform::from_translation(grid_to_world(&pos)),
+        SnakeHead,
+        pos,
+        PreviousPosition(pos),
+        DespawnOnExit(GameState::Playing),
+    )
+}
+
+fn segment_bundle(pos: Position, theme: &Theme) -> impl Bundle {
+    (
+        Sprite {
+            color: theme.body,
+            custom_size: Some(Vec2::splat(CELL_SIZE - 4.0)),
+            ..default()
+        },
+        Transform::from_translation(grid_to_world(&pos)),
+        SnakeSegment,
+        pos,
+        PreviousPosition(pos),
+        DespawnOnExit(GameState::Playing),
+    )
+}
+
+fn spawn_snake(
+    mut commands: Commands,
+    mut snake_body: ResMut<SnakeBody>,
+    resumed: Res<ResumedRound>,
+    theme: Res<Theme>,
+    board: Res<Board>,
+) {
+    let cells = match &resumed.0 {
+        Some(round) => round.snake.clone(),
+        None => board.start().0,
+    };
+
+    for (i, &pos) in cells.iter().enumerate() {
+        let entity = if i == 0 {
+            commands.spawn(head_bundle(pos, &theme)).id()
+        } else {
+            commands.spawn(segment_bundle(pos, &theme)).id()
+        };
+        snake_body.0.push(entity);
+    }
+}
+
+fn spawn_initial_food(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    resumed: Res<ResumedRound>,
+    theme: Res<Theme>,
+    board: Res<Board>,
+) {
+    let food_pos = match &resumed.0 {
+        Some(round) => round.food,
+        None => Some(board.food_cell(&mut rng, &board.start().0)),
+    };
+
+    if let Some(food_pos) = food_pos {
+        commands.spawn(food_bundle(food_pos, &theme));
+    }
+}
+
+fn input_handler(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    current_dir: Res<CurrentDirection>,
+    mut next_dir: ResMut<NextDirection>,
+    mut queued: ResMut<QueuedTurn>,
+) {
+    let [up, down, left, right] = settings.gameplay.controls.bindings();
+    let pressed = if keyboard.any_just_pressed(up.iter().copied()) {
+        Some(Direction::Up)
+    } else if keyboard.any_just_pressed(down.iter().copied()) {
+        Some(Direction::Down)
+    } else if keyboard.any_just_pressed(left.iter().copied()) {
+        Some(Direction::Left)
+    } else if keyboard.any_just_pressed(right.iter().copied()) {
+        Some(Direction::Right)
+    } else {
+        None
+    };
+
+    if let Some(dir) = pressed {
+        press_turn(current_dir.0, &mut next_dir.0, &mut queued.0, dir);
+    }
+}
+
+// Resolves one fixed step: the cell ahead of the head decides whether the snake
+// dies, eats or just moves, and the whole body moves at once. Runs once per
+// step, so a long frame catches up with every move it owes. Everything that
+// follows from the move listens for the messages it writes.
+fn advance_snake(
+    mut current_dir: ResMut<CurrentDirection>,
+    mut next_dir: ResMut<NextDirection>,
+    mut queued: ResMut<QueuedTurn>,
+    snake_body: Res<SnakeBody>,
+    mut positions: Query<(&mut Position, &mut PreviousPosition), Without<Food>>,
+    food: Query<&Position, With<Food>>,
+    board: Res<Board>,
+    rules: Res<RoundRules>,
+    game_over: Res<GameOver>,
+    cheats: Res<Cheats>,
+    mut recording: ResMut<Recording>,
+    mut snake_moved: MessageWriter<SnakeMoved>,
+    mut food_eaten: MessageWriter<FoodEaten<Position>>,
+    mut snake_died: MessageWriter<SnakeDied<Position>>,
+) {
+    if game_over.0 {
+        return;
+    }
+
+    let turned = current_dir.0 != next_dir.0;
+    current_dir.0 = next_dir.0;
+    next_dir.0 = take_queued_turn(current_dir.0, &mut queued.0);
+
+    let body: Vec<Position> = snake_body
+        .0
+        .iter()
+        .filter_map(|&entity| positions.get(entity).ok())
+        .map(|(pos, _)| *pos)
+        .collect();
+    if body.is_empty() {
+        return;
+    }
+    // A cheated round is off the record, ghost included.
+    if cheats.cheated {
+        recording.0 = None;
+    }
+    if let Some(recording) = &mut recording.0 {
+        recording.record(current_dir.0);
+    }
+    let food = food.single().ok().copied();
+
+    let self_collision = rules.mode.self_collision() && !cheats.god_mode;
+    let (outcome, moved, _) = play_tick(&*board, &body, food.as_ref(), current_dir.0, self_collision);
+    if let TickOutcome::Died(cause) = outcome {
+        // God mode skips the move instead, leaving the snake at the wall until it turns.
+        if !cheats.god_mode {
+            snake_died.write(SnakeDied { cause, pos: body[0] });
+        }
+        return;
+    }
+
+    for (&entity, &pos) in snake_body.0.iter().zip(&moved) {
+        if let Ok((mut current, mut prev)) = positions.get_mut(entity) {
+            prev.0 = *current;
+            *current = pos;
+        }
+    }
+    snake_moved.write(SnakeMoved {
+        turned,
+        length: moved.len() as u32,
+    });
+    if let Some(pos) = food.filter(|_| outcome == TickOutcome::Ate) {
+        food_eaten.write(FoodEaten {
+            pos,
+            kind: FoodKind::Plain,
+        });
+    }
+}
+
+// The new segment waits in the cell the tail just left.
+fn grow_snake(
+    mut commands: Commands,
+    mut food_eaten: MessageReader<FoodEaten<Position>>,
+    mut snake_body: ResMut<SnakeBody>,
+    previous: Query<&PreviousPosition>,
+    theme: Res<Theme>,
+) {
+    for _ in food_eaten.read() {
+        let Some(tail) = snake_body.0.last().and_then(|&entity| previous.get(entity).ok()) else {
+            continue;
+        };
+        let segment = commands.spawn(segment_bundle(tail.0, &theme)).id();
+        snake_body.0.push(segment);
+    }
+}
+
+fn score_food(mut food_eaten: MessageReader<FoodEaten<Position>>, mut score: ResMut<Score>) {
+    for eaten in food_eaten.read() {
+        score.0 += eaten.kind.points();
+    }
+}
+
+// Runs after the snake has grown, so the next food keeps clear of the new segment.
+fn respawn_food(
+    mut commands: Commands,
+    mut food_eaten: MessageReader<FoodEaten<Position>>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<&Position>,
+    food: Query<(Entity, &Position), With<Food>>,
+    board: Res<Board>,
+    mut rng: ResMut<GameRng>,
+    theme: Res<Theme>,
+) {
+    for eaten in food_eaten.read() {
+        for (entity, _) in food.iter().filter(|(_, &pos)| pos == eaten.pos) {
+            commands.entity(entity).despawn();
+        }
+        let body: Vec<Position> = snake_body.0.iter().filter_map(|&entity| positions.get(entity).ok()).copied().collect();
+        commands.spawn(food_bundle(board.food_cell(&mut rng, &body), &theme));
+    }
+}
+
+fn end_round(
+    mut snake_died: MessageReader<SnakeDied<Position>>,
+    score: Res<Score>,
+    mut game_over: ResMut<GameOver>,
+    mut game_ended: MessageWriter<GameEnded>,
+) {
+    if snake_died.read().count() > 0 {
+        game_over.0 = true;
+        game_ended.write(GameEnded { score: score.0 });
+    }
+}
+
+fn play_sounds(
+    mut snake_moved: MessageReader<SnakeMoved>,
+    mut food_eaten: MessageReader<FoodEaten<Position>>,
+    mut snake_died: MessageReader<SnakeDied<Position>>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    for _ in snake_moved.read().filter(|moved| moved.turned) {
+        sfx.write(PlaySfx(Sfx::Turn));
+    }
+    for _ in food_eaten.read() {
+        sfx.write(PlaySfx(Sfx::Eat));
+    }
+    for _ in snake_died.read() {
+        sfx.write(PlaySfx(Sfx::Death));
+    }
+}
+
+fn burst_food(
+    mut commands: Commands,
+    mut food_eaten: MessageReader<FoodEaten<Position>>,
+    effects: Res<EffectsSettings>,
+    theme: Res<Theme>,
+) {
+    // How many particles there are depends on the effects settings, so their
+    // directions come from the thread RNG and leave the round's RNG alone.
+    let mut particle_rng = rand::rng();
+    for eaten in food_eaten.read() {
+        spawn_burst(
+            &mut commands,
+            &effects,
+            Burst {
+                origin: grid_to_world(&eaten.pos).with_z(2.0),
+                count: 16,
+                speed: 120.0,
+                lifetime: 0.4,
+                gravity: Vec3::ZERO,
+            },
+            || Vec2::from_angle(particle_rng.random_range(0.0..std::f32::consts::TAU)).extend(0.0),
+            || Sprite {
+                color: theme.food,
+                custom_size: Some(Vec2::splat(4.0)),
+                ..default()
+            },
+        );
+    }
+}
+
+// The ghost plays the tick the live snake just played, so both stop together.
+fn step_ghost(board: Res<Board>, recording: Res<Recording>, mut ghost: ResMut<GhostRun>) {
+    if let (Some(ghost), Some(recording)) = (&mut ghost.0, &recording.0) {
+        ghost.catch_up(&board, recording.ticks);
+    }
+}
+
+fn count_play_time(time: Res<Time>, game_over: Res<GameOver>, mut play_time: ResMut<PlayTime>) {
+    if !game_over.0 {
+        play_time.0 += time.delta();
+    }
+}
+
+// Rendering only: slides each segment from its previous cell to its current one
+// as the next fixed step approaches. The grid `Position` stays authoritative.
+fn interpolate_segments(
+    fixed_time: Res<Time<Fixed>>,
+    game_over: Res<GameOver>,
+    mut query: Query<(&Position, &PreviousPosition, &mut Transform)>,
+) {
+    let t = if game_over.0 { 1.0 } else { fixed_time.overstep_fraction() };
+
+    for (pos, prev, mut transform) in query.iter_mut() {
+        transform.translation = grid_to_world(&prev.0).lerp(grid_to_world(pos), t);
+    }
+}
+
+// Translucent sprites for the ghost beneath the live snake, sliding between cells
+// the same way. Sprites come and go as the ghost's length changes.
+fn draw_ghost(
+    mut commands: Commands,
+    ghost: Res<GhostRun>,
+    fixed_time: Res<Time<Fixed>>,
+    game_over: Res<GameOver>,
+    theme: Res<Theme>,
+    mut segments: Query<(Entity, &GhostSegment, &mut Sprite, &mut Transform)>,
+) {
+    let body = ghost.0.as_ref().map_or(&[][..], |ghost| &ghost.body);
+    let t = if game_over.0 { 1.0 } else { fixed_time.overstep_fraction() };
+    let place = |index: usize| {
+        let pos = &body[index];
+        let prev = ghost.0.as_ref().and_then(|ghost| ghost.previous.get(index)).unwrap_or(pos);
+        grid_to_world(prev).lerp(grid_to_world(pos), t).with_z(0.5)
+    };
+    let color = |index: usize| if index == 0 { theme.head } else { theme.body }.with_alpha(GHOST_ALPHA);
+
+    let mut drawn = 0;
+    for (entity, segment, mut sprite, mut transform) in segments.iter_mut() {
+        if segment.0 >= body.len() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sprite.color = color(segment.0);
+        transform.translation = place(segment.0);
+        drawn += 1;
+    }
+    for index in drawn..body.len() {
+        let size = if index == 0 { CELL_SIZE - 2.0 } else { CELL_SIZE - 4.0 };
+        commands.spawn((
+            Sprite {
+                color: color(index),
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            Transform::from_translation(place(index)),
+            GhostSegment(index),
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+}
+
+fn toggle_body_style(keyboard: Res<ButtonInput<KeyCode>>, mut style: ResMut<BodyStyle>) {
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        *style = match *style {
+            BodyStyle::Segments => BodyStyle::Continuous,
+            BodyStyle::Continuous => BodyStyle::Segments,
+        };
+    }
+}
+
+fn apply_body_style(
+    style: Res<BodyStyle>,
+    mut segments: Query<&mut Visibility, (Or<(With<SnakeHead>, With<SnakeSegment>)>, Without<BodyMesh>)>,
+    mut body_mesh: Query<&mut Visibility, With<BodyMesh>>,
+) {
+    let (segment_visibility, mesh_visibility) = match *style {
+        BodyStyle::Segments => (Visibility::Inherited, Visibility::Hidden),
+        BodyStyle::Continuous => (Visibility::Hidden, Visibility::Inherited),
+    };
+
+    for mut visibility in segments.iter_mut() {
+        visibility.set_if_neq(segment_visibility);
+    }
+    for mut visibility in body_mesh.iter_mut() {
+        visibility.set_if_neq(mesh_visibility);
+    }
+}
+
+fn direction_vector(dir: Direction) -> Vec2 {
+    match dir {
+        Direction::Up => Vec2::Y,
+        Direction::Down => Vec2::NEG_Y,
+        Direction::Left => Vec2::NEG_X,
+        Direction::Right => Vec2::X,
+    }
+}
+
+// Samples the centre line of the body between `head_u` and `tail_u`, measured in
+// cells along `cells` (head cell first). Corners are rounded with quadratic
+// fillets between the midpoints of neighbouring cells so the ribbon keeps its
+// width through bends. Returns (distance from head in cells, point) pairs.
+fn body_path(cells: &[Vec3], head_u: f32, tail_u: f32) -> Vec<(f32, Vec3)> {
+    if cells.len() < 2 {
+        return cells.iter().map(|&c| (0.0, c)).collect();
+    }
+
+    let mid = |i: usize| cells[i].lerp(cells[i + 1], 0.5);
+    let fillet = |j: usize, t: f32| {
+        mid(j - 1) * (1.0 - t) * (1.0 - t) + cells[j] * 2.0 * (1.0 - t) * t + mid(j) * t * t
+    };
+    let point_at = |u: f32| {
+        let k = (u.max(0.0) as usize).min(cells.len() - 2);
+        let f = u - k as f32;
+        if f < 0.5 && k >= 1 {
+            fillet(k, 0.5 + f)
+        } else if f >= 0.5 && k + 2 < cells.len() {
+            fillet(k + 1, f - 0.5)
+        } else {
+            cells[k].lerp(cells[k + 1], f)
+        }
+    };
+
+    let length = tail_u - head_u;
+    let samples = ((length * BODY_SAMPLES_PER_CELL as f32).ceil() as usize).max(1);
+    (0..=samples)
+        .map(|i| {
+            let d = length * i as f32 / samples as f32;
+            (d, point_at(head_u + d))
+        })
+        .collect()
+}
+
+fn push_disc(
+    positions: &mut Vec<[f32; 3]>,
+    colors: &mut Vec<[f32; 4]>,
+    indices: &mut Vec<u32>,
+    center: Vec3,
+    radius: f32,
+    color: Color,
+) {
+    const SIDES: u32 = 16;
+    let base = positions.len() as u32;
+    let color = color.to_linear().to_f32_array();
+
+    positions.push(center.to_array());
+    colors.push(color);
+    for i in 0..SIDES {
+        let angle = i as f32 / SIDES as f32 * std::f32::consts::TAU;
+        positions.push((center + Vec3::new(angle.cos(), angle.sin(), 0.0) * radius).to_array());
+        colors.push(color);
+        indices.extend([base, base + 1 + i, base + 1 + (i + 1) % SIDES]);
+    }
+}
+
+fn build_body_mesh(path: &[(f32, Vec3)], length: f32, head_dir: Vec2, theme: &Theme) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 4]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    let body_color = theme.body.to_linear().to_f32_array();
+    let mut normal = Vec2::Y;
+    for (i, &(distance, point)) in path.iter().enumerate() {
+        let prev = path[i.saturating_sub(1)].1;
+        let next = path[(i + 1).min(path.len() - 1)].1;
+        if let Some(tangent) = (next - prev).truncate().try_normalize() {
+            normal = tangent.perp();
+        }
+
+        let half_width = BODY_HALF_WIDTH * ((length - distance) / TAIL_TAPER_CELLS).clamp(0.2, 1.0);
+        let offset = (normal * half_width).extend(0.0);
+        positions.push((point + offset).with_z(1.0).to_array());
+        positions.push((point - offset).with_z(1.0).to_array());
+        colors.extend([body_color, body_color]);
+
+        if i + 1 < path.len() {
+            let base = 2 * i as u32;
+            indices.extend([base, base + 1, base + 2, base + 1, base + 3, base + 2]);
+        }
+    }
+
+    if let Some(&(_, head)) = path.first() {
+        let side = head_dir.perp().extend(0.0);
+        let forward = head_dir.extend(0.0);
+        push_disc(&mut positions, &mut colors, &mut indices, head.with_z(1.1), HEAD_RADIUS, theme.head);
+        for eye_side in [-1.0, 1.0] {
+            let eye = head.with_z(1.2) + forward * 3.0 + side * eye_side * 4.5;
+            push_disc(&mut positions, &mut colors, &mut indices, eye, 2.8, Color::WHITE);
+            push_disc(&mut positions, &mut colors, &mut indices, (eye + forward * 1.2).with_z(1.3), 1.4, Color::BLACK);
+        }
+    }
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+fn update_body_mesh(
+    style: Res<BodyStyle>,
+    fixed_time: Res<Time<Fixed>>,
+    game_over: Res<GameOver>,
+    current_dir: Res<CurrentDirection>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<(&Position, &PreviousPosition)>,
+    body_mesh: Query<&Mesh2d, With<BodyMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    theme: Res<Theme>,
+) {
+    if *style != BodyStyle::Continuous {
+        return;
+    }
+
+    let Ok(mesh_handle) = body_mesh.single() else {
+        return;
+    };
+
+    let segments: Vec<(Position, Position)> = snake_body
+        .0
+        .iter()
+        .filter_map(|&entity| positions.get(entity).ok())
+        .map(|(pos, prev)| (*pos, prev.0))
+        .collect();
+    let (Some(&(head, head_prev)), Some(&(tail, tail_prev))) = (segments.first(), segments.last()) else {
+        return;
+    };
+
+    // The head slides in from its previous cell and the tail slides out of the
+    // cell it is leaving, so both ends sit part-way along the chain of cells.
+    let t = if game_over.0 { 1.0 } else { fixed_time.overstep_fraction() };
+    let mut cells: Vec<Vec3> = segments.iter().map(|(pos, _)| grid_to_world(pos)).collect();
+    let head_u = if head == head_prev { 0.0 } else { 1.0 - t };
+    let mut tail_u = (cells.len() - 1) as f32;
+    if tail != tail_prev {
+        cells.push(grid_to_world(&tail_prev));
+        tail_u += 1.0 - t;
+    }
+
+    let path = body_path(&cells, head_u, tail_u);
+    if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+        *mesh = build_body_mesh(&path, tail_u - head_u, direction_vector(current_dir.0), &theme);
+    }
+}
+
+// Carries out the developer console's commands. A round they change no longer
+// plays out as recorded, so it isn't offered as a best run.
+// What the console commands change in a round.
+#[derive(SystemParam)]
+struct ConsoleSnake<'w, 's> {
+    commands: Commands<'w, 's>,
+    snake_body: ResMut<'w, SnakeBody>,
+    positions: Query<'w, 's, (&'static mut Position, &'static mut PreviousPosition), Without<Food>>,
+    food: Query<'w, 's, Entity, With<Food>>,
+    board: Res<'w, Board>,
+    current_dir: Res<'w, CurrentDirection>,
+    theme: Res<'w, Theme>,
+}
+
+impl ConsoleTarget for ConsoleSnake<'_, '_> {
+    type Cell = Position;
+
+    fn cell(&self, face: Option<&str>, x: i32, y: i32) -> Result<Position, String> {
+        if face.is_some() {
+            return Err("the board has no faces; give just X Y".to_string());
+        }
+        let pos = Position { x, y };
+        if !self.board.contains(&pos) {
+            return Err(format!("({x}, {y}) is off the {}x{} board", self.board.width, self.board.height));
+        }
+        Ok(pos)
+    }
+
+    fn describe(&self, pos: Position) -> String {
+        format!("({}, {})", pos.x, pos.y)
+    }
+
+    fn grow(&mut self, count: u32) -> Option<usize> {
+        let tail = self.snake_body.0.last().and_then(|&entity| self.positions.get(entity).ok()).map(|(pos, _)| *pos)?;
+        for _ in 0..count {
+            let segment = self.commands.spawn(segment_bundle(tail, &self.theme)).id();
+            self.snake_body.0.push(segment);
+        }
+        Some(self.snake_body.0.len())
+    }
+
+    fn spawn_food(&mut self, pos: Position) {
+        for entity in self.food.iter() {
+            self.commands.entity(entity).despawn();
+        }
+        self.commands.spawn(food_bundle(pos, &self.theme));
+    }
+
+    // The body bunches up where it meets a wall.
+    fn teleport(&mut self, pos: Position) {
+        let mut cell = pos;
+        for &entity in &self.snake_body.0 {
+            if let Ok((mut current, mut prev)) = self.positions.get_mut(entity) {
+                *current = cell;
+                prev.0 = cell;
+            }
+            cell = self.board.step(cell, self.current_dir.0.reversed()).map_or(cell, |(next, _)| next);
+        }
+    }
+}
+
+fn describe_round(
+    current_dir: Res<CurrentDirection>,
+    next_dir: Res<NextDirection>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<&Position>,
+    food: Query<&Position, With<Food>>,
+    board: Res<Board>,
+    cheats: Res<Cheats>,
+    mut lines: ResMut<DebugLines>,
+) {
+    let cell = |pos: &Position| format!("({}, {})", pos.x, pos.y);
+    let head = snake_body.0.first().and_then(|&entity| positions.get(entity).ok());
+    lines.0.extend([
+        format!("Direction: {:?}, next {:?}", current_dir.0, next_dir.0),
+        format!("Head: {}", head.map_or("-".to_string(), cell)),
+        format!("Length: {}", snake_body.0.len()),
+        format!("Food: {}", food.single().map_or("-".to_string(), cell)),
+        format!("Board: {}x{}", board.width, board.height),
+    ]);
+    if cheats.god_mode {
+        lines.0.push("God mode".to_string());
+    }
+}
+
+// Every POINTS_PER_LEVEL points shortens the tick and speeds the music up to match.
+fn update_level(
+    score: Res<Score>,
+    rules: Res<RoundRules>,
+    mut level: ResMut<Level>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut tempo: ResMut<MusicTempo>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let new_level = score.0 / POINTS_PER_LEVEL;
+    if new_level <= level.0 {
+        return;
+    }
+
+    level.0 = new_level;
+    let interval = tick_interval(rules.speed, new_level);
+    fixed_time.set_timestep(Duration::from_secs_f32(interval));
+    tempo.0 = MOVE_INTERVAL / interval;
+    sfx.write(PlaySfx(Sfx::LevelUp));
+}
+
+impl Rewind for SavedRound {
+    // The snake and food are spawned afresh, which also brings a dead snake back.
+    fn restore(&self, world: &mut World) {
+        let stale: Vec<Entity> = world
+            .query_filtered::<Entity, Or<(With<SnakeHead>, With<SnakeSegment>, With<Food>)>>()
+            .iter(world)
+            .collect();
+        for entity in stale {
+            world.despawn(entity);
+        }
+        let theme = world.resource::<Theme>().clone();
+        let body = self
+            .snake
+            .iter()
+            .enumerate()
+            .map(|(i, &pos)| match i {
+                0 => world.spawn(head_bundle(pos, &theme)).id(),
+                _ => world.spawn(segment_bundle(pos, &theme)).id(),
+            })
+            .collect();
+        world.insert_resource(SnakeBody(body));
+        if let Some(food) = self.food {
+            world.spawn(food_bundle(food, &theme));
+        }
+
+        world.insert_resource(CurrentDirection(self.direction));
+        world.insert_resource(NextDirection(self.next_direction));
+        world.insert_resource(QueuedTurn::default());
+        world.insert_resource(Score(self.score));
+        world.insert_resource(Level(self.level));
+        world.insert_resource(GameOver(false));
+        world.insert_resource(PlayTime(Duration::from_secs_f32(self.play_time)));
+        world.insert_resource(GameRng::from_state(self.rng));
+        world.insert_resource(Recording(self.recording.clone()));
+        world.insert_resource(DeathSequence::default());
+
+        let interval = tick_interval(self.speed, self.level);
+        world.resource_mut::<Time<Fixed>>().set_timestep(Duration::from_secs_f32(interval));
+        world.resource_mut::<MusicTempo>().0 = MOVE_INTERVAL / interval;
+        let board = *world.resource::<Board>();
+        if let (Some(ghost), Some(recording)) = (&mut world.resource_mut::<GhostRun>().0, &self.recording) {
+            ghost.rewind(&board, recording.ticks);
+        }
+    }
+}
+
+// Keeps how the day's scored attempt went as it ends, and logs the text for
+// sharing it.
+fn finish_daily(
+    mut game_ended: MessageReader<GameEnded>,
+    rules: Res<RoundRules>,
+    board: Res<Board>,
+    play_time: Res<PlayTime>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<&Position>,
+    food: Query<&Position, With<Food>>,
+    travel: Res<TimeTravel>,
+    cheats: Res<Cheats>,
+    mut daily_log: ResMut<DailyLog>,
+) {
+    let Some(ended) = game_ended.read().last() else {
+        return;
+    };
+    let Some(daily) = rules.daily.filter(|daily| daily.scored && !travel.rewound() && !cheats.cheated) else {
+        return;
+    };
+
+    let cell = |pos: &Position| IVec2::new(pos.x, pos.y);
+    let snake: Vec<IVec2> = snake_body.0.iter().filter_map(|&entity| positions.get(entity).ok()).map(cell).collect();
+    let result = DailyResult {
+        score: ended.score,
+        length: snake.len() as u32,
+        seconds: play_time.0.as_secs() as u32,
+        grid: emoji_grid(board.width, board.height, &snake, food.single().ok().map(cell)),
+    };
+    info!("{}", result.share_text(Snake2dPlugin::NAME, daily.date));
+    daily_log.finish(daily.date, result);
+}
+
+fn record_result(
+    mut game_ended: MessageReader<GameEnded>,
+    rules: Res<RoundRules>,
+    mut high_scores: ResMut<HighScores>,
+    mut round_save: ResMut<RoundSave>,
+    mut recording: ResMut<Recording>,
+    mut best_replays: ResMut<BestReplays>,
+    travel: Res<TimeTravel>,
+    cheats: Res<Cheats>,
+) {
+    for ended in game_ended.read() {
+        round_save.clear();
+        // A rewound round was scored when it first ended, and a cheated one isn't.
+        if travel.rewound() || cheats.cheated {
+            continue;
+        }
+        // Daily rounds are kept in the daily log instead.
+        if rules.daily.is_none() {
+            high_scores.record(ended.score, rules.mode);
+        }
+        if let Some(recording) = &mut recording.0 {
+            recording.score = ended.score;
+            best_replays.offer(recording);
+        }
+    }
+}
+
+fn show_death(
+    mut commands: Commands,
+    mut snake_died: MessageReader<SnakeDied<Position>>,
+    snake_body: Res<SnakeBody>,
+    effects: Res<EffectsSettings>,
+    mut death: ResMut<DeathSequence>,
+    mut screen_shake: ResMut<ScreenShake>,
+    theme: Res<Theme>,
+) {
+    if snake_died.read().count() == 0 || death.is_started() {
+        return;
+    }
+
+    screen_shake.add_trauma(0.8, &effects);
+    spawn_flash(&mut commands, &effects, theme.flash.with_alpha(0.6), 0.4);
+    death.start(snake_body.0.iter().copied(), &effects);
+}
+
+fn update_score_text(
+    score: Res<Score>,
+    level: Res<Level>,
+    death: Res<DeathSequence>,
+    high_scores: Res<HighScores>,
+    rules: Res<RoundRules>,
+    ghost: Res<GhostRun>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    for mut text in query.iter_mut() {
+        if death.is_finished() {
+            **text = match rules.daily {
+                Some(daily) if daily.scored => format!(
+                    "Daily {}: Final Score: {}\nCopy the result from the Daily Challenge menu\nPress Enter for menu",
+                    daily.date, score.0
+                ),
+                Some(daily) => format!("Practice {}: Final Score: {}\nPress Enter for menu", daily.date, score.0),
+                None => {
+                    let best = high_scores.best(rules.mode).unwrap_or(0);
+                    format!("Game Over! Final Score: {}  Best: {}\nPress Enter for menu", score.0, best)
+                }
+            };
+        } else {
+            **text = format!("Score: {}  Level: {}", score.0, level.0 + 1);
+            if let Some(ghost) = &ghost.0 {
+                **text += &format!("  vs Ghost: {:+}", i64::from(score.0) - i64::from(ghost.score));
+            }
+            if let Some(daily) = rules.daily {
+                **text += &format!("  Daily {}", daily.date);
+            }
+        }
+    }
+}
+
+fn take_snapshot(
+    board: Res<Board>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<&Position>,
+    food: Query<&Position, With<Food>>,
+    score: Res<Score>,
+    level: Res<Level>,
+    death: Res<DeathSequence>,
+    mut snapshot: ResMut<BoardSnapshot>,
+) {
+    let cell = |pos: &Position| IVec2::new(pos.x, pos.y);
+    *snapshot = BoardSnapshot {
+        width: board.width,
+        height: board.height,
+        snake: snake_body.0.iter().filter_map(|&entity| positions.get(entity).ok()).map(cell).collect(),
+        food: food.iter().next().map(cell),
+        score: score.0,
+        level: level.0 + 1,
+        game_over: death.is_finished(),
+    };
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sprites: Query<(&mut Sprite, Has<GridLine>, Has<SnakeHead>, Has<SnakeSegment>), Without<Food>>,
+    mut food: Query<(&mut Sprite, &mut Transform), With<Food>>,
+    mut texts: Query<&mut TextColor, With<ScoreText>>,
+) {
+    clear_color.0 = theme.background;
+    for (mut sprite, is_grid, is_head, is_segment) in sprites.iter_mut() {
+        if is_grid {
+            sprite.color = theme.grid;
+        } else if is_head {
+            sprite.color = theme.head;
+        } else if is_segment {
+            sprite.color = theme.body;
+        }
+    }
+    for (mut sprite, mut transform) in food.iter_mut() {
+        sprite.color = theme.food;
+        sprite.custom_size = Some(Vec2::splat((CELL_SIZE - 4.0) * theme.food_shape.scale()));
+        transform.rotation = Quat::from_rotation_z(theme.food_shape.angle());
+    }
+    for mut color in texts.iter_mut() {
+        color.0 = theme.text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+    use crate::daily::Date;
+    use crate::dev_console::ConsoleCommand;
+    use crate::launcher::ActiveGame;
+    use crate::stats::Stats;
+
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO))
+            .insert_resource(GameRng::seeded(0))
+            .add_plugins(Snake2dPlugin);
+
+        // Run startup in the picker, enter the game's menu, then start a round
+        // without letting any time pass.
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<ActiveGame>>()
+            .set(Snake2dPlugin::active());
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+        app
+    }
+
+    // Advances time by exactly one move, then lets go of any keys so each press
+    // lasts a single tick.
+    fn tick(app: &mut App) {
+        let step = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(step));
+        app.update();
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release_all();
+        keyboard.clear();
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(key);
+    }
+
+    // Presses and lets go of a key without letting time pass.
+    fn tap(app: &mut App, key: KeyCode) {
+        press(app, key);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release_all();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+    }
+
+    // Cells from head to tail.
+    fn body(app: &App) -> Vec<Position> {
+        let world = app.world();
+        world
+            .resource::<SnakeBody>()
+            .0
+            .iter()
+            .map(|&entity| *world.get::<Position>(entity).unwrap())
+            .collect()
+    }
+
+    fn set_state(app: &mut App, state: GameState) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+        app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+    }
+
+    fn food(app: &mut App) -> Position {
+        let world = app.world_mut();
+        *world.query_filtered::<&Position, With<Food>>().single(world).unwrap()
+    }
+
+    fn place_food(app: &mut App, pos: Position) {
+        let world = app.world_mut();
+        *world.query_filtered::<&mut Position, With<Food>>().single_mut(world).unwrap() = pos;
+    }
+
+    fn place_food_ahead_of_head(app: &mut App) {
+        let head = body(app)[0];
+        let step = direction_vector(app.world().resource::<NextDirection>().0).as_ivec2();
+        place_food(app, Position { x: head.x + step.x, y: head.y + step.y });
+    }
+
+    #[test]
+    fn eating_scores_and_grows_at_the_tail() {
+        let mut app = headless_app();
+        let before = body(&app);
+        assert_eq!(before.len(), 3);
+
+        // The new segment appears on the same tick, in the cell the tail left.
+        place_food_ahead_of_head(&mut app);
+        tick(&mut app);
+        assert_eq!(app.world().resource::<Score>().0, 10);
+        let grown = body(&app);
+        assert_eq!(grown.len(), 4);
+        assert_eq!(grown[1..], before[..]);
+    }
+
+    #[test]
+    fn running_into_a_wall_ends_the_round() {
+        let mut app = headless_app();
+        place_food(&mut app, Position { x: 0, y: 0 });
+        let board = *app.world().resource::<Board>();
+        let start = body(&app)[0];
+
+        for _ in start.x..board.width - 1 {
+            tick(&mut app);
+            assert!(!app.world().resource::<GameOver>().0);
+        }
+        assert_eq!(body(&app)[0].x, board.width - 1);
+
+        // The round ends before the head leaves the board.
+        tick(&mut app);
+        assert!(app.world().resource::<GameOver>().0);
+        assert_eq!(body(&app)[0].x, board.width - 1);
+        assert_eq!(app.world().resource::<Stats>().totals().wall_deaths, 1);
+    }
+
+    #[test]
+    fn stepping_back_from_a_death_puts_the_round_back() {
+        let mut app = headless_app();
+        place_food_ahead_of_head(&mut app);
+        tick(&mut app);
+        place_food(&mut app, Position { x: 0, y: 0 });
+        let board = *app.world().resource::<Board>();
+        let start = body(&app)[0];
+        tap(&mut app, KeyCode::F4);
+
+        let mut bodies = vec![body(&app)];
+        for _ in start.x..board.width - 1 {
+            tick(&mut app);
+            bodies.push(body(&app));
+        }
+        tick(&mut app);
+        assert!(app.world().resource::<GameOver>().0);
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+        tap(&mut app, KeyCode::F6);
+        assert_eq!(body(&app), bodies[bodies.len() - 2]);
+        assert!(!app.world().resource::<GameOver>().0);
+        tap(&mut app, KeyCode::F7);
+        assert_eq!(body(&app), bodies[bodies.len() - 1]);
+
+        // From the latest tick, stepping forward plays the next one, into the wall again.
+        tap(&mut app, KeyCode::F7);
+        app.update();
+        assert!(app.world().resource::<GameOver>().0);
+        assert_eq!(body(&app), bodies[bodies.len() - 1]);
+
+        // Only the first death counts.
+        assert_eq!(app.world().resource::<HighScores>().entries().len(), 1);
+        assert_eq!(app.world().resource::<Stats>().totals().wall_deaths, 1);
+        tap(&mut app, KeyCode::F6);
+        set_state(&mut app, GameState::Menu);
+        assert!(!app.world().resource::<RoundSave>().has_save());
+    }
+
+    #[test]
+    fn a_cheated_round_stays_off_the_record() {
+        let mut app = headless_app();
+        place_food_ahead_of_head(&mut app);
+        tick(&mut app);
+        app.world_mut().write_message(ConsoleCommand::Seed(7));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+        app.update();
+        place_food(&mut app, Position { x: 0, y: 0 });
+        let board = *app.world().resource::<Board>();
+
+        for _ in body(&app)[0].x..board.width {
+            tick(&mut app);
+        }
+        assert!(app.world().resource::<GameOver>().0);
+        assert!(app.world().resource::<HighScores>().entries().is_empty());
+        assert_eq!(app.world().resource::<Stats>().totals().wall_deaths, 0);
+
+        // The next round is back on the record.
+        set_state(&mut app, GameState::Menu);
+        set_state(&mut app, GameState::Playing);
+        assert!(!app.world().resource::<Cheats>().cheated);
+    }
+
+    #[test]
+    fn god_mode_waits_at_the_wall_for_a_turn() {
+        let mut app = headless_app();
+        place_food(&mut app, Position { x: 0, y: 0 });
+        let mut cheats = app.world_mut().resource_mut::<Cheats>();
+        (cheats.god_mode, cheats.cheated) = (true, true);
+        let board = *app.world().resource::<Board>();
+        let start = body(&app)[0];
+
+        for _ in start.x..board.width + 1 {
+            tick(&mut app);
+        }
+        assert!(!app.world().resource::<GameOver>().0);
+        assert_eq!(body(&app)[0], Position { x: board.width - 1, ..start });
+        assert!(app.world().resource::<Recording>().0.is_none());
+
+        press(&mut app, KeyCode::ArrowUp);
+        tick(&mut app);
+        assert_eq!(body(&app)[0], Position { x: board.width - 1, y: start.y + 1 });
+    }
+
+    #[test]
+    fn the_tick_reports_food_and_the_end_of_the_round() {
+        let mut app = headless_app();
+        place_food_ahead_of_head(&mut app);
+        let food_pos = food(&mut app);
+        tick(&mut app);
+
+        let eaten = app.world().resource::<Messages<FoodEaten<Position>>>();
+        let eaten: Vec<Position> = eaten.get_cursor().read(eaten).map(|eaten| eaten.pos).collect();
+        assert_eq!(eaten, [food_pos]);
+
+        // Straight on into the wall.
+        place_food(&mut app, Position { x: 0, y: 0 });
+        while !app.world().resource::<GameOver>().0 {
+            tick(&mut app);
+        }
+        let ended = app.world().resource::<Messages<GameEnded>>();
+        let scores: Vec<u32> = ended.get_cursor().read(ended).map(|ended| ended.score).collect();
+        assert_eq!(scores, [FoodKind::Plain.points()]);
+    }
+
+    #[test]
+    fn turning_into_the_body_ends_the_round() {
+        let mut app = headless_app();
+        for _ in 0..2 {
+            place_food_ahead_of_head(&mut app);
+            tick(&mut app);
+        }
+        place_food(&mut app, Position { x: 0, y: 0 });
+        assert_eq!(body(&app).len(), 5);
+
+        for key in [KeyCode::ArrowUp, KeyCode::ArrowLeft] {
+            press(&mut app, key);
+            tick(&mut app);
+            assert!(!app.world().resource::<GameOver>().0);
+        }
+        press(&mut app, KeyCode::ArrowDown);
+        tick(&mut app);
+        assert!(app.world().resource::<GameOver>().0);
+    }
+
+    #[test]
+    fn following_the_tail_is_safe() {
+        let mut app = headless_app();
+        place_food_ahead_of_head(&mut app);
+        tick(&mut app);
+        place_food(&mut app, Position { x: 0, y: 0 });
+        assert_eq!(body(&app).len(), 4);
+
+        // Four segments circling a 2x2 square: the last turn moves the head into
+        // the cell the tail is leaving on the same tick.
+        for key in [KeyCode::ArrowUp, KeyCode::ArrowLeft] {
+            press(&mut app, key);
+            tick(&mut app);
+        }
+        let tail = *body(&app).last().unwrap();
+        press(&mut app, KeyCode::ArrowDown);
+        tick(&mut app);
+        assert!(!app.world().resource::<GameOver>().0);
+        assert_eq!(body(&app)[0], tail);
+    }
+
+    #[test]
+    fn uneven_frames_run_every_owed_move() {
+        let mut app = headless_app();
+        place_food(&mut app, Position { x: 0, y: 0 });
+        let start = body(&app)[0];
+        let step = app.world().resource::<Time<Fixed>>().timestep();
+
+        // Two frames of a move and a half each: one move, then the two owed.
+        for (frame, moved) in [(step * 3 / 2, 1), (step * 3 / 2, 3)] {
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(frame));
+            app.update();
+            assert_eq!(body(&app)[0], Position { x: start.x + moved, y: start.y });
+        }
+    }
+
+    #[test]
+    fn a_continued_round_plays_on_as_if_never_left() {
+        let mut left = headless_app();
+        let mut stayed = headless_app();
+        for app in [&mut left, &mut stayed] {
+            place_food_ahead_of_head(app);
+            tick(app);
+            press(app, KeyCode::ArrowUp);
+            tick(app);
+            let half_step = app.world().resource::<Time<Fixed>>().timestep() / 2;
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(half_step));
+            app.update();
+        }
+
+        set_state(&mut left, GameState::Menu);
+        assert!(left.world().resource::<RoundSave>().has_save());
+        left.world_mut().resource_mut::<RoundSave>().request_resume();
+        set_state(&mut left, GameState::Playing);
+        assert!(!left.world().resource::<RoundSave>().has_save());
+
+        // Eating after the break draws the next food from the restored RNG.
+        for app in [&mut left, &mut stayed] {
+            place_food_ahead_of_head(app);
+            tick(app);
+            tick(app);
+        }
+        assert_eq!(body(&left), body(&stayed));
+        assert_eq!(food(&mut left), food(&mut stayed));
+        assert_eq!(left.world().resource::<Score>().0, 20);
+        assert_eq!(
+            left.world().resource::<Time<Fixed>>().overstep(),
+            stayed.world().resource::<Time<Fixed>>().overstep()
+        );
+    }
+
+    // One tick heading for the food, turning aside where it would mean reversing.
+    fn steer_to_food(app: &mut App) {
+        let (head, target) = (body(app)[0], food(app));
+        let heading = app.world().resource::<CurrentDirection>().0;
+        let wanted = if target.x > head.x {
+            Direction::Right
+        } else if target.x < head.x {
+            Direction::Left
+        } else if target.y > head.y {
+            Direction::Up
+        } else {
+            Direction::Down
+        };
+        let dir = if direction_vector(wanted) == -direction_vector(heading) {
+            if matches!(wanted, Direction::Left | Direction::Right) { Direction::Up } else { Direction::Right }
+        } else {
+            wanted
+        };
+        let key = match dir {
+            Direction::Up => KeyCode::ArrowUp,
+            Direction::Down => KeyCode::ArrowDown,
+            Direction::Left => KeyCode::ArrowLeft,
+            Direction::Right => KeyCode::ArrowRight,
+        };
+        press(app, key);
+        tick(app);
+    }
+
+    // Eats twice, then runs into whatever wall the snake is heading for.
+    fn play_scripted_run(app: &mut App, mut after_tick: impl FnMut(&mut App)) {
+        while app.world().resource::<Score>().0 < 20 {
+            steer_to_food(app);
+            after_tick(app);
+        }
+        while !app.world().resource::<GameOver>().0 {
+            tick(app);
+            after_tick(app);
+        }
+    }
+
+    #[test]
+    fn the_ghost_retraces_the_best_run() {
+        let mut app = headless_app();
+        let first_food = food(&mut app);
+        play_scripted_run(&mut app, |_| {});
+        let best = app.world().resource::<BestReplays>().best::<Direction>(GameMode::Classic, Some(BoardSize::Medium));
+        assert_eq!(best.map(|best| best.score), Some(20));
+
+        // Racing the ghost replays the same food, so the same inputs keep the
+        // two snakes together until both hit the wall.
+        app.world_mut().resource_mut::<Settings>().gameplay.ghost = true;
+        set_state(&mut app, GameState::Menu);
+        set_state(&mut app, GameState::Playing);
+        assert_eq!(food(&mut app), first_food);
+        play_scripted_run(&mut app, |app| {
+            let ghost = app.world().resource::<GhostRun>().0.as_ref().unwrap();
+            assert_eq!(ghost.body, body(app));
+            assert_eq!(ghost.score, app.world().resource::<Score>().0);
+        });
+        assert!(app.world().resource::<GhostRun>().0.as_ref().unwrap().is_finished());
+
+        let world = app.world_mut();
+        let text = world.query_filtered::<&Text, With<ScoreText>>().single(world).unwrap();
+        assert!(text.0.ends_with("vs Ghost: +0"), "{}", text.0);
+    }
+
+    fn start_daily(app: &mut App, round: DailyRound) {
+        app.world_mut().resource_mut::<DailyRequest>().0 = Some(round);
+        set_state(app, GameState::Menu);
+        set_state(app, GameState::Playing);
+    }
+
+    #[test]
+    fn a_daily_round_follows_the_date_not_the_settings() {
+        let date = Date { year: 2026, month: 10, day: 19 };
+        let mut first = headless_app();
+        let mut second = headless_app();
+        first.world_mut().resource_mut::<Settings>().gameplay.mode = GameMode::Zen;
+        second.insert_resource(GameRng::seeded(99));
+        for app in [&mut first, &mut second] {
+            start_daily(app, DailyRound { date, scored: true });
+        }
+
+        let rules = *first.world().resource::<RoundRules>();
+        assert_eq!(rules.mode, GameMode::Classic);
+        assert_eq!(rules.board_size, DailyRules::for_date(date).board_size);
+        assert_eq!(rules.daily, Some(DailyRound { date, scored: true }));
+        assert_eq!(food(&mut first), food(&mut second));
+
+        // The attempt is scored into the daily log rather than the high scores.
+        place_food(&mut first, Position { x: 0, y: 0 });
+        while !first.world().resource::<GameOver>().0 {
+            tick(&mut first);
+        }
+        first.update();
+        let result = first.world().resource::<DailyLog>().result(date).cloned().unwrap();
+        assert_eq!((result.score, result.length), (0, 3));
+        assert!(result.grid[0].chars().count() <= 10);
+        assert!(first.world().resource::<HighScores>().entries().is_empty());
+
+        // Trying again the same day is practice.
+        start_daily(&mut first, DailyRound { date, scored: true });
+        assert_eq!(first.world().resource::<RoundRules>().daily, Some(DailyRound { date, scored: false }));
+    }
+
+    #[test]
+    fn reversing_direction_is_ignored() {
+        let mut app = headless_app();
+        place_food(&mut app, Position { x: 0, y: 0 });
+        let start = body(&app)[0];
+
+        press(&mut app, KeyCode::ArrowLeft);
+        tick(&mut app);
+        assert_eq!(app.world().resource::<CurrentDirection>().0, Direction::Right);
+        assert_eq!(body(&app)[0], Position { x: start.x + 1, y: start.y });
+        assert!(!app.world().resource::<GameOver>().0);
+    }
+
+    #[test]
+    fn two_quick_turns_take_a_tick_each() {
+        let mut app = headless_app();
+        place_food(&mut app, Position { x: 0, y: 0 });
+        let start = body(&app)[0];
+
+        tap(&mut app, KeyCode::ArrowUp);
+        tap(&mut app, KeyCode::ArrowLeft);
+        tick(&mut app);
+        assert_eq!(body(&app)[0], Position { x: start.x, y: start.y + 1 });
+        tick(&mut app);
+        assert_eq!(body(&app)[0], Position { x: start.x - 1, y: start.y + 1 });
+
+        // A key still held from an earlier frame doesn't turn again.
+        press(&mut app, KeyCode::ArrowUp);
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+        tick(&mut app);
+        assert_eq!(app.world().resource::<CurrentDirection>().0, Direction::Left);
+    }
+}
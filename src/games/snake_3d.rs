@@ -0,0 +1,2337 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::SystemParam;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use bevy::window::{WindowCloseRequested, WindowFocused};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::audio::{MusicTempo, PlaySfx, Sfx};
+use crate::dev_console::{overlay_visible, run_console_commands, Cheats, ConsoleTarget, DebugLines, DevConsolePlugin};
+use crate::effects::{
+    spawn_burst, spawn_flash, Burst, CameraShake, DeathSequence, EffectsPlugin, EffectsSettings,
+    ScreenShake,
+};
+use crate::high_scores::HighScores;
+use crate::launcher::{add_game_states, MiniGame};
+use crate::menu::GameState;
+use crate::random::{GameRng, RngState};
+use crate::replay::{BestReplays, Ghost, Replay};
+use crate::save_state::RoundSave;
+use crate::settings::{GameMode, Settings, Speed};
+use crate::snake_events::{
+    add_snake_messages, FaceChanged, FoodEaten, FoodKind, GameEnded, GameStarted, SnakeDied, SnakeMoved,
+};
+use crate::snake_rules::{play_tick, press_turn, take_queued_turn, Arena, Heading, TickOutcome};
+use crate::stats::{count_faces, count_snake, track_faces, track_snake, StatsPlugin};
+use crate::telemetry::{record_telemetry, TelemetryLog};
+use crate::theme::Theme;
+use crate::time_travel::{
+    record_history, save_round, travel_through_history, History, Rewind, RoundSnapshot, TimeTravel, TimeTravelPlugin,
+    TimeTravelSystems,
+};
+
+const GRID_SIZE: i32 = 10;
+const CELL_SIZE: f32 = 0.5;
+const CUBE_SIZE: f32 = GRID_SIZE as f32 * CELL_SIZE;
+const MOVE_INTERVAL: f32 = 0.2;
+const MIN_MOVE_INTERVAL: f32 = 0.08;
+const POINTS_PER_LEVEL: u32 = 50;
+const LEVEL_SPEEDUP: f32 = 0.9;
+const CAMERA_DISTANCE: f32 = 12.0;
+const SURFACE_OFFSET: f32 = 0.1;
+const BODY_SAMPLES_PER_CELL: usize = 8;
+const BODY_RING_SIDES: u32 = 10;
+const BODY_RADIUS: f32 = CELL_SIZE * 0.35;
+const TAIL_TAPER_CELLS: f32 = 3.0;
+const MINIMAP_CELL_SIZE: f32 = 5.0;
+const MINIMAP_MARGIN: f32 = 10.0;
+const INDICATOR_MARGIN: f32 = 40.0;
+const INDICATOR_SIZE: f32 = 40.0;
+const GHOST_ALPHA: f32 = 0.3;
+
+#[derive(Component)]
+struct SnakeHead;
+
+#[derive(Component)]
+struct SnakeSegment;
+
+#[derive(Component)]
+struct Food;
+
+// One of the ghost's spheres, by index from its head.
+#[derive(Component)]
+struct GhostSegment(usize);
+
+#[derive(Resource, Default)]
+struct SnakeBody(Vec<Entity>);
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+enum LocalDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Heading for LocalDirection {
+    fn reversed(self) -> Self {
+        match self {
+            LocalDirection::Up => LocalDirection::Down,
+            LocalDirection::Down => LocalDirection::Up,
+            LocalDirection::Left => LocalDirection::Right,
+            LocalDirection::Right => LocalDirection::Left,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct CurrentDirection(LocalDirection);
+
+#[derive(Resource)]
+struct NextDirection(LocalDirection);
+
+// A turn pressed while another was still waiting for its tick.
+#[derive(Resource, Default)]
+struct QueuedTurn(Option<LocalDirection>);
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+enum CubeFace {
+    Top,
+    Bottom,
+    Front,
+    Back,
+    Left,
+    Right,
+}
+
+impl CubeFace {
+    const ALL: [CubeFace; 6] = [CubeFace::Top, CubeFace::Bottom, CubeFace::Front, CubeFace::Back, CubeFace::Left, CubeFace::Right];
+
+    // Case-insensitive, for the developer console.
+    fn named(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|face| format!("{face:?}").eq_ignore_ascii_case(name))
+    }
+}
+
+impl From<CubeFace> for usize {
+    fn from(face: CubeFace) -> usize {
+        face as usize
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+struct GridPosition {
+    face: CubeFace,
+    x: i32,
+    y: i32,
+}
+
+// Grid cell a segment occupied before the last tick, used to interpolate rendering.
+#[derive(Component, Clone, Copy)]
+struct PreviousGridPosition(GridPosition);
+
+#[derive(Resource)]
+struct Score(u32);
+
+#[derive(Resource)]
+struct Level(u32);
+
+#[derive(Resource)]
+struct GameOver(bool);
+
+// The cube the snake crawls over. It has no walls, so the head always has
+// somewhere to go.
+struct Cube;
+
+impl Arena for Cube {
+    type Cell = GridPosition;
+    type Direction = LocalDirection;
+
+    fn start(&self) -> (Vec<GridPosition>, LocalDirection) {
+        let start = GridPosition {
+            face: CubeFace::Top,
+            x: GRID_SIZE / 2,
+            y: GRID_SIZE / 2,
+        };
+        ((0..3).map(|i| GridPosition { y: start.y - i, ..start }).collect(), LocalDirection::Up)
+    }
+
+    fn step(&self, head: GridPosition, direction: LocalDirection) -> Option<(GridPosition, LocalDirection)> {
+        Some(move_position(head, direction))
+    }
+
+    fn food_cell(&self, rng: &mut GameRng, body: &[GridPosition]) -> GridPosition {
+        loop {
+            let pos = GridPosition {
+                face: CubeFace::ALL[rng.random_range(0..CubeFace::ALL.len())],
+                x: rng.random_range(0..GRID_SIZE),
+                y: rng.random_range(0..GRID_SIZE),
+            };
+            if !body.contains(&pos) {
+                return pos;
+            }
+        }
+    }
+}
+
+// The round so far, offered to `BestReplays` when it ends. `None` for a continued
+// round saved without one.
+#[derive(Resource, Default)]
+struct Recording(Option<Replay<LocalDirection>>);
+
+#[derive(Resource, Default)]
+struct GhostRun(Option<Ghost<Cube>>);
+
+#[derive(Resource, Clone, Copy, PartialEq, Default)]
+enum BodyStyle {
+    #[default]
+    Segments,
+    Continuous,
+}
+
+#[derive(Component)]
+struct BodyMesh;
+
+#[derive(Component)]
+struct HeadEye;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct GameCamera;
+
+/// A round in progress, as kept by [`RoundSave`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct SavedRound {
+    mode: GameMode,
+    speed: Speed,
+    /// Head first.
+    snake: Vec<GridPosition>,
+    food: Option<GridPosition>,
+    direction: LocalDirection,
+    next_direction: LocalDirection,
+    score: u32,
+    level: u32,
+    /// Seconds already spent towards the next move.
+    step_progress: f32,
+    rng: RngState,
+    #[serde(default)]
+    recording: Option<Replay<LocalDirection>>,
+}
+
+// What the current round is played under: the player's settings, or the saved
+// round's when continuing.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+struct RoundRules {
+    mode: GameMode,
+    speed: Speed,
+}
+
+// The saved round being continued, if any, for the systems that set up a round.
+#[derive(Resource, Default)]
+struct ResumedRound(Option<SavedRound>);
+
+// What a `SavedRound` is taken from.
+#[derive(SystemParam)]
+struct RoundState<'w, 's> {
+    rules: Res<'w, RoundRules>,
+    snake_body: Res<'w, SnakeBody>,
+    positions: Query<'w, 's, &'static GridPosition>,
+    food: Query<'w, 's, &'static GridPosition, With<Food>>,
+    current_dir: Res<'w, CurrentDirection>,
+    next_dir: Res<'w, NextDirection>,
+    score: Res<'w, Score>,
+    level: Res<'w, Level>,
+    fixed_time: Res<'w, Time<Fixed>>,
+    rng: Res<'w, GameRng>,
+    recording: Res<'w, Recording>,
+    game_over: Res<'w, GameOver>,
+}
+
+impl RoundSnapshot<SavedRound> for RoundState<'_, '_> {
+    fn saved(&self) -> SavedRound {
+        SavedRound {
+            mode: self.rules.mode,
+            speed: self.rules.speed,
+            snake: self.snake_body.0.iter().filter_map(|&entity| self.positions.get(entity).ok()).copied().collect(),
+            food: self.food.single().ok().copied(),
+            direction: self.current_dir.0,
+            next_direction: self.next_dir.0,
+            score: self.score.0,
+            level: self.level.0,
+            step_progress: self.fixed_time.overstep().as_secs_f32(),
+            rng: self.rng.state(),
+            recording: self.recording.0.clone(),
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.game_over.0
+    }
+}
+
+#[derive(Resource)]
+struct SnakeAssets {
+    head_mesh: Handle<Mesh>,
+    segment_mesh: Handle<Mesh>,
+    food_mesh: Handle<Mesh>,
+    head_material: Handle<StandardMaterial>,
+    segment_material: Handle<StandardMaterial>,
+    food_material: Handle<StandardMaterial>,
+    grid_material: Handle<StandardMaterial>,
+    particle_mesh: Handle<Mesh>,
+    body_mesh: Handle<Mesh>,
+    eye_mesh: Handle<Mesh>,
+    pupil_mesh: Handle<Mesh>,
+    eye_material: Handle<StandardMaterial>,
+    pupil_material: Handle<StandardMaterial>,
+    ghost_head_material: Handle<StandardMaterial>,
+    ghost_segment_material: Handle<StandardMaterial>,
+}
+
+pub struct Snake3dPlugin;
+
+#[derive(Component)]
+struct MinimapCell(GridPosition);
+
+#[derive(Component)]
+struct FoodIndicator;
+
+#[derive(Component)]
+struct MinimapFace;
+
+impl MiniGame for Snake3dPlugin {
+    const ID: &'static str = "snake-3d";
+    const NAME: &'static str = "Snake 3D";
+    const THUMBNAIL: &'static [&'static str] = &[
+        "   ++++ ",
+        "  +  o+ ",
+        " ++++o+ ",
+        " +  +o+ ",
+        " + *+#+ ",
+        " +  ++  ",
+        " ++++   ",
+        "        ",
+    ];
+    const GHOSTS: bool = true;
+    const TELEMETRY: bool = true;
+}
+
+impl Plugin for Snake3dPlugin {
+    fn build(&self, app: &mut App) {
+        // SoundPlugin, ThemePlugin and MenuPlugin are added by the launcher so headless
+        // apps stay silent and don't touch saved data; the game falls back to defaults.
+        add_game_states(app);
+        add_snake_messages::<GridPosition>(app);
+        if !app.is_plugin_added::<StatsPlugin>() {
+            app.add_plugins(StatsPlugin);
+        }
+        track_snake::<GridPosition>(app);
+        track_faces::<CubeFace>(app);
+        if !app.is_plugin_added::<DevConsolePlugin>() {
+            app.add_plugins(DevConsolePlugin);
+        }
+        if !app.is_plugin_added::<TimeTravelPlugin>() {
+            app.add_plugins(TimeTravelPlugin);
+        }
+        app.add_plugins(EffectsPlugin)
+            .add_message::<PlaySfx>()
+            .add_message::<WindowCloseRequested>()
+            .add_message::<WindowFocused>()
+            .init_resource::<MusicTempo>()
+            .init_resource::<Theme>()
+            .init_resource::<Settings>()
+            .init_resource::<HighScores>()
+            .init_resource::<RoundSave>()
+            .init_resource::<BestReplays>()
+            .init_resource::<GameRng>()
+            .init_resource::<ClearColor>()
+            .init_resource::<BodyStyle>()
+            .init_resource::<Recording>()
+            .init_resource::<GhostRun>()
+            .init_resource::<TelemetryLog>()
+            .init_resource::<History<SavedRound>>()
+            .add_systems(Startup, setup_assets)
+            .add_systems(Self::on_enter(), (setup, apply_theme).chain())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    load_saved_round,
+                    reset_round,
+                    spawn_hud,
+                    spawn_minimap,
+                    spawn_snake,
+                    spawn_food,
+                    record_history::<RoundState, SavedRound>,
+                )
+                    .chain()
+                    .run_if(Self::is_active()),
+            )
+            .add_systems(OnExit(GameState::Playing), save_round::<RoundState, SavedRound>.run_if(Self::is_active()))
+            .add_systems(
+                Update,
+                apply_theme.run_if(Self::is_active().and(resource_changed::<Theme>)),
+            )
+            .add_systems(
+                RunFixedMainLoop,
+                input_handler
+                    .in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop)
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    advance_snake,
+                    grow_snake,
+                    score_food,
+                    end_round,
+                    respawn_food,
+                    step_ghost,
+                    update_level,
+                    record_history::<RoundState, SavedRound>,
+                    record_telemetry::<GridPosition>,
+                    count_snake::<GridPosition>,
+                    count_faces::<CubeFace>,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_body_style,
+                    record_result,
+                    play_sounds,
+                    burst_food,
+                    show_death,
+                    interpolate_segments,
+                    draw_ghost,
+                    orient_head,
+                    apply_body_style,
+                    update_body_mesh,
+                    update_camera,
+                    update_score_text,
+                    rotate_food,
+                    update_minimap,
+                    update_food_indicator,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                Update,
+                (
+                    run_console_commands::<ConsoleSnake>,
+                    travel_through_history::<SavedRound>.after(TimeTravelSystems),
+                    describe_round.run_if(overlay_visible),
+                )
+                    .run_if(in_state(GameState::Playing).and(Self::is_active())),
+            )
+            .add_systems(
+                Update,
+                save_round::<RoundState, SavedRound>.run_if(
+                    in_state(GameState::Playing)
+                        .and(Self::is_active())
+                        .and(on_message::<WindowCloseRequested>.or(on_message::<WindowFocused>)),
+                ),
+            );
+    }
+}
+
+fn themed_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        emissive: color.to_linear() * 0.4,
+        ..default()
+    }
+}
+
+fn ghost_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color.with_alpha(GHOST_ALPHA),
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    }
+}
+
+fn setup_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    theme: Res<Theme>,
+) {
+    commands.insert_resource(SnakeAssets {
+        head_mesh: meshes.add(Sphere::new(CELL_SIZE * 0.4)),
+        segment_mesh: meshes.add(Sphere::new(CELL_SIZE * 0.35)),
+        food_mesh: meshes.add(Cuboid::new(CELL_SIZE * 0.4, CELL_SIZE * 0.4, CELL_SIZE * 0.4)),
+        head_material: materials.add(themed_material(theme.head)),
+        segment_material: materials.add(themed_material(theme.body)),
+        food_material: materials.add(themed_material(theme.food)),
+        grid_material: materials.add(StandardMaterial {
+            base_color: theme.grid,
+            ..default()
+        }),
+        particle_mesh: meshes.add(Cuboid::from_length(CELL_SIZE * 0.12)),
+        body_mesh: meshes.add(Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )),
+        eye_mesh: meshes.add(Sphere::new(CELL_SIZE * 0.1)),
+        pupil_mesh: meshes.add(Sphere::new(CELL_SIZE * 0.05)),
+        eye_material: materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            emissive: LinearRgba::new(0.3, 0.3, 0.3, 1.0),
+            ..default()
+        }),
+        pupil_material: materials.add(StandardMaterial {
+            base_color: Color::BLACK,
+            ..default()
+        }),
+        ghost_head_material: materials.add(ghost_material(theme.head)),
+        ghost_segment_material: materials.add(ghost_material(theme.body)),
+    });
+}
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, snake_assets: Res<SnakeAssets>) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(CAMERA_DISTANCE, CAMERA_DISTANCE * 0.8, CAMERA_DISTANCE)
+            .looking_at(Vec3::ZERO, Vec3::Y),
+        GameCamera,
+        CameraShake::new(0.3),
+        DespawnOnExit(Snake3dPlugin::active()),
+    ));
+
+    commands.insert_resource(GlobalAmbientLight {
+        color: Color::WHITE,
+        brightness: 2000.0,
+        affects_lightmapped_meshes: false,
+    });
+
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 5000.0,
+            shadows_enabled: false,
+            ..default()
+        },
+        Transform::from_xyz(5.0, 10.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        DespawnOnExit(Snake3dPlugin::active()),
+    ));
+
+    let half_cube = CUBE_SIZE / 2.0;
+    let line_thickness = 0.015;
+
+    commands.spawn((
+        Mesh3d(meshes.add(build_grid_mesh(half_cube, line_thickness))),
+        MeshMaterial3d(snake_assets.grid_material.clone()),
+        Transform::default(),
+        DespawnOnExit(Snake3dPlugin::active()),
+    ));
+}
+
+fn tick_interval(speed: Speed, level: u32) -> f32 {
+    (MOVE_INTERVAL * speed.interval_scale() * LEVEL_SPEEDUP.powi(level as i32)).max(MIN_MOVE_INTERVAL)
+}
+
+// Takes the saved round if the player chose to continue it. The round carries on
+// under the mode and speed it was started with.
+fn load_saved_round(mut commands: Commands, mut round_save: ResMut<RoundSave>) {
+    let round = round_save.take::<SavedRound>().filter(|round| !round.snake.is_empty());
+    commands.insert_resource(ResumedRound(round));
+}
+
+fn reset_round(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    resumed: Res<ResumedRound>,
+    best_replays: Res<BestReplays>,
+    mut rng: ResMut<GameRng>,
+    mut death: ResMut<DeathSequence>,
+    mut tempo: ResMut<MusicTempo>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut game_started: MessageWriter<GameStarted>,
+) {
+    let resumed = resumed.0.as_ref();
+    let rules = match resumed {
+        Some(round) => RoundRules {
+            mode: round.mode,
+            speed: round.speed,
+        },
+        None => RoundRules {
+            mode: settings.gameplay.mode,
+            speed: settings.gameplay.speed,
+        },
+    };
+    let level = resumed.map_or(0, |round| round.level);
+    let interval = tick_interval(rules.speed, level);
+
+    commands.insert_resource(rules);
+    commands.insert_resource(CurrentDirection(resumed.map_or(LocalDirection::Up, |round| round.direction)));
+    commands.insert_resource(NextDirection(resumed.map_or(LocalDirection::Up, |round| round.next_direction)));
+    commands.insert_resource(QueuedTurn::default());
+    commands.insert_resource(SnakeBody::default());
+    commands.insert_resource(Score(resumed.map_or(0, |round| round.score)));
+    commands.insert_resource(Level(level));
+    commands.insert_resource(GameOver(false));
+    *death = DeathSequence::default();
+    tempo.0 = MOVE_INTERVAL / interval;
+
+    // One fixed step is one move. Time owed from before the round is dropped so
+    // the first move comes a full interval after the start, or as long after
+    // as was left when a continued round was saved.
+    fixed_time.set_timestep(Duration::from_secs_f32(interval));
+    let overstep = fixed_time.overstep();
+    fixed_time.discard_overstep(overstep);
+    if let Some(round) = resumed {
+        fixed_time.accumulate_overstep(Duration::from_secs_f32(round.step_progress));
+    }
+
+    // A fresh round racing the ghost starts from the best run's RNG, so food
+    // turns up in the same places for both. A continued round keeps its ghost
+    // if it was racing the same run, caught up to where the round was left.
+    let best = settings.gameplay.ghost.then(|| best_replays.best::<LocalDirection>(rules.mode, None)).flatten();
+    let recording = match resumed {
+        Some(round) => {
+            *rng = GameRng::from_state(round.rng);
+            round.recording.clone()
+        }
+        None => {
+            if let Some(best) = &best {
+                *rng = GameRng::from_state(best.rng);
+            }
+            Some(Replay::new(rng.state(), rules.mode, None))
+        }
+    };
+    let ghost = best.zip(recording.as_ref()).filter(|(best, recording)| best.rng == recording.rng).map(
+        |(best, recording)| {
+            let mut ghost = Ghost::new(&Cube, best);
+            ghost.catch_up(&Cube, recording.ticks);
+            ghost
+        },
+    );
+    commands.insert_resource(Recording(recording));
+    commands.insert_resource(GhostRun(ghost));
+    commands.insert_resource(History::<SavedRound>::default());
+    game_started.write(GameStarted {
+        resumed: resumed.is_some(),
+        rng: rng.state(),
+        mode: rules.mode,
+        speed: rules.speed,
+        board_size: None,
+    });
+}
+
+fn spawn_hud(mut commands: Commands, theme: Res<Theme>) {
+    commands.spawn((
+        Text::new("Score: 0"),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(theme.text),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        ScoreText,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+fn build_grid_mesh(half_cube: f32, thickness: f32) -> Mesh {
+    let mut lines = Vec::new();
+    for face in CubeFace::ALL {
+        face_grid_lines(&mut lines, face, half_cube, thickness);
+    }
+
+    let mut line_meshes = lines
+        .into_iter()
+        .map(|(cuboid, translation)| Mesh::from(cuboid).translated_by(translation));
+    let mut grid_mesh = line_meshes.next().expect("grid has at least one line");
+    for line_mesh in line_meshes {
+        grid_mesh
+            .merge(&line_mesh)
+            .expect("grid line meshes share the same vertex layout");
+    }
+    grid_mesh
+}
+
+fn face_grid_lines(lines: &mut Vec<(Cuboid, Vec3)>, face: CubeFace, half_cube: f32, thickness: f32) {
+    let grid_len = CUBE_SIZE;
+
+    for i in 0..=GRID_SIZE {
+        let offset = (i as f32 / GRID_SIZE as f32 - 0.5) * CUBE_SIZE;
+
+        match face {
+            CubeFace::Top => {
+                lines.push((Cuboid::new(grid_len, thickness, thickness), Vec3::new(0.0, half_cube, offset)));
+                lines.push((Cuboid::new(thickness, thickness, grid_len), Vec3::new(offset, half_cube, 0.0)));
+            }
+            CubeFace::Bottom => {
+                lines.push((Cuboid::new(grid_len, thickness, thickness), Vec3::new(0.0, -half_cube, offset)));
+                lines.push((Cuboid::new(thickness, thickness, grid_len), Vec3::new(offset, -half_cube, 0.0)));
+            }
+            CubeFace::Front => {
+                lines.push((Cuboid::new(grid_len, thickness, thickness), Vec3::new(0.0, offset, half_cube)));
+                lines.push((Cuboid::new(thickness, grid_len, thickness), Vec3::new(offset, 0.0, half_cube)));
+            }
+            CubeFace::Back => {
+                lines.push((Cuboid::new(grid_len, thickness, thickness), Vec3::new(0.0, offset, -half_cube)));
+                lines.push((Cuboid::new(thickness, grid_len, thickness), Vec3::new(offset, 0.0, -half_cube)));
+            }
+            CubeFace::Left => {
+                lines.push((Cuboid::new(thickness, grid_len, thickness), Vec3::new(-half_cube, 0.0, offset)));
+                lines.push((Cuboid::new(thickness, thickness, grid_len), Vec3::new(-half_cube, offset, 0.0)));
+            }
+            CubeFace::Right => {
+                lines.push((Cuboid::new(thickness, grid_len, thickness), Vec3::new(half_cube, 0.0, offset)));
+                lines.push((Cuboid::new(thickness, thickness, grid_len), Vec3::new(half_cube, offset, 0.0)));
+            }
+        }
+    }
+}
+
+fn grid_to_world(pos: &GridPosition) -> Vec3 {
+    let half_cube = CUBE_SIZE / 2.0;
+    let x_offset = (pos.x as f32 + 0.5) / GRID_SIZE as f32 * CUBE_SIZE - half_cube;
+    let y_offset = (pos.y as f32 + 0.5) / GRID_SIZE as f32 * CUBE_SIZE - half_cube;
+
+    match pos.face {
+        CubeFace::Top => Vec3::new(x_offset, half_cube + SURFACE_OFFSET, -y_offset),
+        CubeFace::Bottom => Vec3::new(x_offset, -half_cube - SURFACE_OFFSET, y_offset),
+        CubeFace::Front => Vec3::new(x_offset, y_offset, half_cube + SURFACE_OFFSET),
+        CubeFace::Back => Vec3::new(-x_offset, y_offset, -half_cube - SURFACE_OFFSET),
+        CubeFace::Left => Vec3::new(-half_cube - SURFACE_OFFSET, y_offset, -x_offset),
+        CubeFace::Right => Vec3::new(half_cube + SURFACE_OFFSET, y_offset, x_offset),
+    }
+}
+
+fn segment_bundle(pos: GridPosition, snake_assets: &SnakeAssets) -> impl Bundle {
+    (
+        Mesh3d(snake_assets.segment_mesh.clone()),
+        MeshMaterial3d(snake_assets.segment_material.clone()),
+        Transform::from_translation(grid_to_world(&pos)),
+        SnakeSegment,
+        pos,
+        PreviousGridPosition(pos),
+        DespawnOnExit(GameState::Playing),
+    )
+}
+
+fn head_bundle(pos: GridPosition, snake_assets: &SnakeAssets) -> impl Bundle {
+    // Head-local space: forward is -Z and up is +Y (see `orient_head`).
+    let eye = |side: f32| {
+        (
+            Mesh3d(snake_assets.eye_mesh.clone()),
+            MeshMaterial3d(snake_assets.eye_material.clone()),
+            Transform::from_xyz(side * 0.06, 0.08, -0.14),
+            Visibility::Hidden,
+            HeadEye,
+        )
+    };
+    let pupil = |side: f32| {
+        (
+            Mesh3d(snake_assets.pupil_mesh.clone()),
+            MeshMaterial3d(snake_assets.pupil_material.clone()),
+            Transform::from_xyz(side * 0.07, 0.095, -0.175),
+            Visibility::Hidden,
+            HeadEye,
+        )
+    };
+    (
+        Mesh3d(snake_assets.head_mesh.clone()),
+        MeshMaterial3d(snake_assets.head_material.clone()),
+        Transform::from_translation(grid_to_world(&pos)),
+        SnakeHead,
+        pos,
+        PreviousGridPosition(pos),
+        DespawnOnExit(GameState::Playing),
+        children![eye(-1.0), pupil(-1.0), eye(1.0), pupil(1.0)],
+    )
+}
+
+fn food_bundle(pos: GridPosition, snake_assets: &SnakeAssets) -> impl Bundle {
+    (
+        Mesh3d(snake_assets.food_mesh.clone()),
+        MeshMaterial3d(snake_assets.food_material.clone()),
+        Transform::from_translation(grid_to_world(&pos)),
+        Food,
+        pos,
+        DespawnOnExit(GameState::Playing),
+    )
+}
+
+fn spawn_snake(
+    mut commands: Commands,
+    snake_assets: Res<SnakeAssets>,
+    resumed: Res<ResumedRound>,
+    mut snake_body: ResMut<SnakeBody>,
+) {
+    let cells = match &resumed.0 {
+        Some(round) => round.snake.clone(),
+        None => Cube.start().0,
+    };
+    let head = commands.spawn(head_bundle(cells[0], &snake_assets)).id();
+    snake_body.0.push(head);
+
+    commands.spawn((
+        Mesh3d(snake_assets.body_mesh.clone()),
+        MeshMaterial3d(snake_assets.segment_material.clone()),
+        Transform::default(),
+        Visibility::Hidden,
+        BodyMesh,
+        DespawnOnExit(GameState::Playing),
+    ));
+
+    for &pos in &cells[1..] {
+        let segment = commands.spawn(segment_bundle(pos, &snake_assets)).id();
+        snake_body.0.push(segment);
+    }
+}
+
+fn spawn_food(
+    mut commands: Commands,
+    snake_assets: Res<SnakeAssets>,
+    snake_body: Res<SnakeBody>,
+    resumed: Res<ResumedRound>,
+    positions: Query<&GridPosition>,
+    food_query: Query<Entity, With<Food>>,
+    mut rng: ResMut<GameRng>,
+) {
+    for entity in food_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if let Some(round) = &resumed.0 {
+        if let Some(food_pos) = round.food {
+            commands.spawn(food_bundle(food_pos, &snake_assets));
+        }
+        return;
+    }
+
+    let body: Vec<GridPosition> = snake_body.0.iter().filter_map(|&entity| positions.get(entity).ok()).copied().collect();
+    commands.spawn(food_bundle(Cube.food_cell(&mut rng, &body), &snake_assets));
+}
+
+fn rotate_food(time: Res<Time>, mut query: Query<&mut Transform, With<Food>>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate_y(time.delta_secs() * 2.0);
+        transform.rotate_x(time.delta_secs() * 1.5);
+    }
+}
+
+fn input_handler(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    current_dir: Res<CurrentDirection>,
+    mut next_dir: ResMut<NextDirection>,
+    mut queued: ResMut<QueuedTurn>,
+) {
+    let [up, down, left, right] = settings.gameplay.controls.bindings();
+    let pressed = if keyboard.any_just_pressed(up.iter().copied()) {
+        Some(LocalDirection::Up)
+    } else if keyboard.any_just_pressed(down.iter().copied()) {
+        Some(LocalDirection::Down)
+    } else if keyboard.any_just_pressed(left.iter().copied()) {
+        Some(LocalDirection::Left)
+    } else if keyboard.any_just_pressed(right.iter().copied()) {
+        Some(LocalDirection::Right)
+    } else {
+        None
+    };
+
+    if let Some(dir) = pressed {
+        press_turn(current_dir.0, &mut next_dir.0, &mut queued.0, dir);
+    }
+}
+
+// Returns (new_position, new_direction)
+fn move_position(pos: GridPosition, dir: LocalDirection) -> (GridPosition, LocalDirection) {
+    let mut new_pos = pos;
+    let mut new_dir = dir;
+
+    match dir {
+        LocalDirection::Up => new_pos.y += 1,
+        LocalDirection::Down => new_pos.y -= 1,
+        LocalDirection::Left => new_pos.x -= 1,
+        LocalDirection::Right => new_pos.x += 1,
+    }
+
+    // Handle face transitions
+    if new_pos.x < 0 {
+        let (face, x, y, d) = transition_left(pos.face, pos.y);
+        new_pos.face = face;
+        new_pos.x = x;
+        new_pos.y = y;
+        new_dir = d;
+    } else if new_pos.x >= GRID_SIZE {
+        let (face, x, y, d) = transition_right(pos.face, pos.y);
+        new_pos.face = face;
+        new_pos.x = x;
+        new_pos.y = y;
+        new_dir = d;
+    } else if new_pos.y < 0 {
+        let (face, x, y, d) = transition_down(pos.face, pos.x);
+        new_pos.face = face;
+        new_pos.x = x;
+        new_pos.y = y;
+        new_dir = d;
+    } else if new_pos.y >= GRID_SIZE {
+        let (face, x, y, d) = transition_up(pos.face, pos.x);
+        new_pos.face = face;
+        new_pos.x = x;
+        new_pos.y = y;
+        new_dir = d;
+    }
+
+    (new_pos, new_dir)
+}
+
+fn transition_up(face: CubeFace, x: i32) -> (CubeFace, i32, i32, LocalDirection) {
+    match face {
+        CubeFace::Top => (CubeFace::Back, GRID_SIZE - 1 - x, GRID_SIZE - 1, LocalDirection::Down),
+        CubeFace::Bottom => (CubeFace::Front, x, 0, LocalDirection::Up),
+        CubeFace::Front => (CubeFace::Top, x, 0, LocalDirection::Up),
+        CubeFace::Back => (CubeFace::Top, GRID_SIZE - 1 - x, GRID_SIZE - 1, LocalDirection::Down),
+        CubeFace::Left => (CubeFace::Top, 0, x, LocalDirection::Right),
+        CubeFace::Right => (CubeFace::Top, GRID_SIZE - 1, GRID_SIZE - 1 - x, LocalDirection::Left),
+    }
+}
+
+fn transition_down(face: CubeFace, x: i32) -> (CubeFace, i32, i32, LocalDirection) {
+    match face {
+        CubeFace::Top => (CubeFace::Front, x, GRID_SIZE - 1, LocalDirection::Down),
+        CubeFace::Bottom => (CubeFace::Back, GRID_SIZE - 1 - x, 0, LocalDirection::Up),
+        CubeFace::Front => (CubeFace::Bottom, x, GRID_SIZE - 1, LocalDirection::Down),
+        CubeFace::Back => (CubeFace::Bottom, GRID_SIZE - 1 - x, 0, LocalDirection::Up),
+        CubeFace::Left => (CubeFace::Bottom, 0, GRID_SIZE - 1 - x, LocalDirection::Right),
+        CubeFace::Right => (CubeFace::Bottom, GRID_SIZE - 1, x, LocalDirection::Left),
+    }
+}
+
+fn transition_left(face: CubeFace, y: i32) -> (CubeFace, i32, i32, LocalDirection) {
+    match face {
+        CubeFace::Top => (CubeFace::Left, y, GRID_SIZE - 1, LocalDirection::Down),
+        CubeFace::Bottom => (CubeFace::Left, GRID_SIZE - 1 - y, 0, LocalDirection::Up),
+        CubeFace::Front => (CubeFace::Left, 0, y, LocalDirection::Right),
+        CubeFace::Back => (CubeFace::Right, 0, y, LocalDirection::Right),
+        CubeFace::Left => (CubeFace::Front, 0, y, LocalDirection::Right),
+        CubeFace::Right => (CubeFace::Back, 0, y, LocalDirection::Right),
+    }
+}
+
+fn transition_right(face: CubeFace, y: i32) -> (CubeFace, i32, i32, LocalDirection) {
+    match face {
+        CubeFace::Top => (CubeFace::Right, GRID_SIZE - 1 - y, GRID_SIZE - 1, LocalDirection::Down),
+        CubeFace::Bottom => (CubeFace::Right, y, 0, LocalDirection::Up),
+        CubeFace::Front => (CubeFace::Right, GRID_SIZE - 1, y, LocalDirection::Left),
+        CubeFace::Back => (CubeFace::Left, GRID_SIZE - 1, y, LocalDirection::Left),
+        CubeFace::Left => (CubeFace::Back, GRID_SIZE - 1, y, LocalDirection::Left),
+        CubeFace::Right => (CubeFace::Front, GRID_SIZE - 1, y, LocalDirection::Left),
+    }
+}
+
+// Resolves one fixed step: the cell ahead of the head decides whether the snake
+// dies, eats or just moves, and the whole body moves at once. Runs once per
+// step, so a long frame catches up with every move it owes. Everything that
+// follows from the move listens for the messages it writes.
+fn advance_snake(
+    mut current_dir: ResMut<CurrentDirection>,
+    mut next_dir: ResMut<NextDirection>,
+    mut queued: ResMut<QueuedTurn>,
+    snake_body: Res<SnakeBody>,
+    mut positions: Query<(&mut GridPosition, &mut PreviousGridPosition), Without<Food>>,
+    food: Query<&GridPosition, With<Food>>,
+    rules: Res<RoundRules>,
+    game_over: Res<GameOver>,
+    cheats: Res<Cheats>,
+    mut recording: ResMut<Recording>,
+    mut snake_moved: MessageWriter<SnakeMoved>,
+    mut food_eaten: MessageWriter<FoodEaten<GridPosition>>,
+    mut snake_died: MessageWriter<SnakeDied<GridPosition>>,
+    mut face_changed: MessageWriter<FaceChanged<CubeFace>>,
+) {
+    if game_over.0 {
+        return;
+    }
+
+    let turned = current_dir.0 != next_dir.0;
+    current_dir.0 = next_dir.0;
+
+    let body: Vec<GridPosition> = snake_body
+        .0
+        .iter()
+        .filter_map(|&entity| positions.get(entity).ok())
+        .map(|(pos, _)| *pos)
+        .collect();
+    if body.is_empty() {
+        return;
+    }
+    // A cheated round is off the record, ghost included.
+    if cheats.cheated {
+        recording.0 = None;
+    }
+    if let Some(recording) = &mut recording.0 {
+        recording.record(current_dir.0);
+    }
+    let food = food.single().ok().copied();
+
+    // The cube has no walls, so without self-collision nothing can kill the snake.
+    let (outcome, moved, new_dir) = play_tick(
+        &Cube,
+        &body,
+        food.as_ref(),
+        current_dir.0,
+        rules.mode.self_collision() && !cheats.god_mode,
+    );
+    if let TickOutcome::Died(cause) = outcome {
+        snake_died.write(SnakeDied { cause, pos: body[0] });
+        return;
+    }
+
+    current_dir.0 = new_dir;
+    next_dir.0 = take_queued_turn(new_dir, &mut queued.0);
+    for (&entity, &pos) in snake_body.0.iter().zip(&moved) {
+        if let Ok((mut current, mut prev)) = positions.get_mut(entity) {
+            prev.0 = *current;
+            *current = pos;
+        }
+    }
+    snake_moved.write(SnakeMoved {
+        turned,
+        length: moved.len() as u32,
+    });
+    if moved[0].face != body[0].face {
+        face_changed.write(FaceChanged {
+            from: body[0].face,
+            to: moved[0].face,
+        });
+    }
+    if let Some(pos) = food.filter(|_| outcome == TickOutcome::Ate) {
+        food_eaten.write(FoodEaten {
+            pos,
+            kind: FoodKind::Plain,
+        });
+    }
+}
+
+// The new segment waits in the cell the tail just left.
+fn grow_snake(
+    mut commands: Commands,
+    mut food_eaten: MessageReader<FoodEaten<GridPosition>>,
+    mut snake_body: ResMut<SnakeBody>,
+    previous: Query<&PreviousGridPosition>,
+    snake_assets: Res<SnakeAssets>,
+) {
+    for _ in food_eaten.read() {
+        let Some(tail) = snake_body.0.last().and_then(|&entity| previous.get(entity).ok()) else {
+            continue;
+        };
+        let segment = commands.spawn(segment_bundle(tail.0, &snake_assets)).id();
+        snake_body.0.push(segment);
+    }
+}
+
+fn score_food(mut food_eaten: MessageReader<FoodEaten<GridPosition>>, mut score: ResMut<Score>) {
+    for eaten in food_eaten.read() {
+        score.0 += eaten.kind.points();
+    }
+}
+
+// Runs after the snake has grown, so the next food keeps clear of the new segment.
+fn respawn_food(
+    mut commands: Commands,
+    mut food_eaten: MessageReader<FoodEaten<GridPosition>>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<&GridPosition>,
+    food: Query<(Entity, &GridPosition), With<Food>>,
+    mut rng: ResMut<GameRng>,
+    snake_assets: Res<SnakeAssets>,
+) {
+    for eaten in food_eaten.read() {
+        for (entity, _) in food.iter().filter(|(_, &pos)| pos == eaten.pos) {
+            commands.entity(entity).despawn();
+        }
+        let body: Vec<GridPosition> =
+            snake_body.0.iter().filter_map(|&entity| positions.get(entity).ok()).copied().collect();
+        commands.spawn(food_bundle(Cube.food_cell(&mut rng, &body), &snake_assets));
+    }
+}
+
+fn end_round(
+    mut snake_died: MessageReader<SnakeDied<GridPosition>>,
+    score: Res<Score>,
+    mut game_over: ResMut<GameOver>,
+    mut game_ended: MessageWriter<GameEnded>,
+) {
+    if snake_died.read().count() > 0 {
+        game_over.0 = true;
+        game_ended.write(GameEnded { score: score.0 });
+    }
+}
+
+fn play_sounds(
+    mut snake_moved: MessageReader<SnakeMoved>,
+    mut food_eaten: MessageReader<FoodEaten<GridPosition>>,
+    mut snake_died: MessageReader<SnakeDied<GridPosition>>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    for _ in snake_moved.read().filter(|moved| moved.turned) {
+        sfx.write(PlaySfx(Sfx::Turn));
+    }
+    for _ in food_eaten.read() {
+        sfx.write(PlaySfx(Sfx::Eat));
+    }
+    for _ in snake_died.read() {
+        sfx.write(PlaySfx(Sfx::Death));
+    }
+}
+
+fn burst_food(
+    mut commands: Commands,
+    mut food_eaten: MessageReader<FoodEaten<GridPosition>>,
+    effects: Res<EffectsSettings>,
+    snake_assets: Res<SnakeAssets>,
+) {
+    // How many particles there are depends on the effects settings, so their
+    // directions come from the thread RNG and leave the round's RNG alone.
+    let mut particle_rng = rand::rng();
+    for eaten in food_eaten.read() {
+        let normal = face_normal(eaten.pos.face);
+        spawn_burst(
+            &mut commands,
+            &effects,
+            Burst {
+                origin: grid_to_world(&eaten.pos),
+                count: 16,
+                speed: 3.0,
+                lifetime: 0.6,
+                gravity: -normal * 6.0,
+            },
+            || {
+                let scatter = Vec3::new(
+                    particle_rng.random_range(-1.0..1.0),
+                    particle_rng.random_range(-1.0..1.0),
+                    particle_rng.random_range(-1.0..1.0),
+                );
+                (normal + scatter).normalize_or(normal)
+            },
+            || {
+                (
+                    Mesh3d(snake_assets.particle_mesh.clone()),
+                    MeshMaterial3d(snake_assets.food_material.clone()),
+                )
+            },
+        );
+    }
+}
+
+// The ghost plays the tick the live snake just played, so both stop together.
+fn step_ghost(recording: Res<Recording>, mut ghost: ResMut<GhostRun>) {
+    if let (Some(ghost), Some(recording)) = (&mut ghost.0, &recording.0) {
+        ghost.catch_up(&Cube, recording.ticks);
+    }
+}
+
+// Point on the path from `from` to `to` that hugs the cube surface. When the two
+// cells are on adjacent faces the path runs to the shared edge, rolls around it
+// at the hover height, then continues across the second face.
+fn surface_lerp(from: &GridPosition, to: &GridPosition, t: f32) -> Vec3 {
+    let start = grid_to_world(from);
+    let end = grid_to_world(to);
+    let from_normal = face_normal(from.face);
+    let to_normal = face_normal(to.face);
+    let edge_axis = from_normal.cross(to_normal);
+
+    if edge_axis.length_squared() < 0.5 {
+        return start.lerp(end, t);
+    }
+
+    let half_cube = CUBE_SIZE / 2.0;
+    let start_to_edge = half_cube - start.dot(to_normal);
+    let edge_to_end = half_cube - end.dot(from_normal);
+    let corner_arc = SURFACE_OFFSET * std::f32::consts::FRAC_PI_2;
+    let distance = t * (start_to_edge + corner_arc + edge_to_end);
+
+    let along_edge = start.dot(edge_axis) + (end.dot(edge_axis) - start.dot(edge_axis)) * t;
+    let edge = (from_normal + to_normal) * half_cube + edge_axis * along_edge;
+
+    if distance < start_to_edge {
+        edge + from_normal * SURFACE_OFFSET - to_normal * (start_to_edge - distance)
+    } else if distance < start_to_edge + corner_arc {
+        let angle = (distance - start_to_edge) / SURFACE_OFFSET;
+        edge + (from_normal * angle.cos() + to_normal * angle.sin()) * SURFACE_OFFSET
+    } else {
+        edge + to_normal * SURFACE_OFFSET - from_normal * (distance - start_to_edge - corner_arc)
+    }
+}
+
+// Rendering only: slides each segment from its previous cell to its current one
+// as the next fixed step approaches. The `GridPosition` stays authoritative.
+fn interpolate_segments(
+    fixed_time: Res<Time<Fixed>>,
+    game_over: Res<GameOver>,
+    mut query: Query<(&GridPosition, &PreviousGridPosition, &mut Transform)>,
+) {
+    let t = if game_over.0 { 1.0 } else { fixed_time.overstep_fraction() };
+
+    for (pos, prev, mut transform) in query.iter_mut() {
+        transform.translation = surface_lerp(&prev.0, pos, t);
+    }
+}
+
+// Translucent spheres for the ghost, sliding over the surface the same way as
+// the live snake. Spheres come and go as the ghost's length changes.
+fn draw_ghost(
+    mut commands: Commands,
+    ghost: Res<GhostRun>,
+    fixed_time: Res<Time<Fixed>>,
+    game_over: Res<GameOver>,
+    snake_assets: Res<SnakeAssets>,
+    mut segments: Query<(Entity, &GhostSegment, &mut Transform)>,
+) {
+    let body = ghost.0.as_ref().map_or(&[][..], |ghost| &ghost.body);
+    let t = if game_over.0 { 1.0 } else { fixed_time.overstep_fraction() };
+    let place = |index: usize| {
+        let pos = &body[index];
+        let prev = ghost.0.as_ref().and_then(|ghost| ghost.previous.get(index)).unwrap_or(pos);
+        surface_lerp(prev, pos, t)
+    };
+
+    let mut drawn = 0;
+    for (entity, segment, mut transform) in segments.iter_mut() {
+        if segment.0 >= body.len() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation = place(segment.0);
+        drawn += 1;
+    }
+    for index in drawn..body.len() {
+        let (mesh, material) = if index == 0 {
+            (&snake_assets.head_mesh, &snake_assets.ghost_head_material)
+        } else {
+            (&snake_assets.segment_mesh, &snake_assets.ghost_segment_material)
+        };
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(place(index)),
+            GhostSegment(index),
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+}
+
+// Fractions of the path from `from` to `to` that lie on the first and on the
+// second face. Paths within one face are split evenly.
+fn surface_split(from: &GridPosition, to: &GridPosition) -> (f32, f32) {
+    let from_normal = face_normal(from.face);
+    let to_normal = face_normal(to.face);
+
+    if from_normal.cross(to_normal).length_squared() < 0.5 {
+        return (0.5, 0.5);
+    }
+
+    let half_cube = CUBE_SIZE / 2.0;
+    let start_to_edge = half_cube - grid_to_world(from).dot(to_normal);
+    let edge_to_end = half_cube - grid_to_world(to).dot(from_normal);
+    let total = start_to_edge + SURFACE_OFFSET * std::f32::consts::FRAC_PI_2 + edge_to_end;
+    (start_to_edge / total, edge_to_end / total)
+}
+
+// World-space directions of a face's local +x and +y grid axes.
+fn face_axes(face: CubeFace) -> (Vec3, Vec3) {
+    match face {
+        CubeFace::Top => (Vec3::X, Vec3::NEG_Z),
+        CubeFace::Bottom => (Vec3::X, Vec3::Z),
+        CubeFace::Front => (Vec3::X, Vec3::Y),
+        CubeFace::Back => (Vec3::NEG_X, Vec3::Y),
+        CubeFace::Left => (Vec3::NEG_Z, Vec3::Y),
+        CubeFace::Right => (Vec3::Z, Vec3::Y),
+    }
+}
+
+fn direction_vector(face: CubeFace, dir: LocalDirection) -> Vec3 {
+    let (x_axis, y_axis) = face_axes(face);
+    match dir {
+        LocalDirection::Up => y_axis,
+        LocalDirection::Down => -y_axis,
+        LocalDirection::Left => -x_axis,
+        LocalDirection::Right => x_axis,
+    }
+}
+
+fn toggle_body_style(keyboard: Res<ButtonInput<KeyCode>>, mut style: ResMut<BodyStyle>) {
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        *style = match *style {
+            BodyStyle::Segments => BodyStyle::Continuous,
+            BodyStyle::Continuous => BodyStyle::Segments,
+        };
+    }
+}
+
+fn apply_body_style(
+    style: Res<BodyStyle>,
+    mut segments: Query<&mut Visibility, (With<SnakeSegment>, Without<BodyMesh>, Without<HeadEye>)>,
+    mut body_mesh: Query<&mut Visibility, (With<BodyMesh>, Without<HeadEye>)>,
+    mut eyes: Query<&mut Visibility, With<HeadEye>>,
+) {
+    let (segment_visibility, mesh_visibility) = match *style {
+        BodyStyle::Segments => (Visibility::Inherited, Visibility::Hidden),
+        BodyStyle::Continuous => (Visibility::Hidden, Visibility::Inherited),
+    };
+
+    for mut visibility in segments.iter_mut() {
+        visibility.set_if_neq(segment_visibility);
+    }
+    for mut visibility in body_mesh.iter_mut().chain(eyes.iter_mut()) {
+        visibility.set_if_neq(mesh_visibility);
+    }
+}
+
+fn orient_head(
+    current_dir: Res<CurrentDirection>,
+    mut head_query: Query<(&GridPosition, &mut Transform), With<SnakeHead>>,
+) {
+    for (pos, mut transform) in head_query.iter_mut() {
+        transform.look_to(direction_vector(pos.face, current_dir.0), face_normal(pos.face));
+    }
+}
+
+// Samples the centre line of the body between `head_u` and `tail_u`, measured in
+// cells along `cells` (head cell first). The line follows the cube surface
+// across edges, and turns within a face are rounded with quadratic fillets so
+// the tube keeps its radius through bends. Returns (distance from head in
+// cells, point) pairs.
+fn body_path(cells: &[GridPosition], head_u: f32, tail_u: f32) -> Vec<(f32, Vec3)> {
+    if cells.len() < 2 {
+        return cells.iter().map(|c| (0.0, grid_to_world(c))).collect();
+    }
+
+    let splits: Vec<(f32, f32)> = cells.windows(2).map(|w| surface_split(&w[0], &w[1])).collect();
+    let fillet = |j: usize, t: f32| {
+        let start = surface_lerp(&cells[j - 1], &cells[j], 1.0 - splits[j - 1].1);
+        let end = surface_lerp(&cells[j], &cells[j + 1], splits[j].0);
+        start * (1.0 - t) * (1.0 - t) + grid_to_world(&cells[j]) * 2.0 * (1.0 - t) * t + end * t * t
+    };
+    let point_at = |u: f32| {
+        let k = (u.max(0.0) as usize).min(cells.len() - 2);
+        let f = u - k as f32;
+        let (start_share, end_share) = splits[k];
+        if f < start_share && k >= 1 {
+            fillet(k, 0.5 + 0.5 * f / start_share)
+        } else if f > 1.0 - end_share && k + 2 < cells.len() {
+            fillet(k + 1, 0.5 * (f - (1.0 - end_share)) / end_share)
+        } else {
+            surface_lerp(&cells[k], &cells[k + 1], f)
+        }
+    };
+
+    let length = tail_u - head_u;
+    let samples = ((length * BODY_SAMPLES_PER_CELL as f32).ceil() as usize).max(1);
+    (0..=samples)
+        .map(|i| {
+            let d = length * i as f32 / samples as f32;
+            (d, point_at(head_u + d))
+        })
+        .collect()
+}
+
+fn build_body_mesh(path: &[(f32, Vec3)], length: f32) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    let half_cube = Vec3::splat(CUBE_SIZE / 2.0);
+    let mut tangent = Vec3::Z;
+    let mut surface_normal = Vec3::Y;
+    for (i, &(distance, point)) in path.iter().enumerate() {
+        let prev = path[i.saturating_sub(1)].1;
+        let next = path[(i + 1).min(path.len() - 1)].1;
+        if let Some(t) = (next - prev).try_normalize() {
+            tangent = t;
+        }
+        if let Some(n) = (point - point.clamp(-half_cube, half_cube)).try_normalize() {
+            surface_normal = n;
+        }
+
+        let side = tangent.cross(surface_normal).normalize_or_zero();
+        let up = side.cross(tangent);
+        let radius = BODY_RADIUS * ((length - distance) / TAIL_TAPER_CELLS).clamp(0.2, 1.0);
+        for j in 0..BODY_RING_SIDES {
+            let angle = j as f32 / BODY_RING_SIDES as f32 * std::f32::consts::TAU;
+            let normal = up * angle.cos() + side * angle.sin();
+            positions.push((point + normal * radius).to_array());
+            normals.push(normal.to_array());
+        }
+
+        if i + 1 < path.len() {
+            let ring = i as u32 * BODY_RING_SIDES;
+            for j in 0..BODY_RING_SIDES {
+                let a = ring + j;
+                let b = ring + (j + 1) % BODY_RING_SIDES;
+                let (c, d) = (a + BODY_RING_SIDES, b + BODY_RING_SIDES);
+                indices.extend([a, b, c, b, d, c]);
+            }
+        }
+    }
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+fn update_body_mesh(
+    style: Res<BodyStyle>,
+    fixed_time: Res<Time<Fixed>>,
+    game_over: Res<GameOver>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<(&GridPosition, &PreviousGridPosition)>,
+    snake_assets: Res<SnakeAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if *style != BodyStyle::Continuous {
+        return;
+    }
+
+    let segments: Vec<(GridPosition, GridPosition)> = snake_body
+        .0
+        .iter()
+        .filter_map(|&entity| positions.get(entity).ok())
+        .map(|(pos, prev)| (*pos, prev.0))
+        .collect();
+    let (Some(&(head, head_prev)), Some(&(tail, tail_prev))) = (segments.first(), segments.last()) else {
+        return;
+    };
+
+    // The head slides in from its previous cell and the tail slides out of the
+    // cell it is leaving, so both ends sit part-way along the chain of cells.
+    let t = if game_over.0 { 1.0 } else { fixed_time.overstep_fraction() };
+    let mut cells: Vec<GridPosition> = segments.iter().map(|(pos, _)| *pos).collect();
+    let head_u = if head == head_prev { 0.0 } else { 1.0 - t };
+    let mut tail_u = (cells.len() - 1) as f32;
+    if tail != tail_prev {
+        cells.push(tail_prev);
+        tail_u += 1.0 - t;
+    }
+
+    let path = body_path(&cells, head_u, tail_u);
+    if let Some(mesh) = meshes.get_mut(&snake_assets.body_mesh) {
+        *mesh = build_body_mesh(&path, tail_u - head_u);
+    }
+}
+
+// Cube net layout (viewed from outside the cube):
+//
+//         [Top]
+//  [Left][Front][Right][Back]
+//         [Bottom]
+//
+// Returns the (column, row) of the face block in the net, row 0 at the top.
+fn face_net_block(face: CubeFace) -> (i32, i32) {
+    match face {
+        CubeFace::Top => (1, 0),
+        CubeFace::Left => (0, 1),
+        CubeFace::Front => (1, 1),
+        CubeFace::Right => (2, 1),
+        CubeFace::Back => (3, 1),
+        CubeFace::Bottom => (1, 2),
+    }
+}
+
+// Returns the (column, row) of a grid cell in the unfolded net, row 0 at the top.
+// Seen from outside, Left's block sits left of Front, so its columns run from
+// back to front; Right's sits to the right, so its columns run from front to
+// back. Local x runs the other way on both (front to back on Left, back to front
+// on Right), so both are mirrored to keep cells adjacent across folded edges.
+fn grid_to_net(pos: &GridPosition) -> (i32, i32) {
+    let (block_x, block_y) = face_net_block(pos.face);
+    let x = match pos.face {
+        CubeFace::Left | CubeFace::Right => GRID_SIZE - 1 - pos.x,
+        _ => pos.x,
+    };
+    (
+        block_x * GRID_SIZE + x,
+        block_y * GRID_SIZE + (GRID_SIZE - 1 - pos.y),
+    )
+}
+
+fn face_normal(face: CubeFace) -> Vec3 {
+    match face {
+        CubeFace::Top => Vec3::Y,
+        CubeFace::Bottom => Vec3::NEG_Y,
+        CubeFace::Front => Vec3::Z,
+        CubeFace::Back => Vec3::NEG_Z,
+        CubeFace::Left => Vec3::NEG_X,
+        CubeFace::Right => Vec3::X,
+    }
+}
+
+fn spawn_minimap(mut commands: Commands, theme: Res<Theme>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(MINIMAP_MARGIN),
+                right: Val::Px(MINIMAP_MARGIN),
+                width: Val::Px(4.0 * GRID_SIZE as f32 * MINIMAP_CELL_SIZE),
+                height: Val::Px(3.0 * GRID_SIZE as f32 * MINIMAP_CELL_SIZE),
+                ..default()
+            },
+            DespawnOnExit(GameState::Playing),
+        ))
+        .with_children(|parent| {
+            for face in CubeFace::ALL {
+                let (block_x, block_y) = face_net_block(face);
+                parent.spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(block_x as f32 * GRID_SIZE as f32 * MINIMAP_CELL_SIZE),
+                        top: Val::Px(block_y as f32 * GRID_SIZE as f32 * MINIMAP_CELL_SIZE),
+                        width: Val::Px(GRID_SIZE as f32 * MINIMAP_CELL_SIZE),
+                        height: Val::Px(GRID_SIZE as f32 * MINIMAP_CELL_SIZE),
+                        ..default()
+                    },
+                    BackgroundColor(theme.grid.with_alpha(0.5)),
+                    MinimapFace,
+                ));
+
+                for x in 0..GRID_SIZE {
+                    for y in 0..GRID_SIZE {
+                        let pos = GridPosition { face, x, y };
+                        let (net_x, net_y) = grid_to_net(&pos);
+                        parent.spawn((
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(net_x as f32 * MINIMAP_CELL_SIZE + 0.5),
+                                top: Val::Px(net_y as f32 * MINIMAP_CELL_SIZE + 0.5),
+                                width: Val::Px(MINIMAP_CELL_SIZE - 1.0),
+                                height: Val::Px(MINIMAP_CELL_SIZE - 1.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::NONE),
+                            UiTransform::default(),
+                            MinimapCell(pos),
+                        ));
+                    }
+                }
+            }
+        });
+
+    commands.spawn((
+        Text::new(">"),
+        TextFont {
+            font_size: INDICATOR_SIZE,
+            ..default()
+        },
+        TextColor(theme.food),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        UiTransform::default(),
+        Visibility::Hidden,
+        FoodIndicator,
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
+fn update_minimap(
+    changed: Query<(), Changed<GridPosition>>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<&GridPosition>,
+    food_query: Query<&GridPosition, With<Food>>,
+    mut cells: Query<(&MinimapCell, &mut BackgroundColor, &mut UiTransform)>,
+    theme: Res<Theme>,
+) {
+    if changed.is_empty() && !theme.is_changed() {
+        return;
+    }
+
+    let same_cell = |a: &GridPosition, b: &GridPosition| a.face == b.face && a.x == b.x && a.y == b.y;
+
+    let head_pos = snake_body.0.first().and_then(|&e| positions.get(e).ok());
+    let body_positions: Vec<&GridPosition> = snake_body
+        .0
+        .iter()
+        .skip(1)
+        .filter_map(|&e| positions.get(e).ok())
+        .collect();
+
+    for (cell, mut color, mut ui_transform) in cells.iter_mut() {
+        let is_food = food_query.iter().any(|p| same_cell(p, &cell.0));
+        color.0 = if head_pos.is_some_and(|p| same_cell(p, &cell.0)) {
+            theme.head
+        } else if body_positions.iter().any(|p| same_cell(p, &cell.0)) {
+            theme.body
+        } else if is_food {
+            theme.food
+        } else {
+            Color::NONE
+        };
+        let angle = if is_food { theme.food_shape.angle() } else { 0.0 };
+        ui_transform.rotation = Rot2::radians(angle);
+    }
+}
+
+fn update_food_indicator(
+    food_query: Query<&GridPosition, With<Food>>,
+    head_query: Query<&GridPosition, With<SnakeHead>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut indicator_query: Query<(&mut Node, &mut UiTransform, &mut Visibility), With<FoodIndicator>>,
+) {
+    let Ok((mut node, mut ui_transform, mut visibility)) = indicator_query.single_mut() else {
+        return;
+    };
+
+    let (Ok(food_pos), Ok(head_pos), Ok((camera, camera_transform))) =
+        (food_query.single(), head_query.single(), camera_query.single())
+    else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let food_world = grid_to_world(food_pos);
+    let facing_camera = face_normal(food_pos.face).dot(camera_transform.translation() - food_world) > 0.0;
+    let on_screen = camera
+        .world_to_viewport(camera_transform, food_world)
+        .is_ok_and(|p| p.x >= 0.0 && p.y >= 0.0 && p.x <= viewport_size.x && p.y <= viewport_size.y);
+
+    if facing_camera && on_screen {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    // Project the head-to-food direction onto the camera plane; UI y grows downwards.
+    let to_food = food_world - grid_to_world(head_pos);
+    let screen_dir = Vec2::new(
+        to_food.dot(*camera_transform.right()),
+        -to_food.dot(*camera_transform.up()),
+    );
+    let Some(screen_dir) = screen_dir.try_normalize() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let center = viewport_size / 2.0;
+    let radius = (viewport_size.min_element() / 2.0 - INDICATOR_MARGIN).max(0.0);
+    let arrow_pos = center + screen_dir * radius - Vec2::splat(INDICATOR_SIZE / 2.0);
+
+    node.left = Val::Px(arrow_pos.x);
+    node.top = Val::Px(arrow_pos.y);
+    ui_transform.rotation = Rot2::radians(screen_dir.y.atan2(screen_dir.x));
+    *visibility = Visibility::Visible;
+}
+
+fn update_camera(
+    head_query: Query<&GridPosition, With<SnakeHead>>,
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+    time: Res<Time>,
+) {
+    let Ok(head_pos) = head_query.single() else {
+        return;
+    };
+
+    let Ok(mut camera_transform) = camera_query.single_mut() else {
+        return;
+    };
+
+    let head_world = grid_to_world(head_pos);
+
+    let face_normal = face_normal(head_pos.face);
+
+    let target_camera_pos = head_world + face_normal * CAMERA_DISTANCE * 0.7;
+
+    camera_transform.translation = camera_transform
+        .translation
+        .lerp(target_camera_pos, time.delta_secs() * 2.0);
+
+    let up = if face_normal.abs().y > 0.9 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+
+    camera_transform.look_at(Vec3::ZERO, up);
+}
+
+// Carries out the developer console's commands. A round they change no longer
+// plays out as recorded, so it isn't offered as a best run.
+// What the console commands change in a round.
+#[derive(SystemParam)]
+struct ConsoleSnake<'w, 's> {
+    commands: Commands<'w, 's>,
+    snake_body: ResMut<'w, SnakeBody>,
+    positions: Query<'w, 's, (&'static mut GridPosition, &'static mut PreviousGridPosition), Without<Food>>,
+    food: Query<'w, 's, Entity, With<Food>>,
+    current_dir: Res<'w, CurrentDirection>,
+    snake_assets: Res<'w, SnakeAssets>,
+}
+
+impl ConsoleTarget for ConsoleSnake<'_, '_> {
+    type Cell = GridPosition;
+
+    fn cell(&self, face: Option<&str>, x: i32, y: i32) -> Result<GridPosition, String> {
+        let face = match face {
+            Some(name) => CubeFace::named(name).ok_or_else(|| format!("no face {name:?}; faces are {:?}", CubeFace::ALL))?,
+            None => {
+                let head = self.snake_body.0.first().and_then(|&entity| self.positions.get(entity).ok());
+                head.map_or(CubeFace::Top, |(head, _)| head.face)
+            }
+        };
+        if !(0..GRID_SIZE).contains(&x) || !(0..GRID_SIZE).contains(&y) {
+            return Err(format!("({x}, {y}) is off the {GRID_SIZE}x{GRID_SIZE} face"));
+        }
+        Ok(GridPosition { face, x, y })
+    }
+
+    fn describe(&self, pos: GridPosition) -> String {
+        format!("{:?} ({}, {})", pos.face, pos.x, pos.y)
+    }
+
+    fn grow(&mut self, count: u32) -> Option<usize> {
+        let tail = self.snake_body.0.last().and_then(|&entity| self.positions.get(entity).ok()).map(|(pos, _)| *pos)?;
+        for _ in 0..count {
+            let segment = self.commands.spawn(segment_bundle(tail, &self.snake_assets)).id();
+            self.snake_body.0.push(segment);
+        }
+        Some(self.snake_body.0.len())
+    }
+
+    fn spawn_food(&mut self, pos: GridPosition) {
+        for entity in self.food.iter() {
+            self.commands.entity(entity).despawn();
+        }
+        self.commands.spawn(food_bundle(pos, &self.snake_assets));
+    }
+
+    // The body follows the snake's own way over edges.
+    fn teleport(&mut self, pos: GridPosition) {
+        let (mut trail, mut back) = (pos, self.current_dir.0.reversed());
+        for &entity in &self.snake_body.0 {
+            if let Ok((mut current, mut prev)) = self.positions.get_mut(entity) {
+                *current = trail;
+                prev.0 = trail;
+            }
+            (trail, back) = move_position(trail, back);
+        }
+    }
+}
+
+fn describe_round(
+    current_dir: Res<CurrentDirection>,
+    next_dir: Res<NextDirection>,
+    snake_body: Res<SnakeBody>,
+    positions: Query<&GridPosition>,
+    food: Query<&GridPosition, With<Food>>,
+    cheats: Res<Cheats>,
+    mut lines: ResMut<DebugLines>,
+) {
+    let cell = |pos: &GridPosition| format!("{:?} ({}, {})", pos.face, pos.x, pos.y);
+    let head = snake_body.0.first().and_then(|&entity| positions.get(entity).ok());
+    lines.0.extend([
+        format!("Direction: {:?}, next {:?}", current_dir.0, next_dir.0),
+        format!("Head: {}", head.map_or("-".to_string(), cell)),
+        format!("Length: {}", snake_body.0.len()),
+        format!("Food: {}", food.single().map_or("-".to_string(), cell)),
+    ]);
+    if cheats.god_mode {
+        lines.0.push("God mode".to_string());
+    }
+}
+
+// Every POINTS_PER_LEVEL points shortens the tick and speeds the music up to match.
+fn update_level(
+    score: Res<Score>,
+    rules: Res<RoundRules>,
+    mut level: ResMut<Level>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut tempo: ResMut<MusicTempo>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let new_level = score.0 / POINTS_PER_LEVEL;
+    if new_level <= level.0 {
+        return;
+    }
+
+    level.0 = new_level;
+    let interval = tick_interval(rules.speed, new_level);
+    fixed_time.set_timestep(Duration::from_secs_f32(interval));
+    tempo.0 = MOVE_INTERVAL / interval;
+    sfx.write(PlaySfx(Sfx::LevelUp));
+}
+
+impl Rewind for SavedRound {
+    // The snake and food are spawned afresh, which also brings a dead snake back.
+    // The continuous body mesh is kept and follows the new segments.
+    fn restore(&self, world: &mut World) {
+        let stale: Vec<Entity> = world
+            .query_filtered::<Entity, Or<(With<SnakeHead>, With<SnakeSegment>, With<Food>)>>()
+            .iter(world)
+            .collect();
+        for entity in stale {
+            world.despawn(entity);
+        }
+        world.resource_scope(|world, snake_assets: Mut<SnakeAssets>| {
+            let body = self
+                .snake
+                .iter()
+                .enumerate()
+                .map(|(i, &pos)| match i {
+                    0 => world.spawn(head_bundle(pos, &snake_assets)).id(),
+                    _ => world.spawn(segment_bundle(pos, &snake_assets)).id(),
+                })
+                .collect();
+            world.insert_resource(SnakeBody(body));
+            if let Some(food) = self.food {
+                world.spawn(food_bundle(food, &snake_assets));
+            }
+        });
+
+        world.insert_resource(CurrentDirection(self.direction));
+        world.insert_resource(NextDirection(self.next_direction));
+        world.insert_resource(QueuedTurn::default());
+        world.insert_resource(Score(self.score));
+        world.insert_resource(Level(self.level));
+        world.insert_resource(GameOver(false));
+        world.insert_resource(GameRng::from_state(self.rng));
+        world.insert_resource(Recording(self.recording.clone()));
+        world.insert_resource(DeathSequence::default());
+
+        let interval = tick_interval(self.speed, self.level);
+        world.resource_mut::<Time<Fixed>>().set_timestep(Duration::from_secs_f32(interval));
+        world.resource_mut::<MusicTempo>().0 = MOVE_INTERVAL / interval;
+        if let (Some(ghost), Some(recording)) = (&mut world.resource_mut::<GhostRun>().0, &self.recording) {
+            ghost.rewind(&Cube, recording.ticks);
+        }
+    }
+}
+
+fn record_result(
+    mut game_ended: MessageReader<GameEnded>,
+    rules: Res<RoundRules>,
+    mut high_scores: ResMut<HighScores>,
+    mut round_save: ResMut<RoundSave>,
+    mut recording: ResMut<Recording>,
+    mut best_replays: ResMut<BestReplays>,
+    travel: Res<TimeTravel>,
+    cheats: Res<Cheats>,
+) {
+    for ended in game_ended.read() {
+        round_save.clear();
+        // A rewound round was scored when it first ended, and a cheated one isn't.
+        if travel.rewound() || cheats.cheated {
+            continue;
+        }
+        high_scores.record(ended.score, rules.mode);
+        if let Some(recording) = &mut recording.0 {
+            recording.score = ended.score;
+            best_replays.offer(recording);
+        }
+    }
+}
+
+fn show_death(
+    mut commands: Commands,
+    mut snake_died: MessageReader<SnakeDied<GridPosition>>,
+    snake_body: Res<SnakeBody>,
+    effects: Res<EffectsSettings>,
+    mut death: ResMut<DeathSequence>,
+    mut screen_shake: ResMut<ScreenShake>,
+    theme: Res<Theme>,
+) {
+    if snake_died.read().count() == 0 || death.is_started() {
+        return;
+    }
+
+    screen_shake.add_trauma(0.8, &effects);
+    spawn_flash(&mut commands, &effects, theme.flash.with_alpha(0.6), 0.4);
+    death.start(snake_body.0.iter().copied(), &effects);
+}
+
+fn update_score_text(
+    score: Res<Score>,
+    level: Res<Level>,
+    death: Res<DeathSequence>,
+    high_scores: Res<HighScores>,
+    rules: Res<RoundRules>,
+    ghost: Res<GhostRun>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    for mut text in query.iter_mut() {
+        if death.is_finished() {
+            let best = high_scores.best(rules.mode).unwrap_or(0);
+            **text = format!("Game Over! Final Score: {}  Best: {}\nPress Enter for menu", score.0, best);
+        } else {
+            **text = format!("Score: {}  Level: {}", score.0, level.0 + 1);
+            if let Some(ghost) = &ghost.0 {
+                **text += &format!("  vs Ghost: {:+}", i64::from(score.0) - i64::from(ghost.score));
+            }
+        }
+    }
+}
+
+// Materials are shared, so recolouring them updates every snake, ghost, food and grid entity.
+fn apply_theme(
+    theme: Res<Theme>,
+    snake_assets: Res<SnakeAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut minimap_faces: Query<&mut BackgroundColor, With<MinimapFace>>,
+    mut texts: Query<(&mut TextColor, Has<FoodIndicator>), Or<(With<ScoreText>, With<FoodIndicator>)>>,
+) {
+    clear_color.0 = theme.background;
+    for (handle, color) in [
+        (&snake_assets.head_material, theme.head),
+        (&snake_assets.segment_material, theme.body),
+        (&snake_assets.food_material, theme.food),
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            *material = themed_material(color);
+        }
+    }
+    for (handle, color) in [
+        (&snake_assets.ghost_head_material, theme.head),
+        (&snake_assets.ghost_segment_material, theme.body),
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            *material = ghost_material(color);
+        }
+    }
+    if let Some(material) = materials.get_mut(&snake_assets.grid_material) {
+        material.base_color = theme.grid;
+    }
+    for mut color in minimap_faces.iter_mut() {
+        color.0 = theme.grid.with_alpha(0.5);
+    }
+    for (mut color, is_indicator) in texts.iter_mut() {
+        color.0 = if is_indicator { theme.food } else { theme.text };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+    use crate::dev_console::{ConsoleCommand, DevConsole};
+    use crate::launcher::ActiveGame;
+    use crate::stats::Stats;
+
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO))
+            .insert_resource(GameRng::seeded(0))
+            .add_plugins(Snake3dPlugin);
+
+        // Run startup in the picker, enter the game's menu, then start a round
+        // without letting any time pass.
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<ActiveGame>>()
+            .set(Snake3dPlugin::active());
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+        app
+    }
+
+    // Advances time by exactly one move, then lets go of any keys so each press
+    // lasts a single tick.
+    fn tick(app: &mut App) {
+        let step = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(step));
+        app.update();
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release_all();
+        keyboard.clear();
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(key);
+    }
+
+    // Presses and lets go of a key without letting time pass.
+    fn tap(app: &mut App, key: KeyCode) {
+        press(app, key);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release_all();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+    }
+
+    // Cells from head to tail.
+    fn body(app: &App) -> Vec<GridPosition> {
+        let world = app.world();
+        world
+            .resource::<SnakeBody>()
+            .0
+            .iter()
+            .map(|&entity| *world.get::<GridPosition>(entity).unwrap())
+            .collect()
+    }
+
+    fn set_state(app: &mut App, state: GameState) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+        app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+    }
+
+    fn food(app: &mut App) -> GridPosition {
+        let world = app.world_mut();
+        *world.query_filtered::<&GridPosition, With<Food>>().single(world).unwrap()
+    }
+
+    fn place_food(app: &mut App, pos: GridPosition) {
+        let world = app.world_mut();
+        *world.query_filtered::<&mut GridPosition, With<Food>>().single_mut(world).unwrap() = pos;
+    }
+
+    fn place_food_ahead_of_head(app: &mut App) {
+        let dir = app.world().resource::<NextDirection>().0;
+        let (next_pos, _) = move_position(body(app)[0], dir);
+        place_food(app, next_pos);
+    }
+
+    // A cell the tests never steer through.
+    fn park_food(app: &mut App) {
+        place_food(app, GridPosition { face: CubeFace::Bottom, x: 0, y: 0 });
+    }
+
+    // Runs a developer console command without letting time pass.
+    fn run_command(app: &mut App, line: &str) {
+        app.world_mut().write_message(ConsoleCommand::parse(line).unwrap());
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+        app.update();
+    }
+
+    #[test]
+    fn surface_lerp_follows_cube_edge() {
+        let from = GridPosition { face: CubeFace::Front, x: 3, y: GRID_SIZE - 1 };
+        let to = GridPosition { face: CubeFace::Top, x: 3, y: 0 };
+
+        assert!(surface_lerp(&from, &to, 0.0).distance(grid_to_world(&from)) < 1e-5);
+        assert!(surface_lerp(&from, &to, 1.0).distance(grid_to_world(&to)) < 1e-5);
+
+        let half_cube = CUBE_SIZE / 2.0;
+        for i in 0..=10 {
+            let p = surface_lerp(&from, &to, i as f32 / 10.0);
+            let distance_to_cube = (p.abs() - Vec3::splat(half_cube)).max(Vec3::ZERO).length();
+            assert!((distance_to_cube - SURFACE_OFFSET).abs() < 1e-4, "{p} leaves the surface");
+        }
+    }
+
+    #[test]
+    fn every_edge_leads_onto_the_neighbouring_face() {
+        let reverse = |dir| match dir {
+            LocalDirection::Up => LocalDirection::Down,
+            LocalDirection::Down => LocalDirection::Up,
+            LocalDirection::Left => LocalDirection::Right,
+            LocalDirection::Right => LocalDirection::Left,
+        };
+        let edges = |i| {
+            [
+                (i, GRID_SIZE - 1, LocalDirection::Up),
+                (i, 0, LocalDirection::Down),
+                (0, i, LocalDirection::Left),
+                (GRID_SIZE - 1, i, LocalDirection::Right),
+            ]
+        };
+
+        for face in CubeFace::ALL {
+            for (x, y, dir) in (0..GRID_SIZE).flat_map(edges) {
+                let from = GridPosition { face, x, y };
+                let (to, new_dir) = move_position(from, dir);
+
+                // Over the edge and heading away from the face just left, and
+                // turning round retraces the step.
+                assert_ne!(to.face, face);
+                assert!(grid_to_world(&from).distance(grid_to_world(&to)) < CELL_SIZE, "{from:?} -> {to:?}");
+                assert!(direction_vector(to.face, new_dir).distance(-face_normal(face)) < 1e-5, "{from:?} -> {to:?}");
+                assert_eq!(move_position(to, reverse(new_dir)), (from, reverse(dir)));
+            }
+        }
+    }
+
+    #[test]
+    fn body_path_spans_head_to_tail() {
+        let cells = [
+            GridPosition { face: CubeFace::Top, x: 4, y: 0 },
+            GridPosition { face: CubeFace::Front, x: 4, y: GRID_SIZE - 1 },
+            GridPosition { face: CubeFace::Front, x: 5, y: GRID_SIZE - 1 },
+        ];
+        let path = body_path(&cells, 0.0, 2.0);
+
+        assert!(path[0].1.distance(grid_to_world(&cells[0])) < 1e-5);
+        assert!(path.last().unwrap().1.distance(grid_to_world(&cells[2])) < 1e-5);
+        assert!(path.windows(2).all(|w| w[0].1.distance(w[1].1) < CELL_SIZE / 2.0));
+    }
+
+    #[test]
+    fn eating_does_not_allocate_assets() {
+        let mut app = headless_app();
+        app.insert_resource(BodyStyle::Continuous);
+        tick(&mut app);
+
+        let mesh_count = app.world().resource::<Assets<Mesh>>().len();
+        let material_count = app.world().resource::<Assets<StandardMaterial>>().len();
+
+        for _ in 0..3 {
+            place_food_ahead_of_head(&mut app);
+            tick(&mut app);
+        }
+
+        assert_eq!(app.world().resource::<Score>().0, 30);
+        assert_eq!(app.world().resource::<SnakeBody>().0.len(), 6);
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), mesh_count);
+        assert_eq!(
+            app.world().resource::<Assets<StandardMaterial>>().len(),
+            material_count
+        );
+    }
+
+    #[test]
+    fn eating_scores_and_grows_at_the_tail() {
+        let mut app = headless_app();
+        let before = body(&app);
+
+        // The new segment appears on the same tick, in the cell the tail left.
+        place_food_ahead_of_head(&mut app);
+        tick(&mut app);
+        assert_eq!(app.world().resource::<Score>().0, 10);
+        let grown = body(&app);
+        assert_eq!(grown.len(), 4);
+        assert_eq!(grown[1..], before[..]);
+    }
+
+    #[test]
+    fn crossing_an_edge_continues_on_the_next_face() {
+        let mut app = headless_app();
+        park_food(&mut app);
+        let start = body(&app)[0];
+
+        for _ in start.y..GRID_SIZE {
+            tick(&mut app);
+        }
+        let head = body(&app)[0];
+        assert_eq!(head.face, CubeFace::Back);
+        assert_eq!(head.y, GRID_SIZE - 1);
+        assert_eq!(app.world().resource::<CurrentDirection>().0, LocalDirection::Down);
+        let changed = app.world().resource::<Messages<FaceChanged<CubeFace>>>();
+        let changed: Vec<_> = changed.get_cursor().read(changed).map(|changed| (changed.from, changed.to)).collect();
+        assert_eq!(changed, [(start.face, CubeFace::Back)]);
+
+        tick(&mut app);
+        assert_eq!(body(&app)[0], GridPosition { y: GRID_SIZE - 2, ..head });
+        assert!(!app.world().resource::<GameOver>().0);
+    }
+
+    #[test]
+    fn console_commands_set_up_an_edge_crossing() {
+        let mut app = headless_app();
+        park_food(&mut app);
+        run_command(&mut app, "teleport front 4 9");
+        run_command(&mut app, "grow 2");
+        let at = |face, x, y| GridPosition { face, x, y };
+        assert_eq!(
+            body(&app),
+            [
+                at(CubeFace::Front, 4, 9),
+                at(CubeFace::Front, 4, 8),
+                at(CubeFace::Front, 4, 7),
+                at(CubeFace::Front, 4, 7),
+                at(CubeFace::Front, 4, 7),
+            ]
+        );
+        assert!(app.world().resource::<Cheats>().cheated);
+
+        tick(&mut app);
+        assert!(app.world().resource::<Recording>().0.is_none());
+        assert_eq!(body(&app)[0], at(CubeFace::Top, 4, 0));
+        assert_eq!(body(&app)[4], at(CubeFace::Front, 4, 7));
+
+        run_command(&mut app, "spawn_food 9 9");
+        assert_eq!(food(&mut app), at(CubeFace::Top, 9, 9));
+        run_command(&mut app, "teleport side 0 0");
+        assert_eq!(body(&app)[0], at(CubeFace::Top, 4, 0));
+        assert!(app.world().resource::<DevConsole>().log().last().unwrap().starts_with("no face"));
+    }
+
+    #[test]
+    fn stepping_back_over_an_edge_and_forward_again() {
+        let mut app = headless_app();
+        park_food(&mut app);
+        run_command(&mut app, "teleport front 4 9");
+        tap(&mut app, KeyCode::F4);
+        tick(&mut app);
+        let over_the_edge = body(&app);
+        tick(&mut app);
+        let after = body(&app);
+        assert_eq!(after[0], GridPosition { face: CubeFace::Top, x: 4, y: 1 });
+
+        tap(&mut app, KeyCode::F6);
+        assert_eq!(body(&app), over_the_edge);
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+        let world = app.world_mut();
+        assert_eq!(world.query_filtered::<(), With<SnakeHead>>().iter(world).count(), 1);
+        assert_eq!(world.query_filtered::<(), With<HeadEye>>().iter(world).count(), 4);
+
+        tap(&mut app, KeyCode::F7);
+        assert_eq!(body(&app), after);
+
+        // From the latest tick kept, stepping forward plays one more.
+        tap(&mut app, KeyCode::F7);
+        app.update();
+        assert_eq!(body(&app)[0], GridPosition { face: CubeFace::Top, x: 4, y: 2 });
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+    }
+
+    #[test]
+    fn turning_into_the_body_ends_the_round() {
+        let mut app = headless_app();
+        for _ in 0..2 {
+            place_food_ahead_of_head(&mut app);
+            tick(&mut app);
+        }
+        park_food(&mut app);
+        assert_eq!(body(&app).len(), 5);
+
+        for key in [KeyCode::ArrowRight, KeyCode::ArrowDown] {
+            press(&mut app, key);
+            tick(&mut app);
+            assert!(!app.world().resource::<GameOver>().0);
+        }
+        press(&mut app, KeyCode::ArrowLeft);
+        tick(&mut app);
+        assert!(app.world().resource::<GameOver>().0);
+
+        let totals = app.world().resource::<Stats>().totals();
+        assert_eq!((totals.food_eaten, totals.body_deaths), (2, 1));
+    }
+
+    #[test]
+    fn following_the_tail_is_safe() {
+        let mut app = headless_app();
+        place_food_ahead_of_head(&mut app);
+        tick(&mut app);
+        park_food(&mut app);
+        assert_eq!(body(&app).len(), 4);
+
+        // Four segments circling a 2x2 square: the last turn moves the head into
+        // the cell the tail is leaving on the same tick.
+        for key in [KeyCode::ArrowRight, KeyCode::ArrowDown] {
+            press(&mut app, key);
+            tick(&mut app);
+        }
+        let tail = *body(&app).last().unwrap();
+        press(&mut app, KeyCode::ArrowLeft);
+        tick(&mut app);
+        assert!(!app.world().resource::<GameOver>().0);
+        assert_eq!(body(&app)[0], tail);
+    }
+
+    #[test]
+    fn a_continued_round_plays_on_as_if_never_left() {
+        let mut left = headless_app();
+        let mut stayed = headless_app();
+        for app in [&mut left, &mut stayed] {
+            place_food_ahead_of_head(app);
+            tick(app);
+            press(app, KeyCode::ArrowRight);
+            tick(app);
+            let half_step = app.world().resource::<Time<Fixed>>().timestep() / 2;
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(half_step));
+            app.update();
+        }
+
+        set_state(&mut left, GameState::Menu);
+        assert!(left.world().resource::<RoundSave>().has_save());
+        left.world_mut().resource_mut::<Settings>().gameplay.speed = Speed::Fast;
+        left.world_mut().resource_mut::<RoundSave>().request_resume();
+        set_state(&mut left, GameState::Playing);
+        assert!(!left.world().resource::<RoundSave>().has_save());
+
+        // The round keeps its own speed without changing the player's settings.
+        assert_eq!(left.world().resource::<Settings>().gameplay.speed, Speed::Fast);
+        assert_eq!(
+            left.world().resource::<Time<Fixed>>().timestep(),
+            stayed.world().resource::<Time<Fixed>>().timestep()
+        );
+
+        // Eating after the break draws the next food from the restored RNG.
+        for app in [&mut left, &mut stayed] {
+            place_food_ahead_of_head(app);
+            tick(app);
+            tick(app);
+        }
+        assert_eq!(body(&left), body(&stayed));
+        assert_eq!(food(&mut left), food(&mut stayed));
+        assert_eq!(left.world().resource::<Score>().0, 20);
+        assert_eq!(
+            left.world().resource::<Time<Fixed>>().overstep(),
+            stayed.world().resource::<Time<Fixed>>().overstep()
+        );
+    }
+
+    #[test]
+    fn the_ghost_plays_back_the_best_run_alongside() {
+        let mut app = headless_app();
+        let best = Replay {
+            ticks: 8,
+            turns: vec![(0, LocalDirection::Up), (3, LocalDirection::Right)],
+            ..Replay::new(GameRng::seeded(0).state(), GameMode::Classic, None)
+        };
+        app.world_mut().resource_mut::<BestReplays>().offer(&best);
+        app.world_mut().resource_mut::<Settings>().gameplay.ghost = true;
+        set_state(&mut app, GameState::Menu);
+        set_state(&mut app, GameState::Playing);
+
+        for tick_index in 0..8 {
+            if tick_index == 3 {
+                press(&mut app, KeyCode::ArrowRight);
+            }
+            tick(&mut app);
+            let ghost = app.world().resource::<GhostRun>().0.as_ref().unwrap();
+            assert_eq!(ghost.body, body(&app));
+            assert!(!ghost.is_finished());
+        }
+
+        // Past the end of its run the ghost stops where it was.
+        let last = body(&app);
+        tick(&mut app);
+        let ghost = app.world().resource::<GhostRun>().0.as_ref().unwrap();
+        assert!(ghost.is_finished());
+        assert_eq!(ghost.body, last);
+        let world = app.world_mut();
+        assert_eq!(world.query::<&GhostSegment>().iter(world).count(), last.len());
+    }
+
+    #[test]
+    fn reversing_direction_is_ignored() {
+        let mut app = headless_app();
+        park_food(&mut app);
+        let start = body(&app)[0];
+
+        press(&mut app, KeyCode::ArrowDown);
+        tick(&mut app);
+        assert_eq!(app.world().resource::<CurrentDirection>().0, LocalDirection::Up);
+        assert_eq!(body(&app)[0], GridPosition { y: start.y + 1, ..start });
+    }
+}
@@ -0,0 +1,28 @@
+//! Cell grid layout shared by the grid puzzles: the board's bottom-left corner
+//! sits at the world origin, cell `(0, 0)` is the bottom-left cell, and the
+//! camera is moved to frame the board.
+
+use bevy::prelude::*;
+
+pub fn cell_to_world(cell: IVec2, cell_size: f32) -> Vec2 {
+    (cell.as_vec2() + Vec2::splat(0.5)) * cell_size
+}
+
+pub fn world_to_cell(position: Vec2, cell_size: f32) -> IVec2 {
+    (position / cell_size).floor().as_ivec2()
+}
+
+/// Centres the camera on a `size` board below a HUD strip `hud_height` tall,
+/// zooming out when the board would not fit in `viewport` world units.
+pub fn frame_board(size: IVec2, cell_size: f32, hud_height: f32, viewport: Vec2) -> Transform {
+    let board = size.as_vec2() * cell_size;
+    let zoom = (board / (viewport - Vec2::new(0.0, hud_height))).max_element().max(1.0);
+    Transform::from_translation((board / 2.0 + Vec2::new(0.0, hud_height / 2.0 * zoom)).extend(0.0))
+        .with_scale(Vec3::splat(zoom))
+}
+
+/// The world position under the mouse cursor, if it is over the window.
+pub fn cursor_position(window: &Window, camera: &Camera, transform: &GlobalTransform) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    camera.viewport_to_world_2d(transform, cursor).ok()
+}
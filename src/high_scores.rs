@@ -0,0 +1,85 @@
+//! Per-game high score tables, persisted through [`crate::storage`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::GameMode;
+use crate::storage;
+
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct HighScore {
+    pub score: u32,
+    pub mode: GameMode,
+}
+
+/// Best scores, highest first. The default table is not persisted, which keeps
+/// headless apps away from the player's saved scores.
+#[derive(Resource, Default, Debug)]
+pub struct HighScores {
+    key: Option<String>,
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn load(game_id: &str) -> Self {
+        let key = format!("{game_id}-high-scores");
+        let mut entries: Vec<HighScore> = storage::load(&key).unwrap_or_default();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(MAX_ENTRIES);
+        Self {
+            key: Some(key),
+            entries,
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    pub fn best(&self, mode: GameMode) -> Option<u32> {
+        self.entries.iter().find(|entry| entry.mode == mode).map(|entry| entry.score)
+    }
+
+    /// Adds a finished round to the table; returns whether it made the cut.
+    pub fn record(&mut self, score: u32, mode: GameMode) -> bool {
+        if score == 0 {
+            return false;
+        }
+
+        // Ties keep the older entry ahead of the new one.
+        let index = self.entries.partition_point(|entry| entry.score >= score);
+        if index >= MAX_ENTRIES {
+            return false;
+        }
+
+        self.entries.insert(index, HighScore { score, mode });
+        self.entries.truncate(MAX_ENTRIES);
+        if let Some(key) = &self.key {
+            storage::save(key, &self.entries);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_best_scores_in_order() {
+        let mut scores = HighScores::default();
+        for score in (1..=12).map(|i| i * 10) {
+            assert!(scores.record(score, GameMode::Classic));
+        }
+
+        assert!(!scores.record(10, GameMode::Zen));
+        assert!(!scores.record(0, GameMode::Zen));
+        assert!(scores.record(55, GameMode::Zen));
+
+        let table: Vec<u32> = scores.entries().iter().map(|entry| entry.score).collect();
+        assert_eq!(table, [120, 110, 100, 90, 80, 70, 60, 55, 50, 40]);
+        assert_eq!(scores.best(GameMode::Zen), Some(55));
+    }
+}
@@ -0,0 +1,117 @@
+//! Hosting several games in one app. Each game is a plugin implementing
+//! [`MiniGame`] and registered with [`AddMiniGame::add_mini_game`]; the menu's
+//! game picker lists every registered game.
+
+use bevy::prelude::*;
+
+use crate::daily::DailyLog;
+use crate::high_scores::HighScores;
+use crate::menu::GameState;
+use crate::replay::BestReplays;
+use crate::save_state::RoundSave;
+use crate::stats::Stats;
+use crate::telemetry::TelemetryLog;
+
+/// The game being played; `None` shows the game picker.
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ActiveGame(pub Option<&'static str>);
+
+/// A game in the collection. Implementors register their systems against
+/// [`MiniGame::on_enter`] / [`MiniGame::on_exit`] and guard `Update` systems with
+/// [`MiniGame::is_active`], since every game's plugin is added to the same app.
+pub trait MiniGame: Plugin {
+    /// Stable identifier, also used to key the game's high scores, saved round,
+    /// best runs, daily results and stats.
+    const ID: &'static str;
+    const NAME: &'static str;
+    /// Pixel-art preview for the picker, one string per row: `#` head, `o` body,
+    /// `*` food, `+` grid, anything else background. Coloured by the active theme.
+    const THUMBNAIL: &'static [&'static str];
+    /// Whether the game reads the board size setting.
+    const BOARD_SIZES: bool = false;
+    /// Whether the game can race a ghost of the best run.
+    const GHOSTS: bool = false;
+    /// Whether the game has a daily challenge.
+    const DAILY: bool = false;
+    /// Whether the game can log telemetry.
+    const TELEMETRY: bool = false;
+
+    fn active() -> ActiveGame {
+        ActiveGame(Some(Self::ID))
+    }
+
+    /// Runs when the game is picked. Entities spawned here should carry
+    /// `DespawnOnExit(Self::active())`.
+    fn on_enter() -> OnEnter<ActiveGame> {
+        OnEnter(Self::active())
+    }
+
+    fn on_exit() -> OnExit<ActiveGame> {
+        OnExit(Self::active())
+    }
+
+    fn is_active() -> impl FnMut(Option<Res<State<ActiveGame>>>) -> bool + Clone {
+        in_state(Self::active())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GameInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub thumbnail: &'static [&'static str],
+    pub board_sizes: bool,
+    pub ghosts: bool,
+    pub daily: bool,
+    pub telemetry: bool,
+}
+
+/// Registered games, in picker order.
+#[derive(Resource, Default, Debug)]
+pub struct GameRegistry(Vec<GameInfo>);
+
+impl GameRegistry {
+    pub fn games(&self) -> &[GameInfo] {
+        &self.0
+    }
+
+    pub fn get(&self, id: &str) -> Option<&GameInfo> {
+        self.0.iter().find(|game| game.id == id)
+    }
+}
+
+pub trait AddMiniGame {
+    fn add_mini_game<G: MiniGame>(&mut self, game: G) -> &mut Self;
+}
+
+impl AddMiniGame for App {
+    fn add_mini_game<G: MiniGame>(&mut self, game: G) -> &mut Self {
+        add_game_states(self);
+        self.init_resource::<GameRegistry>();
+        self.world_mut().resource_mut::<GameRegistry>().0.push(GameInfo {
+            id: G::ID,
+            name: G::NAME,
+            thumbnail: G::THUMBNAIL,
+            board_sizes: G::BOARD_SIZES,
+            ghosts: G::GHOSTS,
+            daily: G::DAILY,
+            telemetry: G::TELEMETRY,
+        });
+        self.add_systems(G::on_enter(), load_saved_data::<G>)
+            .add_plugins(game)
+    }
+}
+
+/// Sets up [`ActiveGame`] and [`GameState`]; safe to call from every game plugin.
+pub fn add_game_states(app: &mut App) {
+    app.init_state::<ActiveGame>().add_sub_state::<GameState>();
+}
+
+fn load_saved_data<G: MiniGame>(mut commands: Commands) {
+    commands.insert_resource(HighScores::load(G::ID));
+    commands.insert_resource(RoundSave::load(G::ID));
+    commands.insert_resource(BestReplays::load(G::ID));
+    commands.insert_resource(DailyLog::load(G::ID));
+    commands.insert_resource(Stats::load(G::ID));
+    commands.insert_resource(TelemetryLog::open(G::ID));
+}
@@ -0,0 +1,26 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+//! Code shared by the games in the collection.
+
+pub mod audio;
+pub mod clipboard;
+pub mod daily;
+pub mod dev_console;
+pub mod effects;
+pub mod games;
+pub mod grid;
+pub mod high_scores;
+pub mod launcher;
+pub mod menu;
+pub mod physics;
+pub mod random;
+pub mod replay;
+pub mod save_state;
+pub mod settings;
+pub mod snake_events;
+pub mod snake_rules;
+pub mod stats;
+pub mod storage;
+pub mod telemetry;
+pub mod theme;
+pub mod time_travel;
+pub mod tui;
@@ -0,0 +1,776 @@
+//! Menus shared by the games: the game picker, and each game's main menu with
+//! continue, play, daily challenge, mode select, settings and high scores. Items
+//! can be driven by keyboard, mouse, touch or gamepad.
+//!
+//! Games run their rounds in [`GameState::Playing`], spawning round entities on
+//! `OnEnter(GameState::Playing)` with `DespawnOnExit(GameState::Playing)`.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::clipboard;
+use crate::daily::{
+    DailyLog, DailyRequest, DailyRound, DailyRules, Date, BODY_EMOJI, FOOD_EMOJI, HEAD_EMOJI, PRACTICE_DAYS,
+};
+use crate::effects::DeathSequence;
+use crate::high_scores::HighScores;
+use crate::launcher::{add_game_states, ActiveGame, GameInfo, GameRegistry};
+use crate::save_state::RoundSave;
+use crate::settings::{cycle, BoardSize, ControlScheme, GameMode, Settings, SettingsPlugin, Speed};
+use crate::stats::{AchievementCatalog, Stats};
+use crate::theme::{Theme, ThemeLibrary, ThemePlugin};
+
+const VOLUME_STEP: f32 = 0.1;
+
+/// Exists while a game is active.
+#[derive(SubStates, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[source(ActiveGame = ActiveGame(Some(_)))]
+pub enum GameState {
+    #[default]
+    Menu,
+    Playing,
+}
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SettingsPlugin>() {
+            app.add_plugins(SettingsPlugin);
+        }
+        if !app.is_plugin_added::<ThemePlugin>() {
+            app.add_plugins(ThemePlugin);
+        }
+
+        add_game_states(app);
+        app.init_resource::<GameRegistry>()
+            .init_resource::<HighScores>()
+            .init_resource::<RoundSave>()
+            .init_resource::<DailyLog>()
+            .init_resource::<DailyRequest>()
+            .init_resource::<Stats>()
+            .init_resource::<AchievementCatalog>()
+            .init_resource::<MenuScreen>()
+            .init_resource::<MenuFocus>()
+            .init_resource::<CopyStatus>()
+            .add_message::<CopyDailyResult>()
+            .add_systems(OnEnter(ActiveGame(None)), open_game_picker)
+            .add_systems(OnExit(ActiveGame(None)), close_menu)
+            .add_systems(OnEnter(GameState::Menu), open_main_menu)
+            .add_systems(OnExit(GameState::Menu), close_menu)
+            .add_systems(
+                Update,
+                (menu_input, copy_daily_result, rebuild_menu, highlight_focus)
+                    .chain()
+                    .run_if(in_state(GameState::Menu).or(in_state(ActiveGame(None)))),
+            )
+            .add_systems(Update, leave_round.run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum MenuScreen {
+    #[default]
+    Games,
+    Main,
+    Daily,
+    Mode,
+    Settings,
+    HighScores,
+}
+
+/// Index of the focused item on the current screen.
+#[derive(Resource, Default)]
+struct MenuFocus(usize);
+
+/// How copying today's daily result went, shown until the screen changes.
+#[derive(Resource, Default)]
+struct CopyStatus(Option<String>);
+
+#[derive(Message)]
+struct CopyDailyResult;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum MenuAction {
+    Launch(&'static str),
+    SwitchGame,
+    Continue,
+    Play,
+    PlayDaily(DailyRound),
+    CopyDailyResult,
+    Open(MenuScreen),
+    SelectMode(GameMode),
+    Adjust(SettingField),
+    Back,
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SettingField {
+    BoardSize,
+    Speed,
+    Theme,
+    Controls,
+    Ghost,
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    Telemetry,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component)]
+struct MenuItem {
+    index: usize,
+    action: MenuAction,
+}
+
+#[derive(Default)]
+struct MenuIntent {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+    activate: bool,
+    back: bool,
+}
+
+fn menu_actions(
+    screen: MenuScreen,
+    game: Option<&GameInfo>,
+    registry: &GameRegistry,
+    round_save: &RoundSave,
+    daily_log: &DailyLog,
+    today: Date,
+) -> Vec<MenuAction> {
+    match screen {
+        MenuScreen::Games => {
+            let mut actions: Vec<MenuAction> = registry.games().iter().map(|game| MenuAction::Launch(game.id)).collect();
+            // Closing the tab is the web build's way out.
+            if !cfg!(target_arch = "wasm32") {
+                actions.push(MenuAction::Quit);
+            }
+            actions
+        }
+        MenuScreen::Main => {
+            let mut actions = vec![
+                MenuAction::Play,
+                MenuAction::Open(MenuScreen::Mode),
+                MenuAction::Open(MenuScreen::Settings),
+                MenuAction::Open(MenuScreen::HighScores),
+                MenuAction::SwitchGame,
+            ];
+            if game.is_some_and(|game| game.daily) {
+                actions.insert(1, MenuAction::Open(MenuScreen::Daily));
+            }
+            if round_save.has_save() {
+                actions.insert(0, MenuAction::Continue);
+            }
+            actions
+        }
+        // Today's round is scored until it has been tried; earlier days are practice.
+        MenuScreen::Daily => {
+            let today_round = DailyRound {
+                date: today,
+                scored: !daily_log.attempted(today),
+            };
+            let practice = (1..=PRACTICE_DAYS).map(|days| DailyRound {
+                date: today.plus_days(-days),
+                scored: false,
+            });
+            let copy = daily_log.result(today).map(|_| MenuAction::CopyDailyResult);
+            std::iter::once(today_round)
+                .chain(practice)
+                .map(MenuAction::PlayDaily)
+                .chain(copy)
+                .chain([MenuAction::Back])
+                .collect()
+        }
+        MenuScreen::Mode => GameMode::ALL
+            .into_iter()
+            .map(MenuAction::SelectMode)
+            .chain([MenuAction::Back])
+            .collect(),
+        MenuScreen::Settings => {
+            let mut fields = vec![
+                SettingField::Speed,
+                SettingField::Theme,
+                SettingField::Controls,
+                SettingField::MasterVolume,
+                SettingField::SfxVolume,
+                SettingField::MusicVolume,
+            ];
+            if game.is_some_and(|game| game.ghosts) {
+                fields.insert(3, SettingField::Ghost);
+            }
+            if game.is_some_and(|game| game.board_sizes) {
+                fields.insert(0, SettingField::BoardSize);
+            }
+            // Web builds have nowhere to write logs.
+            if game.is_some_and(|game| game.telemetry) && !cfg!(target_arch = "wasm32") {
+                fields.push(SettingField::Telemetry);
+            }
+            fields
+                .into_iter()
+                .map(MenuAction::Adjust)
+                .chain([MenuAction::Back])
+                .collect()
+        }
+        MenuScreen::HighScores => vec![MenuAction::Back],
+    }
+}
+
+fn action_label(action: MenuAction, settings: &Settings, registry: &GameRegistry) -> String {
+    let percent = |volume: f32| format!("{}%", (volume * 100.0).round());
+    let gameplay = &settings.gameplay;
+    match action {
+        MenuAction::Launch(id) => registry.get(id).map_or(id, |game| game.name).to_string(),
+        MenuAction::SwitchGame => "Switch Game".to_string(),
+        MenuAction::Continue => "Continue".to_string(),
+        MenuAction::Play => format!("Play ({})", gameplay.mode.label()),
+        MenuAction::PlayDaily(round) if round.scored => "Play today's challenge".to_string(),
+        MenuAction::PlayDaily(round) => format!("Practice {}", round.date),
+        MenuAction::CopyDailyResult => "Copy today's result".to_string(),
+        MenuAction::Open(MenuScreen::Daily) => "Daily Challenge".to_string(),
+        MenuAction::Open(MenuScreen::Mode) => "Mode".to_string(),
+        MenuAction::Open(MenuScreen::Settings) => "Settings".to_string(),
+        MenuAction::Open(MenuScreen::HighScores) => "High Scores".to_string(),
+        MenuAction::Open(MenuScreen::Games) => "Games".to_string(),
+        MenuAction::Open(MenuScreen::Main) | MenuAction::Back => "Back".to_string(),
+        MenuAction::SelectMode(mode) if mode == gameplay.mode => format!("{} (selected)", mode.label()),
+        MenuAction::SelectMode(mode) => mode.label().to_string(),
+        MenuAction::Adjust(field) => match field {
+            SettingField::BoardSize => format!("Board size: {}", gameplay.board_size.label()),
+            SettingField::Speed => format!("Speed: {}", gameplay.speed.label()),
+            SettingField::Theme => format!("Theme: {}", settings.theme),
+            SettingField::Controls => format!("Controls: {}", gameplay.controls.label()),
+            SettingField::Ghost => format!("Ghost: {}", if gameplay.ghost { "On" } else { "Off" }),
+            SettingField::MasterVolume => format!("Master volume: {}", percent(settings.audio.master)),
+            SettingField::SfxVolume => format!("Effects volume: {}", percent(settings.audio.sfx)),
+            SettingField::MusicVolume => format!("Music volume: {}", percent(settings.audio.music)),
+            SettingField::Telemetry => format!("Telemetry: {}", if settings.telemetry { "On" } else { "Off" }),
+        },
+        MenuAction::Quit => "Quit".to_string(),
+    }
+}
+
+// The picker brings its own camera since no game is around to provide one.
+fn open_game_picker(
+    mut commands: Commands,
+    mut screen: ResMut<MenuScreen>,
+    mut focus: ResMut<MenuFocus>,
+    theme: Res<Theme>,
+) {
+    commands.spawn((Camera2d, DespawnOnExit(ActiveGame(None))));
+    commands.insert_resource(ClearColor(theme.background));
+    *screen = MenuScreen::Games;
+    focus.0 = 0;
+}
+
+fn open_main_menu(mut screen: ResMut<MenuScreen>, mut focus: ResMut<MenuFocus>) {
+    // Assign even when already on Main so the menu is rebuilt after a round.
+    *screen = MenuScreen::Main;
+    focus.0 = 0;
+}
+
+fn close_menu(mut commands: Commands, roots: Query<Entity, With<MenuRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn active_game<'a>(active: &ActiveGame, registry: &'a GameRegistry) -> Option<&'a GameInfo> {
+    active.0.and_then(|id| registry.get(id))
+}
+
+// Renders a game's thumbnail rows into a small nearest-filtered image.
+fn thumbnail_image(rows: &[&str], theme: &Theme) -> Image {
+    let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0).max(1);
+    let height = rows.len().max(1);
+    let mut data = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row: Vec<char> = rows.get(y).map(|row| row.chars().collect()).unwrap_or_default();
+        for x in 0..width {
+            let color = match row.get(x) {
+                Some('#') => theme.head,
+                Some('o') => theme.body,
+                Some('*') => theme.food,
+                Some('+') => theme.grid,
+                _ => theme.background,
+            };
+            data.extend(color.to_srgba().to_u8_array());
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+fn rebuild_menu(
+    mut commands: Commands,
+    screen: Res<MenuScreen>,
+    active: Res<State<ActiveGame>>,
+    registry: Res<GameRegistry>,
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+    high_scores: Res<HighScores>,
+    round_save: Res<RoundSave>,
+    daily_log: Res<DailyLog>,
+    copy_status: Res<CopyStatus>,
+    stats: Res<Stats>,
+    catalog: Res<AchievementCatalog>,
+    mut images: ResMut<Assets<Image>>,
+    roots: Query<Entity, With<MenuRoot>>,
+) {
+    if !screen.is_changed()
+        && !settings.is_changed()
+        && !theme.is_changed()
+        && !copy_status.is_changed()
+        && !roots.is_empty()
+    {
+        return;
+    }
+
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let game = active_game(active.get(), &registry);
+    let today = Date::today();
+    let title = match *screen {
+        MenuScreen::Games => "Tiny Games",
+        MenuScreen::Main => game.map_or("", |game| game.name),
+        MenuScreen::Daily => "Daily Challenge",
+        MenuScreen::Mode => "Mode",
+        MenuScreen::Settings => "Settings",
+        MenuScreen::HighScores => "High Scores",
+    };
+
+    let mut lines = Vec::new();
+    if *screen == MenuScreen::HighScores {
+        lines = high_scores
+            .entries()
+            .iter()
+            .enumerate()
+            .map(|(rank, entry)| format!("{:>2}. {:>6}  {}", rank + 1, entry.score, entry.mode.label()))
+            .collect();
+        if lines.is_empty() {
+            lines.push("No scores yet".to_string());
+        }
+        // Only the snake games keep stats.
+        let totals = stats.totals();
+        if totals.games_played > 0 {
+            let achievements: Vec<_> = stats.achievements(&catalog).collect();
+            let unlocked = achievements.iter().filter(|achievement| stats.is_unlocked(&achievement.id));
+            lines.push(format!(
+                "Played {} · Food {} · Longest {}",
+                totals.games_played, totals.food_eaten, totals.longest_snake
+            ));
+            lines.push(format!("Achievements {}/{}", unlocked.count(), achievements.len()));
+        }
+    }
+    if *screen == MenuScreen::Settings {
+        lines.push("Left/Right to change".to_string());
+    }
+    // The day's rules, then how today's attempt went with its share grid drawn
+    // in theme colours, as the menu font has no emoji.
+    let mut share_grid = &[][..];
+    if *screen == MenuScreen::Daily {
+        let rules = DailyRules::for_date(today);
+        lines.push(format!("{today}: {} board, {} speed", rules.board_size.label(), rules.speed.label()));
+        if let Some(result) = daily_log.result(today) {
+            lines.push(format!("Today: {}", result.summary()));
+            share_grid = &result.grid;
+        }
+        lines.extend(copy_status.0.clone());
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(theme.background.with_alpha(0.85)),
+            GlobalZIndex(10),
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: 44.0,
+                    ..default()
+                },
+                TextColor(theme.head),
+                Node {
+                    margin: UiRect::bottom(Val::Px(16.0)),
+                    ..default()
+                },
+            ));
+
+            for line in lines {
+                parent.spawn((
+                    Text::new(line),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(theme.text),
+                ));
+            }
+
+            if !share_grid.is_empty() {
+                parent
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.0),
+                        margin: UiRect::vertical(Val::Px(8.0)),
+                        ..default()
+                    })
+                    .with_children(|grid| {
+                        for row in share_grid {
+                            grid.spawn(Node {
+                                column_gap: Val::Px(2.0),
+                                ..default()
+                            })
+                            .with_children(|row_node| {
+                                for emoji in row.chars() {
+                                    row_node.spawn((
+                                        Node {
+                                            width: Val::Px(12.0),
+                                            height: Val::Px(12.0),
+                                            ..default()
+                                        },
+                                        BackgroundColor(emoji_color(emoji, &theme)),
+                                    ));
+                                }
+                            });
+                        }
+                    });
+            }
+
+            let actions = menu_actions(*screen, game, &registry, &round_save, &daily_log, today);
+            for (index, action) in actions.into_iter().enumerate() {
+                let thumbnail = match action {
+                    MenuAction::Launch(id) => registry.get(id).map(|game| game.thumbnail),
+                    _ => None,
+                };
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(320.0),
+                            padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(12.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::NONE),
+                        BorderColor::all(Color::NONE),
+                        MenuItem { index, action },
+                    ))
+                    .with_children(|button| {
+                        if let Some(rows) = thumbnail {
+                            button.spawn((
+                                ImageNode::new(images.add(thumbnail_image(rows, &theme))),
+                                Node {
+                                    width: Val::Px(64.0),
+                                    height: Val::Px(64.0),
+                                    ..default()
+                                },
+                            ));
+                        }
+                        button.spawn((
+                            Text::new(action_label(action, &settings, &registry)),
+                            TextFont {
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(theme.text),
+                        ));
+                    });
+            }
+        });
+}
+
+// Copies the share text with its emoji grid, which the menu can only draw in
+// blocks of colour.
+fn copy_daily_result(
+    mut requests: MessageReader<CopyDailyResult>,
+    screen: Res<MenuScreen>,
+    active: Res<State<ActiveGame>>,
+    registry: Res<GameRegistry>,
+    daily_log: Res<DailyLog>,
+    mut status: ResMut<CopyStatus>,
+) {
+    if requests.read().count() == 0 {
+        if screen.is_changed() && status.0.is_some() {
+            status.0 = None;
+        }
+        return;
+    }
+
+    let today = Date::today();
+    let (Some(game), Some(result)) = (active_game(active.get(), &registry), daily_log.result(today)) else {
+        return;
+    };
+    status.0 = Some(match clipboard::copy(&result.share_text(game.name, today)) {
+        Ok(()) => "Copied to the clipboard".to_string(),
+        Err(err) => format!("Couldn't copy: {err}"),
+    });
+}
+
+fn emoji_color(emoji: char, theme: &Theme) -> Color {
+    match emoji {
+        HEAD_EMOJI => theme.head,
+        BODY_EMOJI => theme.body,
+        FOOD_EMOJI => theme.food,
+        _ => theme.grid,
+    }
+}
+
+fn highlight_focus(
+    focus: Res<MenuFocus>,
+    theme: Res<Theme>,
+    mut items: Query<(&MenuItem, &mut BackgroundColor, &mut BorderColor)>,
+) {
+    for (item, mut background, mut border) in items.iter_mut() {
+        let focused = item.index == focus.0;
+        background.set_if_neq(BackgroundColor(if focused { theme.grid } else { Color::NONE }));
+        border.set_if_neq(BorderColor::all(if focused { theme.head } else { Color::NONE }));
+    }
+}
+
+fn read_intent(keyboard: &ButtonInput<KeyCode>, gamepads: &Query<&Gamepad>) -> MenuIntent {
+    let mut intent = MenuIntent {
+        up: keyboard.any_just_pressed([KeyCode::ArrowUp, KeyCode::KeyW]),
+        down: keyboard.any_just_pressed([KeyCode::ArrowDown, KeyCode::KeyS]),
+        left: keyboard.any_just_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]),
+        right: keyboard.any_just_pressed([KeyCode::ArrowRight, KeyCode::KeyD]),
+        activate: keyboard.any_just_pressed([KeyCode::Enter, KeyCode::Space]),
+        back: keyboard.any_just_pressed([KeyCode::Escape, KeyCode::Backspace]),
+    };
+
+    for gamepad in gamepads.iter() {
+        intent.up |= gamepad.just_pressed(GamepadButton::DPadUp);
+        intent.down |= gamepad.just_pressed(GamepadButton::DPadDown);
+        intent.left |= gamepad.just_pressed(GamepadButton::DPadLeft);
+        intent.right |= gamepad.just_pressed(GamepadButton::DPadRight);
+        intent.activate |= gamepad.any_just_pressed([GamepadButton::South, GamepadButton::Start]);
+        intent.back |= gamepad.just_pressed(GamepadButton::East);
+    }
+    intent
+}
+
+fn menu_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    interactions: Query<(&MenuItem, &Interaction), Changed<Interaction>>,
+    items: Query<&MenuItem>,
+    library: Res<ThemeLibrary>,
+    mut focus: ResMut<MenuFocus>,
+    mut screen: ResMut<MenuScreen>,
+    mut settings: ResMut<Settings>,
+    mut round_save: ResMut<RoundSave>,
+    mut daily_request: ResMut<DailyRequest>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_game: ResMut<NextState<ActiveGame>>,
+    mut copy: MessageWriter<CopyDailyResult>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let count = items.iter().count();
+    if count == 0 {
+        return;
+    }
+
+    let intent = read_intent(&keyboard, &gamepads);
+    let mut pressed = None;
+
+    // Mouse hover and touch both move the focus; a press activates the item.
+    for (item, interaction) in interactions.iter() {
+        match interaction {
+            Interaction::Hovered => focus.0 = item.index,
+            Interaction::Pressed => {
+                focus.0 = item.index;
+                pressed = Some(item.action);
+            }
+            Interaction::None => {}
+        }
+    }
+
+    if intent.up {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+    if intent.down {
+        focus.0 = (focus.0 + 1) % count;
+    }
+    focus.0 = focus.0.min(count - 1);
+
+    let focused = items.iter().find(|item| item.index == focus.0).map(|item| item.action);
+    let (action, step) = if let Some(action) = pressed {
+        (action, 1)
+    } else if intent.back {
+        (MenuAction::Back, 1)
+    } else if let (Some(action @ MenuAction::Adjust(_)), true) = (focused, intent.left || intent.right) {
+        (action, if intent.left { -1 } else { 1 })
+    } else if let (Some(action), true) = (focused, intent.activate) {
+        (action, 1)
+    } else {
+        return;
+    };
+
+    match action {
+        MenuAction::Launch(id) => next_game.set(ActiveGame(Some(id))),
+        MenuAction::SwitchGame => next_game.set(ActiveGame(None)),
+        MenuAction::Continue => {
+            round_save.request_resume();
+            next_state.set(GameState::Playing);
+        }
+        MenuAction::Play => next_state.set(GameState::Playing),
+        MenuAction::PlayDaily(round) => {
+            daily_request.0 = Some(round);
+            next_state.set(GameState::Playing);
+        }
+        MenuAction::CopyDailyResult => {
+            copy.write(CopyDailyResult);
+        }
+        MenuAction::Open(target) => {
+            *screen = target;
+            focus.0 = 0;
+        }
+        MenuAction::SelectMode(mode) => {
+            if settings.gameplay.mode != mode {
+                settings.gameplay.mode = mode;
+            }
+            *screen = MenuScreen::Main;
+            focus.0 = 0;
+        }
+        MenuAction::Adjust(field) => {
+            let adjusted = adjust_setting(&settings, field, step, &library);
+            if adjusted != *settings {
+                *settings = adjusted;
+            }
+        }
+        MenuAction::Back => match *screen {
+            MenuScreen::Games => {}
+            MenuScreen::Main => next_game.set(ActiveGame(None)),
+            _ => {
+                *screen = MenuScreen::Main;
+                focus.0 = 0;
+            }
+        },
+        MenuAction::Quit => {
+            exit.write(AppExit::Success);
+        }
+    }
+}
+
+fn adjust_setting(settings: &Settings, field: SettingField, step: i32, library: &ThemeLibrary) -> Settings {
+    let mut settings = settings.clone();
+    let gameplay = &mut settings.gameplay;
+    let volume = |value: f32| {
+        // Stepping up from full volume wraps to silence so Enter alone can reach every level.
+        let next = ((value + step as f32 * VOLUME_STEP) * 10.0).round() / 10.0;
+        if next > 1.0 && step > 0 && value >= 1.0 {
+            0.0
+        } else {
+            next.clamp(0.0, 1.0)
+        }
+    };
+
+    match field {
+        SettingField::BoardSize => gameplay.board_size = cycle(&BoardSize::ALL, gameplay.board_size, step),
+        SettingField::Speed => gameplay.speed = cycle(&Speed::ALL, gameplay.speed, step),
+        SettingField::Controls => gameplay.controls = cycle(&ControlScheme::ALL, gameplay.controls, step),
+        SettingField::Ghost => gameplay.ghost = !gameplay.ghost,
+        SettingField::Theme => {
+            let names: Vec<&str> = library.themes().iter().map(|theme| theme.name.as_str()).collect();
+            settings.theme = cycle(&names, settings.theme.as_str(), step).to_string();
+        }
+        SettingField::MasterVolume => settings.audio.master = volume(settings.audio.master),
+        SettingField::SfxVolume => settings.audio.sfx = volume(settings.audio.sfx),
+        SettingField::MusicVolume => settings.audio.music = volume(settings.audio.music),
+        SettingField::Telemetry => settings.telemetry = !settings.telemetry,
+    }
+    settings.validated()
+}
+
+// Escape abandons the round; once the death animation has played, Enter returns to the menu.
+fn leave_round(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    death: Option<Res<DeathSequence>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let intent = read_intent(&keyboard, &gamepads);
+    let finished = death.is_some_and(|death| death.is_finished());
+    if intent.back || (finished && intent.activate) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daily::DailyResult;
+
+    #[test]
+    fn adjusting_settings_stays_in_range() {
+        let library = ThemeLibrary::builtin();
+        let mut settings = Settings::default();
+        settings.audio.master = 1.0;
+
+        let louder = adjust_setting(&settings, SettingField::MasterVolume, 1, &library);
+        assert_eq!(louder.audio.master, 0.0);
+        let quieter = adjust_setting(&louder, SettingField::MasterVolume, -1, &library);
+        assert_eq!(quieter.audio.master, 0.0);
+
+        let theme = adjust_setting(&settings, SettingField::Theme, -1, &library);
+        assert_eq!(theme.theme, library.themes().last().unwrap().name);
+    }
+
+    #[test]
+    fn a_finished_daily_attempt_can_be_copied() {
+        let today = Date::from_days(20_000);
+        let mut daily_log = DailyLog::default();
+        let daily_actions = |daily_log: &DailyLog| {
+            menu_actions(MenuScreen::Daily, None, &GameRegistry::default(), &RoundSave::default(), daily_log, today)
+        };
+        assert!(!daily_actions(&daily_log).contains(&MenuAction::CopyDailyResult));
+
+        daily_log.start(today);
+        assert!(!daily_actions(&daily_log).contains(&MenuAction::CopyDailyResult));
+        let result = DailyResult {
+            score: 30,
+            length: 6,
+            seconds: 75,
+            grid: vec![],
+        };
+        daily_log.finish(today, result);
+        let actions = daily_actions(&daily_log);
+        assert_eq!(actions[actions.len() - 2], MenuAction::CopyDailyResult);
+    }
+}
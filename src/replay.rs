@@ -0,0 +1,284 @@
+//! Recorded runs, for racing a ghost of the player's best. A run is the RNG
+//! state it started from and the heading the snake took on each tick, which is
+//! enough to play it back through the game's [`Arena`]. The best run for each
+//! mode is kept through [`crate::storage`].
+
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::{GameRng, RngState};
+use crate::settings::{BoardSize, GameMode};
+use crate::snake_rules::{play_tick, Arena, TickOutcome, POINTS_PER_FOOD};
+use crate::storage;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Replay<D> {
+    pub rng: RngState,
+    pub mode: GameMode,
+    /// Set for games with a board size setting.
+    pub board_size: Option<BoardSize>,
+    pub score: u32,
+    /// Ticks played, including the one the run ended on.
+    pub ticks: u32,
+    /// Every change of heading and the tick it happened on, in order.
+    pub turns: Vec<(u32, D)>,
+}
+
+impl<D: Copy + PartialEq> Replay<D> {
+    pub fn new(rng: RngState, mode: GameMode, board_size: Option<BoardSize>) -> Self {
+        Self {
+            rng,
+            mode,
+            board_size,
+            score: 0,
+            ticks: 0,
+            turns: Vec::new(),
+        }
+    }
+
+    /// Notes the heading the snake moved in on the tick being played.
+    pub fn record(&mut self, heading: D) {
+        if self.turns.last().map(|&(_, last)| last) != Some(heading) {
+            self.turns.push((self.ticks, heading));
+        }
+        self.ticks += 1;
+    }
+
+    pub fn heading_at(&self, tick: u32) -> Option<D> {
+        let index = self.turns.partition_point(|&(at, _)| at <= tick);
+        index.checked_sub(1).map(|index| self.turns[index].1)
+    }
+}
+
+// The part of a stored run that decides which rules it was played under.
+#[derive(Deserialize)]
+struct RunRules {
+    mode: GameMode,
+    board_size: Option<BoardSize>,
+    score: u32,
+}
+
+/// The active game's best run under each mode and board size. The default
+/// store is not persisted, which keeps headless apps away from the player's runs.
+#[derive(Resource, Default, Debug)]
+pub struct BestReplays {
+    key: Option<String>,
+    runs: Vec<Value>,
+}
+
+impl BestReplays {
+    pub fn load(game_id: &str) -> Self {
+        let key = format!("{game_id}-best-runs");
+        Self {
+            runs: storage::load(&key).unwrap_or_default(),
+            key: Some(key),
+        }
+    }
+
+    fn position(&self, mode: GameMode, board_size: Option<BoardSize>) -> Option<(usize, u32)> {
+        self.runs.iter().enumerate().find_map(|(index, run)| {
+            let rules = RunRules::deserialize(run).ok()?;
+            (rules.mode == mode && rules.board_size == board_size).then_some((index, rules.score))
+        })
+    }
+
+    pub fn best<D: DeserializeOwned>(&self, mode: GameMode, board_size: Option<BoardSize>) -> Option<Replay<D>> {
+        let (index, _) = self.position(mode, board_size)?;
+        Replay::deserialize(&self.runs[index]).ok()
+    }
+
+    /// Keeps `replay` if it beats the best run under the same rules; ties keep
+    /// the older run. Returns whether it was kept.
+    pub fn offer<D: Serialize>(&mut self, replay: &Replay<D>) -> bool {
+        let current = self.position(replay.mode, replay.board_size);
+        if current.is_some_and(|(_, score)| score >= replay.score) {
+            return false;
+        }
+        let Ok(run) = serde_json::to_value(replay) else {
+            return false;
+        };
+
+        match current {
+            Some((index, _)) => self.runs[index] = run,
+            None => self.runs.push(run),
+        }
+        if let Some(key) = &self.key {
+            storage::save(key, &self.runs);
+        }
+        true
+    }
+}
+
+/// A recorded run played back tick by tick alongside the live game. It keeps
+/// its own food and RNG, so it never touches the live round.
+pub struct Ghost<A: Arena> {
+    replay: Replay<A::Direction>,
+    rng: GameRng,
+    food: Option<A::Cell>,
+    /// Head first.
+    pub body: Vec<A::Cell>,
+    /// Where each segment was before the last tick.
+    pub previous: Vec<A::Cell>,
+    pub score: u32,
+    tick: u32,
+    finished: bool,
+}
+
+impl<A: Arena> Ghost<A> {
+    pub fn new(arena: &A, replay: Replay<A::Direction>) -> Self {
+        let mut rng = GameRng::from_state(replay.rng);
+        let (body, _) = arena.start();
+        let food = arena.food_cell(&mut rng, &body);
+        Self {
+            replay,
+            rng,
+            food: Some(food),
+            previous: body.clone(),
+            body,
+            score: 0,
+            tick: 0,
+            finished: false,
+        }
+    }
+
+    /// Ticks played so far.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Whether the run has ended, after which the ghost stays where it stopped.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Plays on until `tick` ticks have been played or the run ends.
+    pub fn catch_up(&mut self, arena: &A, tick: u32) {
+        while self.tick < tick && !self.finished {
+            self.step(arena);
+        }
+    }
+
+    /// Plays the run again from the start until `tick` ticks have been played,
+    /// for when the live round goes back in time.
+    pub fn rewind(&mut self, arena: &A, tick: u32) {
+        if tick < self.tick {
+            *self = Self::new(arena, self.replay.clone());
+        }
+        self.catch_up(arena, tick);
+    }
+
+    pub fn step(&mut self, arena: &A) {
+        let heading = self.replay.heading_at(self.tick);
+        let Some(heading) = heading.filter(|_| !self.finished && self.tick < self.replay.ticks) else {
+            self.finished = true;
+            return;
+        };
+        self.tick += 1;
+
+        let self_collision = self.replay.mode.self_collision();
+        let (outcome, body, _) = play_tick(arena, &self.body, self.food.as_ref(), heading, self_collision);
+        match outcome {
+            TickOutcome::Died(_) => self.finished = true,
+            TickOutcome::Ate => {
+                self.score += POINTS_PER_FOOD;
+                self.food = Some(arena.food_cell(&mut self.rng, &body));
+            }
+            TickOutcome::Moved => {}
+        }
+        self.previous = std::mem::replace(&mut self.body, body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    // A 5-wide strip with walls at both ends; food goes anywhere not taken.
+    struct Strip;
+
+    impl Arena for Strip {
+        type Cell = i32;
+        type Direction = i32;
+
+        fn start(&self) -> (Vec<i32>, i32) {
+            (vec![1, 0], 1)
+        }
+
+        fn step(&self, head: i32, direction: i32) -> Option<(i32, i32)> {
+            let next = head + direction;
+            (0..5).contains(&next).then_some((next, direction))
+        }
+
+        fn food_cell(&self, rng: &mut GameRng, body: &[i32]) -> i32 {
+            loop {
+                let cell = rng.random_range(0..5);
+                if !body.contains(&cell) {
+                    return cell;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn replays_record_turns_only() {
+        let mut replay = Replay::new(GameRng::seeded(1).state(), GameMode::Classic, None);
+        for heading in [1, 1, -1, -1, 1] {
+            replay.record(heading);
+        }
+        assert_eq!(replay.ticks, 5);
+        assert_eq!(replay.turns, [(0, 1), (2, -1), (4, 1)]);
+        assert_eq!(replay.heading_at(3), Some(-1));
+        assert_eq!(replay.heading_at(9), Some(1));
+    }
+
+    #[test]
+    fn a_ghost_replays_the_run_it_was_recorded_from() {
+        // Play a run live, recording it, until the snake hits the far wall.
+        let mut rng = GameRng::seeded(5);
+        let mut replay = Replay::new(rng.state(), GameMode::Classic, None);
+        let (mut body, _) = Strip.start();
+        let mut food = Strip.food_cell(&mut rng, &body);
+        let mut score = 0;
+        loop {
+            replay.record(1);
+            let (outcome, moved, _) = play_tick(&Strip, &body, Some(&food), 1, true);
+            if matches!(outcome, TickOutcome::Died(_)) {
+                break;
+            }
+            if outcome == TickOutcome::Ate {
+                score += POINTS_PER_FOOD;
+                food = Strip.food_cell(&mut rng, &moved);
+            }
+            body = moved;
+        }
+        replay.score = score;
+
+        let mut ghost = Ghost::new(&Strip, replay.clone());
+        ghost.catch_up(&Strip, u32::MAX);
+        assert!(ghost.is_finished());
+        assert_eq!(ghost.tick(), replay.ticks);
+        assert_eq!(ghost.body, body);
+        assert_eq!(ghost.score, score);
+    }
+
+    #[test]
+    fn only_a_better_run_replaces_the_best() {
+        let mut best = BestReplays::default();
+        let run = |score, mode| Replay::<i32> {
+            score,
+            ..Replay::new(GameRng::seeded(1).state(), mode, Some(BoardSize::Small))
+        };
+
+        assert!(best.offer(&run(20, GameMode::Classic)));
+        assert!(!best.offer(&run(20, GameMode::Classic)));
+        assert!(best.offer(&run(10, GameMode::Zen)));
+        assert!(best.offer(&run(30, GameMode::Classic)));
+
+        let classic = best.best::<i32>(GameMode::Classic, Some(BoardSize::Small)).unwrap();
+        assert_eq!(classic.score, 30);
+        assert!(best.best::<i32>(GameMode::Classic, Some(BoardSize::Large)).is_none());
+    }
+}
@@ -0,0 +1,165 @@
+//! Rounds in progress. A game saves its round when the player leaves it or the
+//! window closes, and the game's menu offers "Continue" while a save exists.
+//! Saves go through [`crate::storage`] wrapped with a format version, and
+//! older versions are upgraded by [`MIGRATIONS`] on load.
+
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::storage;
+
+type Migration = fn(&mut Value);
+
+/// `MIGRATIONS[i]` upgrades a save from version `i + 1` to `i + 2`. When a
+/// saved round changes shape, append a step here.
+const MIGRATIONS: &[Migration] = &[];
+
+/// Version written with new saves.
+pub const VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct Envelope {
+    version: u32,
+    round: Value,
+}
+
+/// The active game's saved round, if any. The default slot is not persisted,
+/// which keeps headless apps away from the player's saves.
+#[derive(Resource, Default, Debug)]
+pub struct RoundSave {
+    key: Option<String>,
+    saved: Option<Envelope>,
+    resume: bool,
+}
+
+impl RoundSave {
+    pub fn load(game_id: &str) -> Self {
+        let key = format!("{game_id}-saved-round");
+        let saved = storage::load(&key).and_then(|envelope| upgrade(envelope, MIGRATIONS));
+        Self {
+            key: Some(key),
+            saved,
+            resume: false,
+        }
+    }
+
+    pub fn has_save(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Makes the next [`RoundSave::take`] return the saved round.
+    pub fn request_resume(&mut self) {
+        self.resume = true;
+    }
+
+    /// Called as a round starts: returns the saved round if the player chose to
+    /// continue it, and discards the save either way.
+    pub fn take<T: DeserializeOwned>(&mut self) -> Option<T> {
+        let resume = std::mem::take(&mut self.resume);
+        let saved = self.saved.take()?;
+        if let Some(key) = &self.key {
+            storage::remove(key);
+        }
+        if !resume {
+            return None;
+        }
+
+        match serde_json::from_value(saved.round) {
+            Ok(round) => Some(round),
+            Err(err) => {
+                warn!("ignoring unreadable saved round: {err}");
+                None
+            }
+        }
+    }
+
+    pub fn store<T: Serialize>(&mut self, round: &T) {
+        let round = match serde_json::to_value(round) {
+            Ok(round) => round,
+            Err(err) => {
+                warn!("failed to serialize round: {err}");
+                return;
+            }
+        };
+        let envelope = Envelope {
+            version: VERSION,
+            round,
+        };
+        if let Some(key) = &self.key {
+            storage::save(key, &envelope);
+        }
+        self.saved = Some(envelope);
+    }
+
+    pub fn clear(&mut self) {
+        if self.saved.take().is_some() {
+            if let Some(key) = &self.key {
+                storage::remove(key);
+            }
+        }
+    }
+}
+
+fn upgrade(mut envelope: Envelope, migrations: &[Migration]) -> Option<Envelope> {
+    let current = migrations.len() as u32 + 1;
+    if envelope.version == 0 || envelope.version > current {
+        warn!("ignoring saved round with unknown version {}", envelope.version);
+        return None;
+    }
+
+    for migrate in &migrations[envelope.version as usize - 1..] {
+        migrate(&mut envelope.round);
+    }
+    envelope.version = current;
+    Some(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn older_saves_are_migrated_in_order() {
+        let migrations: &[Migration] = &[
+            |round| round["lives"] = 3.into(),
+            |round| {
+                if let Some(score) = round.as_object_mut().and_then(|round| round.remove("score")) {
+                    round["points"] = score;
+                }
+            },
+        ];
+        let old = Envelope {
+            version: 1,
+            round: serde_json::json!({ "score": 40 }),
+        };
+
+        let upgraded = upgrade(old, migrations).unwrap();
+        assert_eq!(upgraded.version, 3);
+        assert_eq!(upgraded.round, serde_json::json!({ "points": 40, "lives": 3 }));
+
+        let newer = Envelope {
+            version: 4,
+            round: Value::Null,
+        };
+        assert_eq!(upgrade(newer, migrations), None);
+    }
+
+    #[test]
+    fn a_save_is_only_resumed_once() {
+        let mut save = RoundSave::default();
+        save.store(&7u32);
+        assert!(save.has_save());
+
+        save.request_resume();
+        assert_eq!(save.take::<u32>(), Some(7));
+        assert!(!save.has_save());
+        assert_eq!(save.take::<u32>(), None);
+
+        // Starting a fresh round throws the save away.
+        save.store(&8u32);
+        assert_eq!(save.take::<u32>(), None);
+        assert!(!save.has_save());
+    }
+}
@@ -0,0 +1,217 @@
+//! Player settings shared by every game, persisted through [`crate::storage`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "settings";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = storage::load::<Settings>(STORAGE_KEY).map(Settings::validated);
+        app.insert_resource(settings.unwrap_or_default())
+            .add_systems(Last, save_settings);
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub audio: AudioSettings,
+    /// Name of the active [`crate::theme::Theme`].
+    pub theme: String,
+    pub gameplay: GameplaySettings,
+    /// Log gameplay to [`crate::telemetry`] files in the data directory.
+    pub telemetry: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            audio: AudioSettings::default(),
+            theme: "classic".to_string(),
+            gameplay: GameplaySettings::default(),
+            telemetry: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Clamps values edited by hand or saved by older builds into their valid ranges.
+    /// Unknown theme names are resolved by the theme plugin.
+    pub fn validated(mut self) -> Self {
+        let defaults = AudioSettings::default();
+        let clamp = |value: f32, default: f32| if value.is_finite() { value.clamp(0.0, 1.0) } else { default };
+        self.audio.master = clamp(self.audio.master, defaults.master);
+        self.audio.sfx = clamp(self.audio.sfx, defaults.sfx);
+        self.audio.music = clamp(self.audio.music, defaults.music);
+        self
+    }
+}
+
+/// Linear volumes in `0.0..=1.0`. Effective volumes are multiplied by `master`.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub sfx: f32,
+    pub music: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            sfx: 1.0,
+            music: 0.5,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn sfx_volume(&self) -> f32 {
+        self.master * self.sfx
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        storage::save(STORAGE_KEY, &*settings);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct GameplaySettings {
+    pub mode: GameMode,
+    pub speed: Speed,
+    pub board_size: BoardSize,
+    pub controls: ControlScheme,
+    /// Race a ghost of the best run, in games that have one.
+    pub ghost: bool,
+}
+
+/// Steps through `all` from `current`, wrapping at either end.
+pub fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: i32) -> T {
+    let index = all.iter().position(|&value| value == current).unwrap_or(0) as i32;
+    all[(index + step).rem_euclid(all.len() as i32) as usize]
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    /// The snake can pass through its own body.
+    Zen,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 2] = [GameMode::Classic, GameMode::Zen];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Zen => "Zen",
+        }
+    }
+
+    pub fn self_collision(self) -> bool {
+        self == GameMode::Classic
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Speed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl Speed {
+    pub const ALL: [Speed; 3] = [Speed::Slow, Speed::Normal, Speed::Fast];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Speed::Slow => "Slow",
+            Speed::Normal => "Normal",
+            Speed::Fast => "Fast",
+        }
+    }
+
+    /// Multiplier applied to a game's base tick interval.
+    pub fn interval_scale(self) -> f32 {
+        match self {
+            Speed::Slow => 1.3,
+            Speed::Normal => 1.0,
+            Speed::Fast => 0.75,
+        }
+    }
+}
+
+/// Each game maps the preset to its own dimensions.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum BoardSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl BoardSize {
+    pub const ALL: [BoardSize; 3] = [BoardSize::Small, BoardSize::Medium, BoardSize::Large];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BoardSize::Small => "Small",
+            BoardSize::Medium => "Medium",
+            BoardSize::Large => "Large",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ControlScheme {
+    #[default]
+    ArrowsAndWasd,
+    Arrows,
+    Wasd,
+}
+
+impl ControlScheme {
+    pub const ALL: [ControlScheme; 3] = [ControlScheme::ArrowsAndWasd, ControlScheme::Arrows, ControlScheme::Wasd];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ControlScheme::ArrowsAndWasd => "Arrows + WASD",
+            ControlScheme::Arrows => "Arrows",
+            ControlScheme::Wasd => "WASD",
+        }
+    }
+
+    /// Keys bound to up, down, left and right.
+    pub fn bindings(self) -> [&'static [KeyCode]; 4] {
+        match self {
+            ControlScheme::ArrowsAndWasd => [
+                &[KeyCode::ArrowUp, KeyCode::KeyW],
+                &[KeyCode::ArrowDown, KeyCode::KeyS],
+                &[KeyCode::ArrowLeft, KeyCode::KeyA],
+                &[KeyCode::ArrowRight, KeyCode::KeyD],
+            ],
+            ControlScheme::Arrows => [
+                &[KeyCode::ArrowUp],
+                &[KeyCode::ArrowDown],
+                &[KeyCode::ArrowLeft],
+                &[KeyCode::ArrowRight],
+            ],
+            ControlScheme::Wasd => [&[KeyCode::KeyW], &[KeyCode::KeyS], &[KeyCode::KeyA], &[KeyCode::KeyD]],
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Gameplay messages shared by the snake games. The tick only moves the snake
+//! and reports what happened; scoring, sound, effects, stats and the HUD each
+//! subscribe to these, so a new feature can listen in without touching the tick.
+
+use bevy::prelude::*;
+
+use crate::random::RngState;
+use crate::settings::{BoardSize, GameMode, Speed};
+use crate::snake_rules::{DeathCause, POINTS_PER_FOOD};
+
+/// A round began, or a saved one was continued, and the rules it plays under.
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameStarted {
+    pub resumed: bool,
+    /// Where the round's RNG stands as play begins.
+    pub rng: RngState,
+    pub mode: GameMode,
+    pub speed: Speed,
+    /// Set for games with a board size setting.
+    pub board_size: Option<BoardSize>,
+}
+
+/// The round is over and `score` is final. Comes after [`SnakeDied`].
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameEnded {
+    pub score: u32,
+}
+
+/// The snake moved one cell.
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SnakeMoved {
+    /// The player changed heading for this move.
+    pub turned: bool,
+    /// Segments after the move, counting any it grew by.
+    pub length: u32,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum FoodKind {
+    #[default]
+    Plain,
+}
+
+impl FoodKind {
+    pub fn points(self) -> u32 {
+        match self {
+            FoodKind::Plain => POINTS_PER_FOOD,
+        }
+    }
+}
+
+/// The head moved onto food at `pos`, a cell in the game's own coordinates.
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FoodEaten<P: Send + Sync + 'static> {
+    pub pos: P,
+    pub kind: FoodKind,
+}
+
+/// The tick would have killed the snake, so nothing moved; `pos` is the head's cell.
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SnakeDied<P: Send + Sync + 'static> {
+    pub cause: DeathCause,
+    pub pos: P,
+}
+
+/// Snake-3d: the head crossed an edge of the cube onto another face.
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FaceChanged<F: Send + Sync + 'static> {
+    pub from: F,
+    pub to: F,
+}
+
+/// Registers the messages every snake game writes, for a game whose cells are `P`.
+pub fn add_snake_messages<P: Send + Sync + 'static>(app: &mut App) {
+    app.add_message::<GameStarted>()
+        .add_message::<GameEnded>()
+        .add_message::<SnakeMoved>()
+        .add_message::<FoodEaten<P>>()
+        .add_message::<SnakeDied<P>>();
+}
@@ -0,0 +1,175 @@
+//! Tick rules shared by the snake games, so snake-2d and snake-3d agree on when
+//! a move kills, eats or just moves. Cells are whatever position type the game
+//! uses; the rules only compare them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::random::GameRng;
+
+pub const POINTS_PER_FOOD: u32 = 10;
+
+/// Where a snake game is played: how the head moves and where food turns up.
+/// The live game and [`crate::replay::Ghost`] both play through it, so a
+/// replay unfolds exactly like the run it was recorded from.
+pub trait Arena {
+    type Cell: Copy + PartialEq;
+    type Direction: Copy + PartialEq;
+
+    /// The snake a round starts with, head first, and its heading.
+    fn start(&self) -> (Vec<Self::Cell>, Self::Direction);
+
+    /// The cell ahead of `head` going `direction` and the heading once there,
+    /// or `None` past the edge of the board.
+    fn step(&self, head: Self::Cell, direction: Self::Direction) -> Option<(Self::Cell, Self::Direction)>;
+
+    /// A random cell clear of `body` for the next food.
+    fn food_cell(&self, rng: &mut GameRng, body: &[Self::Cell]) -> Self::Cell;
+}
+
+/// A snake's heading, which it can't turn straight back from.
+pub trait Heading: Copy + PartialEq {
+    fn reversed(self) -> Self;
+}
+
+/// Takes a direction key pressed between ticks. The first turn becomes `next`
+/// and one more waits in `queued` for the tick after, so two quick presses make
+/// two turns rather than the second replacing the first. Turning straight back
+/// is ignored.
+pub fn press_turn<D: Heading>(current: D, next: &mut D, queued: &mut Option<D>, pressed: D) {
+    if *next == current {
+        if pressed != current.reversed() {
+            *next = pressed;
+        }
+    } else if pressed != *next && pressed != next.reversed() {
+        *queued = Some(pressed);
+    }
+}
+
+/// The heading for the tick after one that left the snake going `current`:
+/// the queued turn if there is one, otherwise straight on.
+pub fn take_queued_turn<D: Heading>(current: D, queued: &mut Option<D>) -> D {
+    queued.take().filter(|&turn| turn != current.reversed()).unwrap_or(current)
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeathCause {
+    Wall,
+    Body,
+}
+
+/// What one tick does to the snake.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TickOutcome {
+    /// The head would leave the board or run into the body. Nothing moves.
+    Died(DeathCause),
+    /// The head moves onto the food and the snake grows by one on the same tick.
+    Ate,
+    Moved,
+}
+
+/// Decides a tick from the body (head first), the cell the head moves into
+/// (`None` when that is off the board) and the food's cell. The tail's cell
+/// is safe to move into unless the snake is growing, as the tail leaves it on
+/// the same tick.
+pub fn resolve_tick<P: PartialEq>(
+    body: &[P],
+    next_head: Option<&P>,
+    food: Option<&P>,
+    self_collision: bool,
+) -> TickOutcome {
+    let Some(next_head) = next_head else {
+        return TickOutcome::Died(DeathCause::Wall);
+    };
+
+    let eats = food == Some(next_head);
+    let blocking = if eats {
+        body
+    } else {
+        &body[..body.len().saturating_sub(1)]
+    };
+    if self_collision && blocking.contains(next_head) {
+        return TickOutcome::Died(DeathCause::Body);
+    }
+
+    if eats {
+        TickOutcome::Ate
+    } else {
+        TickOutcome::Moved
+    }
+}
+
+/// The body after the head moves to `next_head`. Growing keeps the old tail
+/// cell as a new last segment.
+pub fn advance<P: Copy>(body: &[P], next_head: P, grow: bool) -> Vec<P> {
+    let kept = if grow { body.len() } else { body.len().saturating_sub(1) };
+    std::iter::once(next_head).chain(body[..kept].iter().copied()).collect()
+}
+
+/// Plays one tick in `arena` going `direction`: the outcome, then the body and
+/// heading afterwards, which are unchanged if the snake died.
+pub fn play_tick<A: Arena>(
+    arena: &A,
+    body: &[A::Cell],
+    food: Option<&A::Cell>,
+    direction: A::Direction,
+    self_collision: bool,
+) -> (TickOutcome, Vec<A::Cell>, A::Direction) {
+    let ahead = body.first().and_then(|&head| arena.step(head, direction));
+    let outcome = resolve_tick(body, ahead.as_ref().map(|(cell, _)| cell), food, self_collision);
+    match ahead {
+        Some((next_head, heading)) if !matches!(outcome, TickOutcome::Died(_)) => {
+            (outcome, advance(body, next_head, outcome == TickOutcome::Ate), heading)
+        }
+        _ => (outcome, body.to_vec(), direction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A snake of four heading left along the bottom row: head (2, 0), tail (5, 0).
+    const BODY: [(i32, i32); 4] = [(2, 0), (3, 0), (4, 0), (5, 0)];
+
+    #[test]
+    fn leaving_the_board_or_hitting_the_body_kills() {
+        assert_eq!(resolve_tick(&BODY, None, None, true), TickOutcome::Died(DeathCause::Wall));
+        assert_eq!(resolve_tick(&BODY, Some(&(3, 0)), None, true), TickOutcome::Died(DeathCause::Body));
+        assert_eq!(resolve_tick(&BODY, Some(&(3, 0)), None, false), TickOutcome::Moved);
+    }
+
+    #[test]
+    fn the_tail_cell_is_free_unless_growing() {
+        assert_eq!(resolve_tick(&BODY, Some(&(5, 0)), None, true), TickOutcome::Moved);
+        assert_eq!(resolve_tick(&BODY, Some(&(5, 0)), Some(&(5, 0)), true), TickOutcome::Died(DeathCause::Body));
+    }
+
+    impl Heading for (i32, i32) {
+        fn reversed(self) -> Self {
+            (-self.0, -self.1)
+        }
+    }
+
+    #[test]
+    fn a_second_quick_turn_waits_for_the_next_tick() {
+        let (left, up, right) = ((-1, 0), (0, 1), (1, 0));
+        let (mut next, mut queued) = (left, None);
+
+        press_turn(left, &mut next, &mut queued, right);
+        assert_eq!((next, queued), (left, None));
+        press_turn(left, &mut next, &mut queued, up);
+        press_turn(left, &mut next, &mut queued, right);
+        assert_eq!((next, queued), (up, Some(right)));
+
+        // The tick turns up, and the queued turn right is next.
+        assert_eq!(take_queued_turn(up, &mut queued), right);
+        assert_eq!(take_queued_turn(right, &mut queued), right);
+    }
+
+    #[test]
+    fn eating_grows_on_the_same_tick() {
+        assert_eq!(resolve_tick(&BODY, Some(&(1, 0)), Some(&(1, 0)), true), TickOutcome::Ate);
+        assert_eq!(advance(&BODY, (1, 0), true), [(1, 0), (2, 0), (3, 0), (4, 0), (5, 0)]);
+        assert_eq!(advance(&BODY, (1, 0), false), [(1, 0), (2, 0), (3, 0), (4, 0)]);
+    }
+}
@@ -0,0 +1,461 @@
+//! Lifetime statistics and achievements for the snake games. [`StatsPlugin`]
+//! adds up the games' [`crate::snake_events`], unlocks achievements from the
+//! catalog in `assets/achievements.ron`, shows a toast for each unlock and keeps
+//! everything through [`crate::storage`]. Rounds that have been rewound with the
+//! step debugger are not counted.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dev_console::Cheats;
+use crate::snake_events::{FaceChanged, FoodEaten, GameStarted, SnakeDied, SnakeMoved};
+use crate::snake_rules::DeathCause;
+use crate::storage;
+use crate::theme::Theme;
+use crate::time_travel::TimeTravel;
+
+const CATALOG: &str = include_str!("../assets/achievements.ron");
+const TOAST_SECONDS: f32 = 3.0;
+const TOAST_HEIGHT: f32 = 48.0;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<GameStarted>()
+            .add_message::<SnakeMoved>()
+            .add_message::<AchievementUnlocked>()
+            .init_resource::<Stats>()
+            .init_resource::<AchievementCatalog>()
+            .add_systems(Update, (show_toasts, expire_toasts));
+    }
+}
+
+/// Adds the messages a game with cells `P` reports. The game counts them with
+/// [`count_snake`].
+pub fn track_snake<P: Send + Sync + 'static>(app: &mut App) {
+    app.add_message::<FoodEaten<P>>().add_message::<SnakeDied<P>>();
+}
+
+/// Adds the message a game with cube faces `F` reports as the snake crosses
+/// an edge. The game counts them with [`count_faces`].
+pub fn track_faces<F: Send + Sync + 'static>(app: &mut App) {
+    app.add_message::<FaceChanged<F>>();
+}
+
+/// Written for each achievement as it unlocks.
+#[derive(Message, Clone, PartialEq, Debug)]
+pub struct AchievementUnlocked(pub Achievement);
+
+// What the stats count, from whichever message reported it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Tally {
+    RoundStarted { resumed: bool },
+    Moved { turned: bool, length: u32 },
+    Ate,
+    EnteredFace(usize),
+    Died(DeathCause),
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default)]
+pub struct Totals {
+    pub games_played: u32,
+    pub food_eaten: u32,
+    pub longest_snake: u32,
+    pub ticks: u64,
+    pub wall_deaths: u32,
+    pub body_deaths: u32,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Goal {
+    FoodInRound(u32),
+    /// Food eaten in a row with no turn in between.
+    FoodWithoutTurning(u32),
+    Length(u32),
+    /// Different cube faces visited in one round.
+    FacesInRound(u32),
+    TotalFood(u32),
+    GamesPlayed(u32),
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub goal: Goal,
+    /// The games it can be earned in, by id; every game that keeps stats when empty.
+    #[serde(default)]
+    pub games: Vec<String>,
+}
+
+#[derive(Resource, Debug)]
+pub struct AchievementCatalog(Vec<Achievement>);
+
+impl AchievementCatalog {
+    pub fn builtin() -> Self {
+        Self(ron::from_str(CATALOG).expect("built-in achievements should parse"))
+    }
+
+    pub fn achievements(&self) -> &[Achievement] {
+        &self.0
+    }
+}
+
+impl Default for AchievementCatalog {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+// Progress within the current round.
+#[derive(Default, Debug)]
+struct RoundProgress {
+    food: u32,
+    food_since_turn: u32,
+    length: u32,
+    /// One bit per cube face.
+    faces: u8,
+}
+
+impl Goal {
+    fn is_met(self, totals: &Totals, round: &RoundProgress) -> bool {
+        match self {
+            Goal::FoodInRound(count) => round.food >= count,
+            Goal::FoodWithoutTurning(count) => round.food_since_turn >= count,
+            Goal::Length(length) => round.length >= length,
+            Goal::FacesInRound(count) => round.faces.count_ones() >= count,
+            Goal::TotalFood(count) => totals.food_eaten >= count,
+            Goal::GamesPlayed(count) => totals.games_played >= count,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+struct Record {
+    totals: Totals,
+    unlocked: Vec<String>,
+}
+
+/// The active game's lifetime totals and unlocked achievements. The default is
+/// not persisted, which keeps headless apps away from the player's record.
+#[derive(Resource, Default, Debug)]
+pub struct Stats {
+    game: Option<String>,
+    key: Option<String>,
+    record: Record,
+    round: RoundProgress,
+}
+
+impl Stats {
+    pub fn load(game_id: &str) -> Self {
+        let key = format!("{game_id}-stats");
+        Self {
+            game: Some(game_id.to_string()),
+            record: storage::load(&key).unwrap_or_default(),
+            key: Some(key),
+            round: RoundProgress::default(),
+        }
+    }
+
+    pub fn totals(&self) -> &Totals {
+        &self.record.totals
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.record.unlocked.iter().any(|unlocked| unlocked == id)
+    }
+
+    /// The achievements in `catalog` this game can earn.
+    pub fn achievements<'a>(&self, catalog: &'a AchievementCatalog) -> impl Iterator<Item = &'a Achievement> {
+        let game = self.game.clone();
+        catalog.achievements().iter().filter(move |achievement| {
+            achievement.games.is_empty() || game.as_ref().is_none_or(|game| achievement.games.contains(game))
+        })
+    }
+
+    /// Counts one tally and returns the achievements it unlocked. The record is
+    /// saved as rounds start and end and whenever something unlocks, rather
+    /// than on every move.
+    fn apply<'a>(&mut self, tally: Tally, catalog: &'a AchievementCatalog) -> Vec<&'a Achievement> {
+        let totals = &mut self.record.totals;
+        let round = &mut self.round;
+        match tally {
+            Tally::RoundStarted { resumed } => {
+                *round = RoundProgress::default();
+                if !resumed {
+                    totals.games_played += 1;
+                }
+            }
+            Tally::Moved { turned, length } => {
+                totals.ticks += 1;
+                totals.longest_snake = totals.longest_snake.max(length);
+                round.length = length;
+                if turned {
+                    round.food_since_turn = 0;
+                }
+            }
+            Tally::Ate => {
+                totals.food_eaten += 1;
+                round.food += 1;
+                round.food_since_turn += 1;
+            }
+            Tally::EnteredFace(face) => round.faces |= 1 << face,
+            Tally::Died(DeathCause::Wall) => totals.wall_deaths += 1,
+            Tally::Died(DeathCause::Body) => totals.body_deaths += 1,
+        }
+
+        let unlocked: Vec<&Achievement> = self
+            .achievements(catalog)
+            .filter(|achievement| !self.is_unlocked(&achievement.id))
+            .filter(|achievement| achievement.goal.is_met(&self.record.totals, &self.round))
+            .collect();
+        self.record.unlocked.extend(unlocked.iter().map(|achievement| achievement.id.clone()));
+
+        let round_edge = matches!(tally, Tally::RoundStarted { .. } | Tally::Died(_));
+        if round_edge || !unlocked.is_empty() {
+            self.save();
+        }
+        unlocked
+    }
+
+    fn save(&self) {
+        if let Some(key) = &self.key {
+            storage::save(key, &self.record);
+        }
+    }
+}
+
+#[derive(Component)]
+struct Toast(Timer);
+
+fn count<I: IntoIterator<Item = Tally>>(
+    tallies: I,
+    travel: Option<Res<TimeTravel>>,
+    cheats: Option<Res<Cheats>>,
+    stats: &mut Stats,
+    catalog: &AchievementCatalog,
+    unlocked: &mut MessageWriter<AchievementUnlocked>,
+) {
+    let off_record = travel.is_some_and(|travel| travel.rewound()) || cheats.is_some_and(|cheats| cheats.cheated);
+    for tally in tallies.into_iter().filter(|_| !off_record) {
+        for achievement in stats.apply(tally, catalog) {
+            unlocked.write(AchievementUnlocked(achievement.clone()));
+        }
+    }
+}
+
+/// Counts the rounds, moves, food and deaths of a snake game with cells `P`.
+/// Belongs at the end of the game's `FixedUpdate` chain, so each run sees what
+/// one step did: the move, then the food eaten or the death where it led.
+pub fn count_snake<P: Send + Sync + 'static>(
+    mut started: MessageReader<GameStarted>,
+    mut moved: MessageReader<SnakeMoved>,
+    mut eaten: MessageReader<FoodEaten<P>>,
+    mut died: MessageReader<SnakeDied<P>>,
+    travel: Option<Res<TimeTravel>>,
+    cheats: Option<Res<Cheats>>,
+    catalog: Res<AchievementCatalog>,
+    mut stats: ResMut<Stats>,
+    mut unlocked: MessageWriter<AchievementUnlocked>,
+) {
+    let started = started.read().map(|started| Tally::RoundStarted { resumed: started.resumed });
+    let moved = moved.read().map(|moved| Tally::Moved {
+        turned: moved.turned,
+        length: moved.length,
+    });
+    let eaten = eaten.read().map(|_| Tally::Ate);
+    let died = died.read().map(|died| Tally::Died(died.cause));
+    count(started.chain(moved).chain(eaten).chain(died), travel, cheats, &mut stats, &catalog, &mut unlocked);
+}
+
+/// Counts the cube faces a game with faces `F` visits; `F` converts to the
+/// face's index, `0..6`. Belongs right after [`count_snake`], so a crossing
+/// counts after the round it happened in has started. Both sides of each edge
+/// count, so the face a round starts on is counted with the first crossing.
+pub fn count_faces<F: Copy + Into<usize> + Send + Sync + 'static>(
+    mut changed: MessageReader<FaceChanged<F>>,
+    travel: Option<Res<TimeTravel>>,
+    cheats: Option<Res<Cheats>>,
+    catalog: Res<AchievementCatalog>,
+    mut stats: ResMut<Stats>,
+    mut unlocked: MessageWriter<AchievementUnlocked>,
+) {
+    let faces = changed.read().flat_map(|changed| [changed.from.into(), changed.to.into()]);
+    count(faces.map(Tally::EnteredFace), travel, cheats, &mut stats, &catalog, &mut unlocked);
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    mut unlocked: MessageReader<AchievementUnlocked>,
+    theme: Res<Theme>,
+    toasts: Query<(), With<Toast>>,
+) {
+    // Toasts stack upwards from the bottom-right corner.
+    for (shown, AchievementUnlocked(achievement)) in unlocked.read().enumerate() {
+        let shown = toasts.iter().count() + shown;
+        commands
+            .spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    bottom: Val::Px(20.0 + shown as f32 * TOAST_HEIGHT),
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BackgroundColor(theme.background.with_alpha(0.9)),
+                BorderColor::all(theme.head),
+                GlobalZIndex(20),
+                Toast(Timer::from_seconds(TOAST_SECONDS, TimerMode::Once)),
+            ))
+            .with_children(|toast| {
+                toast.spawn((
+                    Text::new(format!("Achievement: {}", achievement.name)),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(theme.text),
+                ));
+            });
+    }
+}
+
+fn expire_toasts(mut commands: Commands, time: Res<Time>, mut toasts: Query<(Entity, &mut Toast)>) {
+    for (entity, mut toast) in toasts.iter_mut() {
+        if toast.0.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_achievements_parse_with_unique_ids() {
+        let catalog = AchievementCatalog::builtin();
+        let mut ids: Vec<&str> = catalog.achievements().iter().map(|achievement| achievement.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), catalog.achievements().len());
+    }
+
+    #[test]
+    fn rounds_add_up_into_lifetime_totals() {
+        let catalog = AchievementCatalog::builtin();
+        let mut stats = Stats::default();
+        for tally in [
+            Tally::RoundStarted { resumed: false },
+            Tally::Moved { turned: false, length: 4 },
+            Tally::Ate,
+            Tally::Moved { turned: true, length: 4 },
+            Tally::Died(DeathCause::Wall),
+            Tally::RoundStarted { resumed: true },
+            Tally::Died(DeathCause::Body),
+        ] {
+            stats.apply(tally, &catalog);
+        }
+
+        let totals = stats.totals();
+        assert_eq!((totals.games_played, totals.food_eaten, totals.ticks), (1, 1, 2));
+        assert_eq!((totals.longest_snake, totals.wall_deaths, totals.body_deaths), (4, 1, 1));
+        assert!(stats.is_unlocked("first-bite"));
+    }
+
+    #[test]
+    fn achievements_unlock_once_from_round_events() {
+        let catalog = AchievementCatalog::builtin();
+        let mut stats = Stats::default();
+        stats.apply(Tally::RoundStarted { resumed: false }, &catalog);
+
+        // A turn resets the streak of food eaten going straight.
+        for _ in 0..9 {
+            stats.apply(Tally::Ate, &catalog);
+        }
+        stats.apply(Tally::Moved { turned: true, length: 12 }, &catalog);
+        stats.apply(Tally::Ate, &catalog);
+        assert!(!stats.is_unlocked("straight-and-narrow"));
+
+        let mut unlocked = Vec::new();
+        for face in [0, 1, 2, 2, 3, 4, 5] {
+            unlocked.extend(stats.apply(Tally::EnteredFace(face), &catalog).iter().map(|a| a.id.clone()));
+        }
+        assert_eq!(unlocked, ["globetrotter"]);
+        assert!(stats.apply(Tally::EnteredFace(0), &catalog).is_empty());
+    }
+
+    #[test]
+    fn only_the_games_an_achievement_names_can_earn_it() {
+        let catalog = AchievementCatalog::builtin();
+        let in_game = |game: &str| Stats {
+            game: Some(game.to_string()),
+            ..default()
+        };
+        let listed = |stats: &Stats| stats.achievements(&catalog).any(|achievement| achievement.id == "globetrotter");
+        assert!(listed(&in_game("snake-3d")));
+        assert!(!listed(&in_game("snake-2d")));
+
+        let mut stats = in_game("snake-2d");
+        for face in 0..6 {
+            stats.apply(Tally::EnteredFace(face), &catalog);
+        }
+        assert!(!stats.is_unlocked("globetrotter"));
+    }
+
+    // What each fixed step of a pretend game does: whether it turns, and whether it eats.
+    #[derive(Resource)]
+    struct Steps(Vec<(bool, bool)>);
+
+    fn play_step(
+        mut steps: ResMut<Steps>,
+        mut moved: MessageWriter<SnakeMoved>,
+        mut eaten: MessageWriter<FoodEaten<IVec2>>,
+    ) {
+        let (turned, ate) = steps.0.remove(0);
+        moved.write(SnakeMoved { turned, length: 3 });
+        if ate {
+            eaten.write(FoodEaten { pos: IVec2::ZERO, kind: Default::default() });
+        }
+    }
+
+    #[test]
+    fn steps_in_one_frame_count_in_order() {
+        use bevy::time::TimeUpdateStrategy;
+        use std::time::Duration;
+
+        let streak = Achievement {
+            id: "streak".to_string(),
+            name: "Streak".to_string(),
+            description: String::new(),
+            goal: Goal::FoodWithoutTurning(2),
+            games: Vec::new(),
+        };
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatsPlugin))
+            .init_resource::<Theme>()
+            .insert_resource(AchievementCatalog(vec![streak]))
+            .insert_resource(Steps(vec![(false, true), (true, false), (false, true), (false, true)]))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO))
+            .add_systems(FixedUpdate, (play_step, count_snake::<IVec2>).chain());
+        track_snake::<IVec2>(&mut app);
+        app.update();
+
+        // Eat, turn, eat: the turn comes between the two, so there is no streak.
+        let step = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(step * 3));
+        app.update();
+        let stats = app.world().resource::<Stats>();
+        assert_eq!((stats.totals().ticks, stats.totals().food_eaten), (3, 2));
+        assert!(!stats.is_unlocked("streak"));
+
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(step));
+        app.update();
+        assert!(app.world().resource::<Stats>().is_unlocked("streak"));
+    }
+}
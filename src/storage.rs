@@ -0,0 +1,108 @@
+//! Small key/value persistence for settings and progress. Values are stored
+//! as JSON: one file per key on native builds, `localStorage` entries on the web.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const KEY_PREFIX: &str = "tiny-games";
+
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let text = backend::read(key)?;
+    match serde_json::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            bevy::log::warn!("ignoring unreadable {key} data: {err}");
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(text) => backend::write(key, &text),
+        Err(err) => bevy::log::warn!("failed to serialize {key}: {err}"),
+    }
+}
+
+pub fn remove(key: &str) {
+    backend::remove(key)
+}
+
+/// The directory native builds keep their files in.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn data_dir() -> std::path::PathBuf {
+    backend::data_dir()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::path::PathBuf;
+
+    // `TINY_GAMES_DATA_DIR` overrides the platform data directory.
+    pub fn data_dir() -> PathBuf {
+        if let Some(dir) = std::env::var_os("TINY_GAMES_DATA_DIR") {
+            return PathBuf::from(dir);
+        }
+
+        let base = if cfg!(windows) {
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+        } else {
+            std::env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        };
+        base.unwrap_or_default().join(super::KEY_PREFIX)
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        std::fs::read_to_string(data_dir().join(format!("{key}.json"))).ok()
+    }
+
+    pub fn write(key: &str, text: &str) {
+        let dir = data_dir();
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(dir.join(format!("{key}.json")), text));
+        if let Err(err) = result {
+            bevy::log::warn!("failed to save {key}: {err}");
+        }
+    }
+
+    pub fn remove(key: &str) {
+        match std::fs::remove_file(data_dir().join(format!("{key}.json"))) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                bevy::log::warn!("failed to remove {key}: {err}");
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        storage()?.get_item(&format!("{}/{key}", super::KEY_PREFIX)).ok()?
+    }
+
+    pub fn write(key: &str, text: &str) {
+        let saved = storage().is_some_and(|storage| {
+            storage
+                .set_item(&format!("{}/{key}", super::KEY_PREFIX), text)
+                .is_ok()
+        });
+        if !saved {
+            bevy::log::warn!("failed to save {key} to localStorage");
+        }
+    }
+
+    pub fn remove(key: &str) {
+        if let Some(storage) = storage() {
+            let _ = storage.remove_item(&format!("{}/{key}", super::KEY_PREFIX));
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Opt-in gameplay telemetry, for tuning difficulty from real play. With the
+//! setting on, each snake game appends what its [`crate::snake_events`] report
+//! to a newline-delimited JSON log of its own in the data directory; nothing is
+//! sent anywhere. A log is rotated once it reaches [`MAX_FILE_BYTES`] and only
+//! [`ROTATED_FILES`] old ones are kept. The `snake-stats` binary reads them back
+//! through [`Report`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dev_console::Cheats;
+use crate::random::RngState;
+use crate::settings::{BoardSize, GameMode, Settings, Speed};
+use crate::snake_events::{FoodEaten, GameEnded, GameStarted, SnakeDied, SnakeMoved};
+use crate::snake_rules::DeathCause;
+use crate::time_travel::TimeTravel;
+
+pub const MAX_FILE_BYTES: u64 = 512 * 1024;
+pub const ROTATED_FILES: usize = 4;
+const EXTENSION: &str = "ndjson";
+// Scores are grouped into buckets this wide in a report.
+const SCORE_BUCKET: u32 = 50;
+
+/// One line of a log.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Record {
+    /// A round began; the lines up to the next session belong to it.
+    Session {
+        game: String,
+        version: String,
+        /// Seconds since 1970-01-01 UTC.
+        time: u64,
+        rng: RngState,
+        mode: GameMode,
+        speed: Speed,
+        board_size: Option<BoardSize>,
+        resumed: bool,
+    },
+    /// `tick` counts the moves made since the session began.
+    Tick { tick: u32, length: u32 },
+    Turn { tick: u32 },
+    /// Cells are the game's own, such as `{"x":3,"y":4}` or `{"face":"Top","x":3,"y":4}`.
+    Eat { tick: u32, pos: Value },
+    Death { tick: u32, cause: DeathCause, pos: Value },
+    End { tick: u32, score: u32 },
+}
+
+/// Where the active game's telemetry goes. The default log writes nothing,
+/// which keeps headless apps off the player's disk.
+#[derive(Resource, Default, Debug)]
+pub struct TelemetryLog {
+    game: String,
+    dir: Option<PathBuf>,
+}
+
+impl TelemetryLog {
+    /// The game's log in the data directory. Web builds have nowhere to keep one.
+    pub fn open(game_id: &str) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Self::in_dir(telemetry_dir(), game_id);
+        #[cfg(target_arch = "wasm32")]
+        Self {
+            game: game_id.to_string(),
+            dir: None,
+        }
+    }
+
+    pub fn in_dir(dir: impl Into<PathBuf>, game_id: &str) -> Self {
+        Self {
+            game: game_id.to_string(),
+            dir: Some(dir.into()),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    // The live log is `index` 0; rotated ones count up from 1, oldest last.
+    fn path(&self, dir: &Path, index: usize) -> PathBuf {
+        match index {
+            0 => dir.join(format!("{}.{EXTENSION}", self.game)),
+            _ => dir.join(format!("{}.{index}.{EXTENSION}", self.game)),
+        }
+    }
+
+    pub fn write(&mut self, record: &Record) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let result = serde_json::to_string(record)
+            .map_err(io::Error::from)
+            .and_then(|line| self.append(dir, &line));
+        if let Err(err) = result {
+            warn!("failed to write telemetry: {err}");
+        }
+    }
+
+    fn append(&self, dir: &Path, line: &str) -> io::Result<()> {
+        let live = self.path(dir, 0);
+        let size = fs::metadata(&live).map_or(0, |metadata| metadata.len());
+        if size > 0 && size + line.len() as u64 + 1 > MAX_FILE_BYTES {
+            self.rotate(dir)?;
+        }
+        fs::create_dir_all(dir)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(live)?;
+        writeln!(file, "{line}")
+    }
+
+    // Shifts every log one place older; the oldest falls off the end.
+    fn rotate(&self, dir: &Path) -> io::Result<()> {
+        match fs::remove_file(self.path(dir, ROTATED_FILES)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        for index in (0..ROTATED_FILES).rev() {
+            let from = self.path(dir, index);
+            if from.exists() {
+                fs::rename(from, self.path(dir, index + 1))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where the logs are kept: `telemetry` in the data directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn telemetry_dir() -> PathBuf {
+    crate::storage::data_dir().join("telemetry")
+}
+
+fn unix_seconds() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+/// Logs the messages of a snake game with cells `P`. Belongs at the end of the
+/// game's `FixedUpdate` chain, so each run sees what one step did in order.
+pub fn record_telemetry<P: Serialize + Send + Sync + 'static>(
+    settings: Res<Settings>,
+    mut log: ResMut<TelemetryLog>,
+    mut tick: Local<u32>,
+    mut game_started: MessageReader<GameStarted>,
+    mut snake_moved: MessageReader<SnakeMoved>,
+    mut food_eaten: MessageReader<FoodEaten<P>>,
+    mut snake_died: MessageReader<SnakeDied<P>>,
+    mut game_ended: MessageReader<GameEnded>,
+    travel: Option<Res<TimeTravel>>,
+    cheats: Option<Res<Cheats>>,
+) {
+    // Messages from while telemetry was off are dropped, not saved up, as are
+    // those of a round rewound with the step debugger or cheated at the console.
+    let off_record = travel.is_some_and(|travel| travel.rewound()) || cheats.is_some_and(|cheats| cheats.cheated);
+    if !settings.telemetry || !log.is_open() || off_record {
+        game_started.clear();
+        snake_moved.clear();
+        food_eaten.clear();
+        snake_died.clear();
+        game_ended.clear();
+        return;
+    }
+
+    let mut records = Vec::new();
+    for started in game_started.read() {
+        *tick = 0;
+        records.push(Record::Session {
+            game: log.game.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            time: unix_seconds(),
+            rng: started.rng,
+            mode: started.mode,
+            speed: started.speed,
+            board_size: started.board_size,
+            resumed: started.resumed,
+        });
+    }
+    for moved in snake_moved.read() {
+        *tick += 1;
+        if moved.turned {
+            records.push(Record::Turn { tick: *tick });
+        }
+        records.push(Record::Tick {
+            tick: *tick,
+            length: moved.length,
+        });
+    }
+    let cell = |pos: &P| serde_json::to_value(pos).unwrap_or_default();
+    for eaten in food_eaten.read() {
+        records.push(Record::Eat {
+            tick: *tick,
+            pos: cell(&eaten.pos),
+        });
+    }
+    for died in snake_died.read() {
+        records.push(Record::Death {
+            tick: *tick,
+            cause: died.cause,
+            pos: cell(&died.pos),
+        });
+    }
+    for ended in game_ended.read() {
+        records.push(Record::End {
+            tick: *tick,
+            score: ended.score,
+        });
+    }
+
+    for record in &records {
+        log.write(record);
+    }
+}
+
+/// What the logs of one game add up to.
+#[derive(Default, Debug)]
+pub struct GameReport {
+    /// Rounds begun, not counting continued ones.
+    pub rounds: u32,
+    /// Final scores of the rounds that ended.
+    pub scores: Vec<u32>,
+    pub wall_deaths: u32,
+    pub body_deaths: u32,
+    /// Deaths by board size and face, for games that have them, then cell.
+    /// Cells only line up on boards of the same size.
+    pub deaths: BTreeMap<(Option<BoardSize>, Option<String>), BTreeMap<(i64, i64), u32>>,
+    // The board of the session the records being added belong to.
+    board_size: Option<BoardSize>,
+}
+
+impl GameReport {
+    pub fn add(&mut self, record: &Record) {
+        match record {
+            Record::Session { resumed, board_size, .. } => {
+                self.board_size = *board_size;
+                if !resumed {
+                    self.rounds += 1;
+                }
+            }
+            Record::End { score, .. } => self.scores.push(*score),
+            Record::Death { cause, pos, .. } => {
+                match cause {
+                    DeathCause::Wall => self.wall_deaths += 1,
+                    DeathCause::Body => self.body_deaths += 1,
+                }
+                let (Some(x), Some(y)) = (pos["x"].as_i64(), pos["y"].as_i64()) else {
+                    return;
+                };
+                let face = pos["face"].as_str().map(str::to_string);
+                *self.deaths.entry((self.board_size, face)).or_default().entry((x, y)).or_default() += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Rounds ending in each [`SCORE_BUCKET`]-wide band of scores, lowest first,
+    /// as the band's lowest score and a count.
+    pub fn score_histogram(&self) -> Vec<(u32, u32)> {
+        let Some(&best) = self.scores.iter().max() else {
+            return Vec::new();
+        };
+        (0..=best / SCORE_BUCKET)
+            .map(|bucket| {
+                let count = self.scores.iter().filter(|&&score| score / SCORE_BUCKET == bucket).count();
+                (bucket * SCORE_BUCKET, count as u32)
+            })
+            .collect()
+    }
+
+    /// Deaths per cell as rows of text, top row first, covering every cell
+    /// anyone died in: `.` for none, then `1` to `9` scaled to the worst cell.
+    pub fn death_heatmap(&self, board_size: Option<BoardSize>, face: Option<&str>) -> Vec<String> {
+        let Some(cells) = self.deaths.get(&(board_size, face.map(str::to_string))) else {
+            return Vec::new();
+        };
+        let worst = cells.values().copied().max().unwrap_or(1);
+        let width = cells.keys().map(|&(x, _)| x).max().unwrap_or(0) + 1;
+        let height = cells.keys().map(|&(_, y)| y).max().unwrap_or(0) + 1;
+        (0..height)
+            .rev()
+            .map(|y| {
+                (0..width)
+                    .map(|x| match cells.get(&(x, y)) {
+                        Some(&count) => char::from_digit((count * 9).div_ceil(worst), 10).unwrap_or('9'),
+                        None => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for GameReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} rounds, {} finished", self.rounds, self.scores.len())?;
+        if let Some(&best) = self.scores.iter().max() {
+            let mean = self.scores.iter().sum::<u32>() / self.scores.len() as u32;
+            writeln!(f, "Scores (best {best}, mean {mean}):")?;
+            let most = self.score_histogram().iter().map(|&(_, count)| count).max().unwrap_or(1);
+            for (from, count) in self.score_histogram() {
+                let bar = "#".repeat((count * 40).div_ceil(most) as usize);
+                writeln!(f, "  {from:>5}-{:<5} {bar} {count}", from + SCORE_BUCKET - 1)?;
+            }
+        }
+        writeln!(f, "Deaths: {} wall, {} body", self.wall_deaths, self.body_deaths)?;
+        for (board_size, face) in self.deaths.keys() {
+            let board = board_size.map(|board_size| format!(" on the {} board", board_size.label()));
+            match face {
+                Some(face) => writeln!(f, "Where the snake died on the {face} face{}:", board.unwrap_or_default())?,
+                None => writeln!(f, "Where the snake died{}:", board.unwrap_or_default())?,
+            }
+            for row in self.death_heatmap(*board_size, face.as_deref()) {
+                writeln!(f, "  {row}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Every game's logs in a directory, added up.
+#[derive(Default, Debug)]
+pub struct Report {
+    pub games: BTreeMap<String, GameReport>,
+    /// Lines that were not records, such as one cut short by a crash.
+    pub skipped: u32,
+}
+
+impl Report {
+    pub fn from_dir(dir: &Path) -> io::Result<Self> {
+        let mut report = Self::default();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == EXTENSION) {
+                report.add_log(&fs::read_to_string(&path)?);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Adds the lines of one log. Each session names the game the lines after it belong to.
+    pub fn add_log(&mut self, text: &str) {
+        let mut game = None;
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let Ok(record) = serde_json::from_str::<Record>(line) else {
+                self.skipped += 1;
+                continue;
+            };
+            if let Record::Session { game: name, .. } = &record {
+                game = Some(name.clone());
+            }
+            // A rotated log can begin partway through a session.
+            let Some(game) = &game else {
+                self.skipped += 1;
+                continue;
+            };
+            self.games.entry(game.clone()).or_default().add(&record);
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (game, report) in &self.games {
+            writeln!(f, "== {game} ==")?;
+            writeln!(f, "{report}")?;
+        }
+        if self.skipped > 0 {
+            writeln!(f, "({} lines skipped)", self.skipped)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::GameRng;
+    use serde_json::json;
+
+    fn session() -> Record {
+        session_on(BoardSize::Small)
+    }
+
+    fn session_on(board_size: BoardSize) -> Record {
+        Record::Session {
+            game: "snake-2d".to_string(),
+            version: "0.1.0".to_string(),
+            time: 0,
+            rng: GameRng::seeded(1).state(),
+            mode: GameMode::Classic,
+            speed: Speed::Normal,
+            board_size: Some(board_size),
+            resumed: false,
+        }
+    }
+
+    #[test]
+    fn logs_rotate_and_stay_capped() {
+        let dir = std::env::temp_dir().join(format!("tiny-games-telemetry-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut log = TelemetryLog::in_dir(&dir, "snake-2d");
+        let record = Record::Tick { tick: 1, length: 3 };
+        let line = serde_json::to_string(&record).unwrap().len() as u64 + 1;
+        for _ in 0..(MAX_FILE_BYTES / line) * (ROTATED_FILES as u64 + 3) {
+            log.write(&record);
+        }
+
+        let mut sizes: Vec<u64> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().metadata().unwrap().len()).collect();
+        sizes.sort();
+        assert_eq!(sizes.len(), ROTATED_FILES + 1);
+        assert!(sizes.iter().all(|&size| size <= MAX_FILE_BYTES));
+        assert!(log.path(&dir, ROTATED_FILES).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reports_add_up_scores_and_deaths() {
+        let mut log = String::new();
+        let death = |x: i32, y: i32, cause| Record::Death {
+            tick: 5,
+            cause,
+            pos: json!({ "x": x, "y": y }),
+        };
+        let end = |score| Record::End { tick: 5, score };
+        for record in [
+            // Lines from before the log's first session have no game.
+            end(10),
+            session(),
+            death(0, 0, DeathCause::Wall),
+            end(20),
+            session(),
+            death(2, 1, DeathCause::Body),
+            end(60),
+            session(),
+            death(2, 1, DeathCause::Wall),
+            end(70),
+            // The same cell on another board is another place.
+            session_on(BoardSize::Large),
+            death(0, 0, DeathCause::Wall),
+        ] {
+            log += &serde_json::to_string(&record).unwrap();
+            log += "\n";
+        }
+        log += "{\"event\":\"tick\",\"ti";
+
+        let mut report = Report::default();
+        report.add_log(&log);
+        assert_eq!(report.skipped, 2);
+
+        let game = &report.games["snake-2d"];
+        assert_eq!((game.rounds, game.wall_deaths, game.body_deaths), (4, 3, 1));
+        assert_eq!(game.score_histogram(), [(0, 1), (50, 2)]);
+        assert_eq!(game.death_heatmap(Some(BoardSize::Small), None), ["..9", "5.."]);
+        assert_eq!(game.death_heatmap(Some(BoardSize::Large), None), ["9"]);
+        assert!(game.death_heatmap(None, None).is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+//! Named colour palettes shared by every game. Palettes are defined in
+//! `assets/themes/*.ron` and embedded at compile time so the web build needs no
+//! extra requests.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer};
+
+use crate::settings::{Settings, SettingsPlugin};
+
+const THEME_FILES: [&str; 4] = [
+    include_str!("../assets/themes/classic.ron"),
+    include_str!("../assets/themes/high-contrast.ron"),
+    include_str!("../assets/themes/colorblind.ron"),
+    include_str!("../assets/themes/nokia.ron"),
+];
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SettingsPlugin>() {
+            app.add_plugins(SettingsPlugin);
+        }
+
+        let library = ThemeLibrary::builtin();
+        let theme = library.get_or_default(&app.world().resource::<Settings>().theme).clone();
+        app.insert_resource(library)
+            .insert_resource(theme)
+            .add_systems(Update, (cycle_theme, sync_theme).chain());
+    }
+}
+
+/// Shape used to draw food, so it can be told apart from the snake without colour.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FoodShape {
+    Square,
+    Diamond,
+}
+
+impl FoodShape {
+    /// Rotation applied to a square sprite or UI node to draw this shape.
+    pub fn angle(self) -> f32 {
+        match self {
+            FoodShape::Square => 0.0,
+            FoodShape::Diamond => std::f32::consts::FRAC_PI_4,
+        }
+    }
+
+    /// Size relative to a square, keeping a diamond's corners inside its cell.
+    pub fn scale(self) -> f32 {
+        match self {
+            FoodShape::Square => 1.0,
+            FoodShape::Diamond => 0.75,
+        }
+    }
+}
+
+/// The active palette. Games read it when spawning and re-apply it when it changes.
+#[derive(Resource, Deserialize, Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub name: String,
+    #[serde(deserialize_with = "hex_color")]
+    pub background: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub grid: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub head: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub body: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub food: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub text: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub flash: Color,
+    pub food_shape: FoodShape,
+    /// Colours for games with several kinds of piece; kinds without one use `body`.
+    #[serde(default, deserialize_with = "hex_colors")]
+    pub pieces: Vec<Color>,
+}
+
+impl Theme {
+    pub fn piece(&self, index: usize) -> Color {
+        self.pieces.get(index).copied().unwrap_or(self.body)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemeLibrary::builtin().0.swap_remove(0)
+    }
+}
+
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    Srgba::hex(&hex)
+        .map(Color::from)
+        .map_err(|err| serde::de::Error::custom(format!("invalid colour {hex:?}: {err}")))
+}
+
+fn hex_colors<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Color>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|hex| {
+            Srgba::hex(hex)
+                .map(Color::from)
+                .map_err(|err| serde::de::Error::custom(format!("invalid colour {hex:?}: {err}")))
+        })
+        .collect()
+}
+
+#[derive(Resource, Debug)]
+pub struct ThemeLibrary(Vec<Theme>);
+
+impl ThemeLibrary {
+    pub fn builtin() -> Self {
+        Self(
+            THEME_FILES
+                .iter()
+                .map(|text| ron::from_str(text).expect("built-in theme should parse"))
+                .collect(),
+        )
+    }
+
+    pub fn themes(&self) -> &[Theme] {
+        &self.0
+    }
+
+    /// The named theme, falling back to the first one for unknown names.
+    pub fn get_or_default(&self, name: &str) -> &Theme {
+        self.0.iter().find(|theme| theme.name == name).unwrap_or(&self.0[0])
+    }
+
+    /// The theme after `name`, wrapping around.
+    pub fn next(&self, name: &str) -> &Theme {
+        let index = self.0.iter().position(|theme| theme.name == name).map_or(0, |i| i + 1);
+        &self.0[index % self.0.len()]
+    }
+}
+
+// T cycles through the palettes; the choice is persisted with the other settings.
+fn cycle_theme(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    library: Res<ThemeLibrary>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyT) {
+        settings.theme = library.next(&settings.theme).name.clone();
+    }
+}
+
+fn sync_theme(settings: Res<Settings>, library: Res<ThemeLibrary>, mut theme: ResMut<Theme>) {
+    if settings.is_changed() {
+        let selected = library.get_or_default(&settings.theme);
+        if *theme != *selected {
+            *theme = selected.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes_parse_with_unique_names() {
+        let library = ThemeLibrary::builtin();
+        let mut names: Vec<&str> = library.themes().iter().map(|theme| theme.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), THEME_FILES.len());
+        assert_eq!(library.next("nokia").name, "classic");
+        assert_eq!(library.get_or_default("missing").name, "classic");
+    }
+
+    #[test]
+    fn accessible_themes_do_not_rely_on_colour_alone() {
+        let library = ThemeLibrary::builtin();
+        for name in ["high-contrast", "colorblind", "nokia"] {
+            assert_eq!(library.get_or_default(name).food_shape, FoodShape::Diamond, "{name}");
+        }
+    }
+}
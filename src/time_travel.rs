@@ -0,0 +1,393 @@
+//! Step debugger for the snake games. The active game keeps the state after
+//! each of the last [`HISTORY_TICKS`] ticks in a [`History`]. F4 opens a timeline
+//! panel; with it open, F5 pauses and resumes, F6 and F7 step back and forward
+//! a tick, and dragging the slider scrubs. The game puts the round back as it
+//! was at whichever tick is picked, and play resumes from there, forgetting the
+//! ticks after it. The simulation also pauses when the snake dies, so the
+//! ticks before a death can be stepped through. A round that has been rewound
+//! is off the record: its results, stats and telemetry are not kept.
+//!
+//! A game reads its round through a [`RoundSnapshot`], puts a kept one back
+//! with [`Rewind`], and registers [`record_history`], [`travel_through_history`]
+//! and [`save_round`] for the two.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::ecs::system::{StaticSystemParam, SystemParam};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use serde::Serialize;
+
+use crate::dev_console::Cheats;
+use crate::menu::GameState;
+use crate::save_state::RoundSave;
+use crate::snake_events::GameEnded;
+use crate::theme::Theme;
+
+pub const HISTORY_TICKS: usize = 300;
+const FONT_SIZE: f32 = 16.0;
+const SLIDER_WIDTH: f32 = 300.0;
+const SLIDER_HEIGHT: f32 = 12.0;
+
+pub struct TimeTravelPlugin;
+
+impl Plugin for TimeTravelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<GameEnded>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<TimeTravel>()
+            .add_systems(
+                Update,
+                (travel_controls, drag_slider, pause_on_death)
+                    .in_set(TimeTravelSystems)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Playing), start_round)
+            .add_systems(OnExit(GameState::Playing), stop_travelling)
+            .add_systems(PostUpdate, draw_timeline);
+    }
+}
+
+/// Where the controls ask for a tick; the game goes to it after these run.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TimeTravelSystems;
+
+/// The last [`HISTORY_TICKS`] states of a round, oldest first, and which of them
+/// the round is showing.
+#[derive(Resource, Debug)]
+pub struct History<S> {
+    states: VecDeque<S>,
+    shown: usize,
+}
+
+impl<S> Default for History<S> {
+    fn default() -> Self {
+        Self {
+            states: VecDeque::new(),
+            shown: 0,
+        }
+    }
+}
+
+impl<S> History<S> {
+    /// Adds the state after a tick. Ticks after the one shown were undone by
+    /// going back, so they are forgotten first.
+    pub fn push(&mut self, state: S) {
+        self.states.truncate(self.shown + 1);
+        self.states.push_back(state);
+        if self.states.len() > HISTORY_TICKS {
+            self.states.pop_front();
+        }
+        self.shown = self.states.len() - 1;
+    }
+
+    /// Shows the state at `index`, if there is one.
+    pub fn seek(&mut self, index: usize) -> Option<&S> {
+        let state = self.states.get(index)?;
+        self.shown = index;
+        Some(state)
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn shown(&self) -> usize {
+        self.shown
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct TimeTravel {
+    pub open: bool,
+    /// Ticks in the active game's history and the one it shows, as it last reported.
+    kept: usize,
+    shown: usize,
+    seek: Option<usize>,
+    rewound: bool,
+}
+
+impl TimeTravel {
+    /// Reports the active game's history for the timeline.
+    pub fn follow<S>(&mut self, history: &History<S>) {
+        self.kept = history.len();
+        self.shown = history.shown();
+    }
+
+    /// The tick the active game should go back or forward to, once. The round
+    /// is rewound from then on.
+    pub fn take_seek(&mut self) -> Option<usize> {
+        let seek = self.seek.take();
+        self.rewound |= seek.is_some();
+        seek
+    }
+
+    /// Whether the round has gone back in time, and so may end more than once.
+    pub fn rewound(&self) -> bool {
+        self.rewound
+    }
+
+    fn is_live(&self) -> bool {
+        self.shown + 1 >= self.kept
+    }
+}
+
+/// A game's round as it is now, read through a [`SystemParam`] that
+/// implements this, and saved as an `R`.
+pub trait RoundSnapshot<R> {
+    fn saved(&self) -> R;
+
+    /// Whether the round has ended, leaving nothing to keep.
+    fn is_over(&self) -> bool;
+}
+
+/// A kept round that can be put back into the world.
+pub trait Rewind: Clone + Send + Sync + 'static {
+    fn restore(&self, world: &mut World);
+}
+
+/// Keeps the round read through `S` after each tick, and once it is set up.
+/// The tick a snake dies on moves nothing, so it isn't kept.
+pub fn record_history<S: SystemParam + 'static, R: Send + Sync + 'static>(
+    round: StaticSystemParam<S>,
+    mut history: ResMut<History<R>>,
+    mut travel: ResMut<TimeTravel>,
+) where
+    for<'w, 's> S::Item<'w, 's>: RoundSnapshot<R>,
+{
+    if !round.is_over() {
+        history.push(round.saved());
+    }
+    travel.follow(&history);
+}
+
+/// Puts the round back as it was after the tick picked on the timeline.
+pub fn travel_through_history<R: Rewind>(world: &mut World) {
+    let Some(index) = world.resource_mut::<TimeTravel>().take_seek() else {
+        return;
+    };
+    let Some(round) = world.resource_mut::<History<R>>().seek(index).cloned() else {
+        return;
+    };
+    round.restore(world);
+    world.resource_scope(|world, mut travel: Mut<TimeTravel>| travel.follow(world.resource::<History<R>>()));
+}
+
+/// Keeps the round read through `S` for "Continue". A finished round has
+/// nothing to continue, a rewound one may already have been scored, and a
+/// cheated one is off the record.
+pub fn save_round<S: SystemParam + 'static, R: Serialize>(
+    mut round_save: ResMut<RoundSave>,
+    travel: Res<TimeTravel>,
+    cheats: Res<Cheats>,
+    round: StaticSystemParam<S>,
+) where
+    for<'w, 's> S::Item<'w, 's>: RoundSnapshot<R>,
+{
+    if round.is_over() || travel.rewound() || cheats.cheated {
+        return;
+    }
+
+    round_save.store(&round.saved());
+}
+
+// Finishes the move under way, so the snake is drawn in the cells it is in.
+fn pause(time: &mut Time<Virtual>, fixed_time: &mut Time<Fixed>) {
+    time.pause();
+    let left = fixed_time.timestep().saturating_sub(fixed_time.overstep());
+    fixed_time.accumulate_overstep(left.saturating_sub(Duration::from_micros(1)));
+}
+
+fn travel_controls(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut travel: ResMut<TimeTravel>,
+    mut time: ResMut<Time<Virtual>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    if keyboard.just_pressed(KeyCode::F4) {
+        travel.open = !travel.open;
+        if !travel.open {
+            time.unpause();
+        }
+    }
+    if !travel.open {
+        return;
+    }
+
+    let back = keyboard.just_pressed(KeyCode::F6);
+    let forward = keyboard.just_pressed(KeyCode::F7);
+    if keyboard.just_pressed(KeyCode::F5) && time.is_paused() {
+        time.unpause();
+    } else if keyboard.just_pressed(KeyCode::F5) || ((back || forward) && !time.is_paused()) {
+        pause(&mut time, &mut fixed_time);
+    }
+
+    if back {
+        travel.seek = Some(travel.shown.saturating_sub(1));
+    } else if forward && travel.is_live() {
+        // Lets exactly one more move's worth of time into the paused fixed loop.
+        let step = fixed_time.timestep();
+        fixed_time.accumulate_overstep(step);
+    } else if forward {
+        travel.seek = Some(travel.shown + 1);
+    }
+}
+
+fn pause_on_death(
+    mut game_ended: MessageReader<GameEnded>,
+    travel: Res<TimeTravel>,
+    mut time: ResMut<Time<Virtual>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    if game_ended.read().count() > 0 && travel.open && !time.is_paused() {
+        pause(&mut time, &mut fixed_time);
+    }
+}
+
+fn start_round(mut travel: ResMut<TimeTravel>) {
+    travel.rewound = false;
+}
+
+fn stop_travelling(mut travel: ResMut<TimeTravel>, mut time: ResMut<Time<Virtual>>) {
+    travel.seek = None;
+    time.unpause();
+}
+
+#[derive(Component)]
+struct TimelinePanel;
+
+#[derive(Component)]
+struct Timeline;
+
+#[derive(Component)]
+struct TimelineText;
+
+#[derive(Component)]
+struct TimelineFill;
+
+fn drag_slider(
+    slider: Query<(&Interaction, &RelativeCursorPosition), With<Timeline>>,
+    mut travel: ResMut<TimeTravel>,
+    mut time: ResMut<Time<Virtual>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    let Ok((Interaction::Pressed, cursor)) = slider.single() else {
+        return;
+    };
+    let Some(position) = cursor.normalized.filter(|_| travel.kept > 0) else {
+        return;
+    };
+    if !time.is_paused() {
+        pause(&mut time, &mut fixed_time);
+    }
+    let index = ((position.x + 0.5).clamp(0.0, 1.0) * (travel.kept - 1) as f32).round() as usize;
+    if index != travel.shown {
+        travel.seek = Some(index);
+    }
+}
+
+fn draw_timeline(
+    mut commands: Commands,
+    travel: Res<TimeTravel>,
+    time: Res<Time<Virtual>>,
+    theme: Res<Theme>,
+    panels: Query<Entity, With<TimelinePanel>>,
+    mut text: Query<&mut Text, With<TimelineText>>,
+    mut fill: Query<&mut Node, With<TimelineFill>>,
+) {
+    if !travel.open {
+        for entity in panels.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let state = if time.is_paused() { "Paused" } else { "Running" };
+    let shown = format!(
+        "{state}  tick {} of {}\nF5 pause  F6 back  F7 forward  F4 close",
+        travel.shown + 1,
+        travel.kept
+    );
+    let filled = Val::Percent(if travel.kept > 1 { travel.shown as f32 / (travel.kept - 1) as f32 * 100.0 } else { 100.0 });
+    if let (Ok(mut text), Ok(mut fill)) = (text.single_mut(), fill.single_mut()) {
+        **text = shown;
+        fill.width = filled;
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(50.0),
+                bottom: Val::Px(10.0),
+                margin: UiRect::left(Val::Px(-SLIDER_WIDTH / 2.0)),
+                padding: UiRect::all(Val::Px(6.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(theme.background.with_alpha(0.8)),
+            GlobalZIndex(30),
+            TimelinePanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(shown),
+                TextFont {
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(theme.text),
+                TimelineText,
+            ));
+            panel
+                .spawn((
+                    Node {
+                        width: Val::Px(SLIDER_WIDTH),
+                        height: Val::Px(SLIDER_HEIGHT),
+                        margin: UiRect::top(Val::Px(6.0)),
+                        ..default()
+                    },
+                    BackgroundColor(theme.grid),
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    Timeline,
+                ))
+                .with_child((
+                    Node {
+                        width: filled,
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(theme.head),
+                    TimelineFill,
+                ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_keeps_the_latest_ticks_and_forgets_undone_ones() {
+        let mut history = History::default();
+        for tick in 0..HISTORY_TICKS + 5 {
+            history.push(tick);
+        }
+        assert_eq!((history.len(), history.shown()), (HISTORY_TICKS, HISTORY_TICKS - 1));
+        assert_eq!(history.seek(0), Some(&5));
+        assert_eq!(history.seek(HISTORY_TICKS), None);
+
+        // Going back and playing on replaces what came after.
+        assert_eq!(history.seek(9), Some(&14));
+        history.push(100);
+        assert_eq!((history.len(), history.shown()), (11, 10));
+        assert_eq!(history.seek(10), Some(&100));
+    }
+}
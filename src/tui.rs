@@ -0,0 +1,178 @@
+//! Draws a snake-2d [`BoardSnapshot`] as ANSI text for the `snake-tui` binary.
+//! Boards are drawn with two columns per cell when the terminal is big enough,
+//! then with half-block characters packing two rows per line, and otherwise
+//! replaced by a note asking for a bigger terminal.
+
+use bevy::prelude::*;
+use std::fmt::Write;
+
+use crate::games::snake_2d::BoardSnapshot;
+use crate::theme::Theme;
+
+const HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const CLEAR_BELOW: &str = "\x1b[J";
+const RESET: &str = "\x1b[0m";
+// Raw mode turns off output processing, so lines need an explicit carriage return.
+const NEWLINE: &str = "\r\n";
+// The HUD line above the board and the hint line below it.
+const TEXT_LINES: u16 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Layout {
+    Full,
+    Compact,
+    TooSmall,
+}
+
+fn layout(width: i32, height: i32, columns: u16, rows: u16) -> Layout {
+    let fits = |cell_columns: i32, board_rows: i32| {
+        cell_columns * width + 2 <= columns as i32 && board_rows + 2 + TEXT_LINES as i32 <= rows as i32
+    };
+    if fits(2, height) {
+        Layout::Full
+    } else if fits(1, (height + 1) / 2) {
+        Layout::Compact
+    } else {
+        Layout::TooSmall
+    }
+}
+
+fn foreground(color: Color) -> String {
+    let [r, g, b, _] = color.to_srgba().to_u8_array();
+    format!("\x1b[38;2;{r};{g};{b}m")
+}
+
+fn background(color: Color) -> String {
+    let [r, g, b, _] = color.to_srgba().to_u8_array();
+    format!("\x1b[48;2;{r};{g};{b}m")
+}
+
+fn cell_color(snapshot: &BoardSnapshot, theme: &Theme, cell: IVec2) -> Option<Color> {
+    match snapshot.snake.iter().position(|&segment| segment == cell) {
+        Some(0) => Some(theme.head),
+        Some(_) => Some(theme.body),
+        None if snapshot.food == Some(cell) => Some(theme.food),
+        None => None,
+    }
+}
+
+/// One full frame for a terminal of `columns` by `rows` characters.
+pub fn render(snapshot: &BoardSnapshot, theme: &Theme, best: u32, columns: u16, rows: u16) -> String {
+    let mut frame = String::from(HOME);
+    let text = foreground(theme.text);
+    let board_layout = layout(snapshot.width, snapshot.height, columns, rows);
+
+    if board_layout == Layout::TooSmall {
+        let _ = write!(
+            frame,
+            "{text}Terminal too small: need {}x{}{RESET}{CLEAR_LINE}{NEWLINE}{CLEAR_BELOW}",
+            snapshot.width + 2,
+            (snapshot.height + 1) / 2 + 2 + TEXT_LINES as i32
+        );
+        return frame;
+    }
+
+    let _ = write!(
+        frame,
+        "{text}Score: {}  Level: {}  Best: {}{RESET}{CLEAR_LINE}{NEWLINE}",
+        snapshot.score,
+        snapshot.level,
+        best.max(snapshot.score)
+    );
+
+    let cell_columns = if board_layout == Layout::Full { 2 } else { 1 };
+    let border = foreground(theme.grid);
+    let edge = "─".repeat((snapshot.width * cell_columns) as usize);
+    let _ = write!(frame, "{border}┌{edge}┐{RESET}{CLEAR_LINE}{NEWLINE}");
+
+    let board = background(theme.background);
+    let mut y = snapshot.height - 1;
+    while y >= 0 {
+        let _ = write!(frame, "{border}│{board}");
+        for x in 0..snapshot.width {
+            let top = cell_color(snapshot, theme, IVec2::new(x, y));
+            if board_layout == Layout::Full {
+                match top {
+                    Some(color) => {
+                        let _ = write!(frame, "{}██", foreground(color));
+                    }
+                    None => frame.push_str("  "),
+                }
+            } else {
+                // Upper half in the foreground colour, lower half in the background.
+                let bottom = (y > 0).then(|| cell_color(snapshot, theme, IVec2::new(x, y - 1))).flatten();
+                let _ = write!(
+                    frame,
+                    "{}{}▀",
+                    foreground(top.unwrap_or(theme.background)),
+                    background(bottom.unwrap_or(theme.background))
+                );
+            }
+        }
+        let _ = write!(frame, "{RESET}{border}│{RESET}{CLEAR_LINE}{NEWLINE}");
+        y -= if board_layout == Layout::Full { 1 } else { 2 };
+    }
+
+    let _ = write!(frame, "{border}└{edge}┘{RESET}{CLEAR_LINE}{NEWLINE}");
+    let hint = if snapshot.game_over {
+        "Game over! Enter to play again, q to quit"
+    } else {
+        "Arrows or WASD to move, T for theme, q to quit"
+    };
+    let _ = write!(frame, "{text}{hint}{RESET}{CLEAR_LINE}{CLEAR_BELOW}");
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> BoardSnapshot {
+        BoardSnapshot {
+            width: 4,
+            height: 3,
+            snake: vec![IVec2::new(1, 2), IVec2::new(0, 2)],
+            food: Some(IVec2::new(3, 0)),
+            score: 20,
+            level: 1,
+            game_over: false,
+        }
+    }
+
+    // The frame with colour codes removed.
+    fn plain(frame: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = frame.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn picks_a_layout_that_fits() {
+        let theme = Theme::default();
+        let full = plain(&render(&snapshot(), &theme, 50, 80, 24));
+        let lines: Vec<&str> = full.split(NEWLINE).collect();
+        assert_eq!(lines[0], "Score: 20  Level: 1  Best: 50");
+        assert_eq!(lines[2], "│████    │");
+        assert_eq!(lines[4], "│      ██│");
+
+        let compact = plain(&render(&snapshot(), &theme, 0, 6, 6));
+        let lines: Vec<&str> = compact.split(NEWLINE).collect();
+        assert_eq!(lines[1], "┌────┐");
+        assert_eq!(lines.len(), 6);
+
+        let too_small = plain(&render(&snapshot(), &theme, 0, 5, 6));
+        assert!(too_small.starts_with("Terminal too small: need 6x6"));
+    }
+}
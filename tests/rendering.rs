@@ -0,0 +1,277 @@
+//! Golden-image tests for the snake games. Each scene runs a game with a fixed
+//! seed, fixed frame time and scripted key presses, renders the last frame
+//! offscreen and compares it with a PNG in `tests/golden/`.
+//!
+//! Rendering goes through wgpu's OpenGL backend, which Mesa's llvmpipe provides
+//! on machines without a GPU. Without an OpenGL adapter the tests fail, unless
+//! `SKIP_RENDERING_TESTS=1` is set to skip them on purpose. Run with
+//! `UPDATE_GOLDEN=1` to accept the current output, and check the
+//! `*-actual.png` / `*-diff.png` files in the target directory when one fails.
+
+use bevy::camera::RenderTarget;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::settings::{Backends, RenderCreation, WgpuLimits, WgpuSettings};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy::render::RenderPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tiny_games::games::snake_2d::Snake2dPlugin;
+use tiny_games::games::snake_3d::Snake3dPlugin;
+use tiny_games::launcher::{ActiveGame, MiniGame};
+use tiny_games::menu::GameState;
+use tiny_games::random::GameRng;
+
+// The launcher's window size, so the HUD lays out as it does in the game.
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+const FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+const SEED: u64 = 39;
+// Per-channel difference a pixel may have before it counts as changed, and how
+// many changed pixels a frame may have. Covers rounding differences between Mesa
+// versions while still catching a food sprite in the wrong cell (256 pixels).
+const CHANNEL_TOLERANCE: u8 = 8;
+const MAX_CHANGED_PIXELS: usize = 100;
+// Screenshots are read back a few frames after they are requested.
+const MAX_CAPTURE_FRAMES: u32 = 10;
+
+// One app renders at a time; the software rasterizer already uses every core.
+static RENDER_LOCK: Mutex<()> = Mutex::new(());
+
+/// A round to play: how many frames to run, and which keys to tap on which frame.
+struct Script {
+    frames: u32,
+    taps: &'static [(u32, KeyCode)],
+}
+
+#[derive(Resource)]
+struct Target(Handle<Image>);
+
+fn software_renderer_available() -> bool {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::GL,
+        ..Default::default()
+    });
+    bevy::tasks::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).is_ok()
+}
+
+fn offscreen_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+                ..default()
+            })
+            .set(RenderPlugin {
+                render_creation: RenderCreation::Automatic(WgpuSettings {
+                    backends: Some(Backends::GL),
+                    // Keeps SSAO from loading: its compute shaders don't translate to GLSL,
+                    // and the snake games don't use it.
+                    constrained_limits: Some(WgpuLimits {
+                        max_storage_textures_per_shader_stage: 4,
+                        ..default()
+                    }),
+                    ..default()
+                }),
+                // Otherwise meshes pop in whenever their pipeline happens to finish compiling.
+                synchronous_pipeline_compilation: true,
+                ..default()
+            })
+            .disable::<WinitPlugin>()
+            .disable::<bevy::log::LogPlugin>()
+            .disable::<bevy::audio::AudioPlugin>(),
+    )
+    .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+    .insert_resource(GameRng::seeded(SEED));
+
+    let target = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(Image::new_target_texture(WIDTH, HEIGHT, TextureFormat::Rgba8UnormSrgb, None));
+    app.insert_resource(Target(target));
+
+    // Point every camera the game spawns at the image, and hang the HUD off it
+    // since there is no window for the UI to pick a camera by. Cameras stay off
+    // while the script plays, as the software rasterizer only needs to draw the
+    // frame that is captured; multisampling is left out for the same reason.
+    app.add_observer(
+        |add: On<Add, Camera>, target: Res<Target>, mut cameras: Query<&mut Camera>, mut commands: Commands| {
+            if let Ok(mut camera) = cameras.get_mut(add.entity) {
+                camera.is_active = false;
+            }
+            commands
+                .entity(add.entity)
+                .insert((RenderTarget::Image(target.0.clone().into()), IsDefaultUiCamera, Msaa::Off));
+        },
+    );
+    app
+}
+
+fn tap(app: &mut App, key_code: KeyCode, state: ButtonState) {
+    app.world_mut().write_message(KeyboardInput {
+        key_code,
+        logical_key: Key::Unidentified(bevy::input::keyboard::NativeKey::Unidentified),
+        state,
+        text: None,
+        repeat: false,
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+// Plays `script` in a fresh app with `game` added and returns the final frame.
+fn render<G: MiniGame>(game: G, script: &Script) -> RgbaImage {
+    let mut app = offscreen_app();
+    app.add_plugins(game);
+    while app.plugins_state() == bevy::app::PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    app.update();
+    app.world_mut().resource_mut::<NextState<ActiveGame>>().set(G::active());
+    app.update();
+    app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Playing);
+
+    for frame in 0..script.frames {
+        for &(_, key) in script.taps.iter().filter(|(at, _)| *at == frame) {
+            tap(&mut app, key, ButtonState::Pressed);
+        }
+        for &(_, key) in script.taps.iter().filter(|(at, _)| at + 1 == frame) {
+            tap(&mut app, key, ButtonState::Released);
+        }
+        app.update();
+    }
+
+    // Freeze the scene while the screenshot makes its way back from the render world.
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+    for mut camera in app.world_mut().query::<&mut Camera>().iter_mut(app.world_mut()) {
+        camera.is_active = true;
+    }
+    let captured = Arc::new(Mutex::new(None));
+    let slot = captured.clone();
+    let target = app.world().resource::<Target>().0.clone();
+    app.world_mut()
+        .spawn(Screenshot::image(target))
+        .observe(move |screenshot: On<ScreenshotCaptured>| {
+            *slot.lock().unwrap() = Some(screenshot.image.clone());
+        });
+
+    for _ in 0..MAX_CAPTURE_FRAMES {
+        app.update();
+        if let Some(image) = captured.lock().unwrap().take() {
+            return image
+                .try_into_dynamic()
+                .expect("render target is an 8-bit RGBA image")
+                .to_rgba8();
+        }
+    }
+    panic!("screenshot was not captured within {MAX_CAPTURE_FRAMES} frames");
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{name}.png"))
+}
+
+/// Changed pixels in red over a faded copy of the golden image.
+fn diff_image(golden: &RgbaImage, actual: &RgbaImage) -> (RgbaImage, usize) {
+    let mut changed = 0;
+    let diff = RgbaImage::from_fn(golden.width(), golden.height(), |x, y| {
+        let (a, b) = (golden.get_pixel(x, y), actual.get_pixel(x, y));
+        if a.0.iter().zip(b.0).any(|(&a, b)| a.abs_diff(b) > CHANNEL_TOLERANCE) {
+            changed += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let luma = (a[0] as u32 + a[1] as u32 + a[2] as u32) / 3;
+            let faded = (luma / 3 + 170) as u8;
+            Rgba([faded, faded, faded, 255])
+        }
+    });
+    (diff, changed)
+}
+
+fn assert_matches_golden(name: &str, actual: &RgbaImage) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        actual.save(&path).unwrap();
+        return;
+    }
+
+    let golden = image::open(&path)
+        .unwrap_or_else(|err| panic!("cannot read {}: {err}; run with UPDATE_GOLDEN=1 to create it", path.display()))
+        .to_rgba8();
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let actual_path = out_dir.join(format!("{name}-actual.png"));
+
+    if golden.dimensions() != actual.dimensions() {
+        actual.save(&actual_path).unwrap();
+        panic!(
+            "{name}: rendered {:?} but the golden image is {:?}; see {}",
+            actual.dimensions(),
+            golden.dimensions(),
+            actual_path.display()
+        );
+    }
+
+    let (diff, changed) = diff_image(&golden, actual);
+    if changed > MAX_CHANGED_PIXELS {
+        let diff_path = out_dir.join(format!("{name}-diff.png"));
+        actual.save(&actual_path).unwrap();
+        diff.save(&diff_path).unwrap();
+        panic!(
+            "{name}: {changed} pixels differ from {}; see {} and {}",
+            path.display(),
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+fn check_scene<G: MiniGame>(name: &str, game: G, script: &Script) {
+    let _lock = RENDER_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if std::env::var_os("SKIP_RENDERING_TESTS").is_some() {
+        eprintln!("skipping {name}: SKIP_RENDERING_TESTS is set");
+        return;
+    }
+    assert!(
+        software_renderer_available(),
+        "{name}: no OpenGL adapter; install Mesa for software rendering, or set SKIP_RENDERING_TESTS=1"
+    );
+    assert_matches_golden(name, &render(game, script));
+}
+
+#[test]
+fn snake_2d_after_two_turns() {
+    check_scene(
+        "snake-2d",
+        Snake2dPlugin,
+        &Script {
+            frames: 80,
+            taps: &[(30, KeyCode::ArrowUp), (55, KeyCode::ArrowLeft)],
+        },
+    );
+}
+
+#[test]
+fn snake_3d_after_a_turn() {
+    check_scene(
+        "snake-3d",
+        Snake3dPlugin,
+        &Script {
+            frames: 150,
+            taps: &[(40, KeyCode::ArrowRight)],
+        },
+    );
+}